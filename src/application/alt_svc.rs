@@ -0,0 +1,51 @@
+use crate::domain::AltService;
+use super::server_timing::{split_unquoted, unquote};
+
+pub fn parse_alt_svc<'a>(values: impl Iterator<Item = &'a str>) -> Vec<AltService> {
+    values.flat_map(split_unquoted(','))
+        .filter_map(|entry| parse_alternative(&entry))
+        .collect()
+}
+
+fn parse_alternative(entry: &str) -> Option<AltService> {
+    let mut params = split_unquoted(';')(entry).into_iter();
+    let (protocol, authority) = params.next()?.split_once('=').map(|(p, a)| (p.trim().to_string(), unquote(a.trim())))?;
+    let (host, port) = authority.rsplit_once(':')?;
+    let port = port.parse::<u16>().ok()?;
+    let host = (!host.is_empty()).then(|| host.trim_start_matches('[').trim_end_matches(']').to_string());
+
+    let max_age = params
+        .filter_map(|p| p.split_once('=').map(|(k, v)| (k.trim().to_ascii_lowercase(), unquote(v.trim()))))
+        .find(|(k, _)| k == "ma")
+        .and_then(|(_, v)| v.parse().ok());
+
+    Some(AltService { protocol, host, port, max_age })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alt(protocol: &str, host: Option<&str>, port: u16, max_age: Option<u64>) -> AltService {
+        AltService { protocol: protocol.to_string(), host: host.map(|h| h.to_string()), port, max_age }
+    }
+
+    #[test]
+    fn parses_alternatives_and_max_age() {
+        let parsed = parse_alt_svc(["h3=\":443\"; ma=86400, h3-29=\":443\"; persist=1"].into_iter());
+        assert_eq!(parsed, vec![alt("h3", None, 443, Some(86400)), alt("h3-29", None, 443, None)]);
+        assert!(parsed[0].is_h3() && !parsed[1].is_h3());
+    }
+
+    #[test]
+    fn parses_hosts_and_multiple_header_values() {
+        let parsed = parse_alt_svc(["h3=\"alt.example.com:8443\"; MA=\"60\"", "h2=\"[2001:db8::1]:443\""].into_iter());
+        assert_eq!(parsed, vec![alt("h3", Some("alt.example.com"), 8443, Some(60)), alt("h2", Some("2001:db8::1"), 443, None)]);
+    }
+
+    #[test]
+    fn skips_clear_and_malformed_entries() {
+        assert!(parse_alt_svc(["clear"].into_iter()).is_empty());
+        assert!(parse_alt_svc(["h3=\"example.com\", h3=\":99999\", h3"].into_iter()).is_empty());
+    }
+}
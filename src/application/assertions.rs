@@ -0,0 +1,167 @@
+use crate::domain::{Assertion, AssertionResult, HeaderMatch, Report};
+use super::config::parse_duration;
+
+pub fn parse_status_assertion(value: &str) -> Result<Assertion, String> {
+    let codes: Vec<String> = value.split(',').map(|c| c.trim().to_ascii_lowercase()).filter(|c| !c.is_empty()).collect();
+    if codes.is_empty() {
+        return Err("expected a status code such as 200, 2xx or 200,204".to_string());
+    }
+    for code in &codes {
+        let valid = code.len() == 3
+            && code.as_bytes()[0].is_ascii_digit()
+            && (code[1..].bytes().all(|b| b.is_ascii_digit()) || &code[1..] == "xx");
+        if !valid {
+            return Err(format!("invalid status pattern '{}'", code));
+        }
+    }
+    Ok(Assertion::Status(codes))
+}
+
+pub fn parse_header_assertion(value: &str) -> Result<Assertion, String> {
+    let split = value.find(['~', '=']);
+    let (name, expect) = match split {
+        Some(idx) if value.as_bytes()[idx] == b'~' => (&value[..idx], HeaderMatch::Contains(value[idx + 1..].to_string())),
+        Some(idx) => (&value[..idx], HeaderMatch::Equals(value[idx + 1..].trim().to_string())),
+        None => (value, HeaderMatch::Present),
+    };
+    let name = name.trim().to_ascii_lowercase();
+    if name.is_empty() {
+        return Err(format!("invalid header expectation '{}', expected name, name=value or name~substring", value));
+    }
+    Ok(Assertion::Header { name, expect })
+}
+
+pub fn parse_duration_ms(value: &str) -> Result<f64, String> {
+    parse_duration(value)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .ok_or_else(|| format!("invalid duration '{}', expected e.g. 300ms or 1s", value))
+}
+
+pub fn evaluate_assertions(assertions: &[Assertion], report: &Report, body: &[u8]) -> Vec<AssertionResult> {
+    assertions.iter().map(|a| {
+        let (passed, actual) = evaluate(a, report, body);
+        AssertionResult { assertion: a.describe(), passed, actual }
+    }).collect()
+}
+
+fn evaluate(assertion: &Assertion, report: &Report, body: &[u8]) -> (bool, String) {
+    match assertion {
+        Assertion::Status(codes) => {
+            let status = report.http.status.to_string();
+            (status_matches(codes, &status), status)
+        }
+        Assertion::Header { name, expect } => {
+            let values: Vec<&str> = report.headers.get_all(name).collect();
+            if values.is_empty() {
+                return (false, "missing".to_string());
+            }
+            (header_matches(expect, &values), values.join(", "))
+        }
+        Assertion::BodyContains(needle) => {
+            let found = !needle.is_empty() && body.windows(needle.len()).any(|w| w == needle.as_bytes());
+            let truncated = report.body.as_ref().is_some_and(|b| b.truncated);
+            let actual = match (found, truncated) {
+                (true, _) => "found".to_string(),
+                (false, true) => format!("body truncated at {} bytes, assertion inconclusive", body.len()),
+                (false, false) => format!("not found in {} bytes", body.len()),
+            };
+            (found, actual)
+        }
+        Assertion::MaxTtfb(max) => (report.timings.ttfb_ms <= *max, format!("{:.1}ms", report.timings.ttfb_ms)),
+        Assertion::MaxTotal(max) => (report.timings.total_ms <= *max, format!("{:.1}ms", report.timings.total_ms)),
+        Assertion::MinCertDays(min) => match report.cert {
+            Some(ref cert) => (cert.days_left >= *min, format!("{} days", cert.days_left)),
+            None => (false, "no certificate".to_string()),
+        },
+    }
+}
+
+fn status_matches(codes: &[String], status: &str) -> bool {
+    codes.iter().any(|c| {
+        if let Some(class) = c.strip_suffix("xx") { status.starts_with(class) } else { c == status }
+    })
+}
+
+fn header_matches(expect: &HeaderMatch, values: &[&str]) -> bool {
+    match expect {
+        HeaderMatch::Present => true,
+        HeaderMatch::Equals(v) => values.iter().any(|h| h.eq_ignore_ascii_case(v)),
+        HeaderMatch::Contains(v) => {
+            let needle = v.to_ascii_lowercase();
+            values.iter().any(|h| h.to_ascii_lowercase().contains(&needle))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_status_codes_and_classes() {
+        let Ok(Assertion::Status(codes)) = parse_status_assertion(" 200, 3XX ,") else { panic!("expected status assertion") };
+        assert_eq!(codes, ["200", "3xx"]);
+        assert!(status_matches(&codes, "200"));
+        assert!(status_matches(&codes, "304"));
+        assert!(!status_matches(&codes, "204"));
+
+        for bad in ["", ",", "20", "2000", "x00", "2x0", "abc"] {
+            assert!(parse_status_assertion(bad).is_err(), "{:?} should be rejected", bad);
+        }
+    }
+
+    #[test]
+    fn parses_header_expectations() {
+        let header = |v| match parse_header_assertion(v) {
+            Ok(Assertion::Header { name, expect }) => (name, expect),
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(header("X-Cache"), ("x-cache".to_string(), HeaderMatch::Present));
+        assert_eq!(header("Content-Type = text/html "), ("content-type".to_string(), HeaderMatch::Equals("text/html".to_string())));
+        // The first separator wins, so values may themselves contain '=' or '~'.
+        assert_eq!(header("Link~rel=preload"), ("link".to_string(), HeaderMatch::Contains("rel=preload".to_string())));
+        assert_eq!(header("a=b~c"), ("a".to_string(), HeaderMatch::Equals("b~c".to_string())));
+        assert!(parse_header_assertion("=value").is_err());
+        assert!(parse_header_assertion(" ~x").is_err());
+    }
+
+    #[test]
+    fn header_matching_is_case_insensitive_across_values() {
+        let values = ["max-age=60", "Public"];
+        assert!(header_matches(&HeaderMatch::Present, &values));
+        assert!(header_matches(&HeaderMatch::Equals("public".to_string()), &values));
+        assert!(!header_matches(&HeaderMatch::Equals("max-age".to_string()), &values));
+        assert!(header_matches(&HeaderMatch::Contains("MAX-AGE".to_string()), &values));
+        assert!(!header_matches(&HeaderMatch::Contains("private".to_string()), &values));
+    }
+
+    #[test]
+    fn body_assertion_is_inconclusive_on_a_truncated_body() {
+        let assertions = [Assertion::BodyContains("needle".to_string())];
+        let mut report = Report::fixture();
+        let body = b"hay hay needle";
+        report.body = Some(crate::application::inspect_body(body, None, false, false, false));
+        assert_eq!(evaluate_assertions(&assertions, &report, body)[0].actual, "found");
+        assert_eq!(evaluate_assertions(&assertions, &report, b"hay hay")[0].actual, "not found in 7 bytes");
+
+        report.body = Some(crate::application::inspect_body(b"hay hay", None, true, false, false));
+        let result = &evaluate_assertions(&assertions, &report, b"hay hay")[0];
+        assert!(!result.passed);
+        assert_eq!(result.actual, "body truncated at 7 bytes, assertion inconclusive");
+    }
+
+    #[test]
+    fn body_assertion_lifts_the_preview_limit() {
+        let config = crate::application::Config::default();
+        assert_eq!(config.effective_body_limit(), config.body_limit);
+        let config = config.with_assertion(Assertion::BodyContains("x".to_string()));
+        assert_eq!(config.effective_body_limit(), usize::MAX);
+    }
+
+    #[test]
+    fn parses_durations_in_milliseconds() {
+        assert_eq!(parse_duration_ms("300ms"), Ok(300.0));
+        assert_eq!(parse_duration_ms("2s"), Ok(2000.0));
+        assert!(parse_duration_ms("fast").is_err());
+    }
+}
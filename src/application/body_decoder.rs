@@ -0,0 +1,118 @@
+use std::io::Read;
+use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+use crate::domain::BodyEncoding;
+
+pub const SUPPORTED_ENCODINGS: [&str; 3] = ["gzip", "br", "zstd"];
+
+pub const DECODED_LIMIT: usize = 64 * 1024 * 1024;
+
+pub struct DecodedBody {
+    pub bytes: Vec<u8>,
+    pub summary: BodyEncoding,
+}
+
+pub fn accept_encoding_header() -> String {
+    SUPPORTED_ENCODINGS.join(", ")
+}
+
+pub fn decode_body(content_encoding: &str, wire: &[u8], truncated: bool) -> DecodedBody {
+    let codings: Vec<String> = content_encoding.split(',')
+        .map(|c| c.trim().to_ascii_lowercase())
+        .filter(|c| !c.is_empty() && c != "identity")
+        .collect();
+
+    let mut bytes = wire.to_vec();
+    let mut error = None;
+    let mut limit_hit = false;
+
+    for coding in codings.iter().rev() {
+        match decode_one(coding, &bytes, truncated) {
+            Ok((out, hit)) => {
+                bytes = out;
+                limit_hit |= hit;
+            }
+            Err(e) => {
+                error = Some(e);
+                bytes.clear();
+                break;
+            }
+        }
+    }
+
+    let mut summary = BodyEncoding::new(codings.join(", "), wire.len(), bytes.len(), truncated, error);
+    summary.decode_limit_hit = limit_hit;
+    DecodedBody { bytes, summary }
+}
+
+fn decode_one(coding: &str, data: &[u8], truncated: bool) -> Result<(Vec<u8>, bool), String> {
+    match coding {
+        "gzip" | "x-gzip" => read_all(MultiGzDecoder::new(data), truncated),
+        "deflate" => read_all(ZlibDecoder::new(data), truncated)
+            .or_else(|_| read_all(DeflateDecoder::new(data), truncated)),
+        "br" => read_all(brotli::Decompressor::new(data, 8192), truncated),
+        "zstd" => {
+            let decoder = zstd::stream::read::Decoder::new(data).map_err(|e| format!("zstd: {}", e))?;
+            read_all(decoder, truncated)
+        }
+        other => Err(format!("unsupported content-encoding '{}'", other)),
+    }
+}
+
+/// Returns the decoded bytes and whether decoding stopped at `DECODED_LIMIT`.
+fn read_all<R: Read>(mut reader: R, truncated: bool) -> Result<(Vec<u8>, bool), String> {
+    let mut out = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok((out, false)),
+            Ok(n) => {
+                out.extend_from_slice(&buf[..n]);
+                if out.len() > DECODED_LIMIT {
+                    out.truncate(DECODED_LIMIT);
+                    return Ok((out, true));
+                }
+            }
+            Err(_) if truncated && !out.is_empty() => return Ok((out, false)),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use flate2::{write::GzEncoder, Compression};
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn decodes_gzip() {
+        let decoded = decode_body("gzip", &gzip(b"hello world"), false);
+        assert_eq!(decoded.bytes, b"hello world");
+        assert_eq!((decoded.summary.decoded_bytes, decoded.summary.decode_limit_hit), (11, false));
+        assert!(decoded.summary.error.is_none());
+    }
+
+    #[test]
+    fn flags_bodies_past_the_decode_limit() {
+        let decoded = decode_body("gzip", &gzip(&vec![0u8; DECODED_LIMIT + 1]), false);
+        assert_eq!(decoded.bytes.len(), DECODED_LIMIT);
+        assert!(decoded.summary.decode_limit_hit);
+
+        let decoded = decode_body("gzip", &gzip(&vec![0u8; DECODED_LIMIT]), false);
+        assert_eq!(decoded.bytes.len(), DECODED_LIMIT);
+        assert!(!decoded.summary.decode_limit_hit);
+    }
+
+    #[test]
+    fn reports_unsupported_codings() {
+        let decoded = decode_body("compress", b"abc", false);
+        assert!(decoded.bytes.is_empty());
+        assert!(decoded.summary.error.unwrap().contains("compress"));
+    }
+}
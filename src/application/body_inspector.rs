@@ -0,0 +1,149 @@
+use std::fmt::Write;
+use crate::domain::{BodyKind, BodySummary};
+use super::cert_parser::sha256;
+
+const PREVIEW_CHARS: usize = 512;
+const PREVIEW_LINES: usize = 12;
+
+pub fn inspect_body(bytes: &[u8], content_type: Option<&str>, truncated: bool, with_preview: bool, with_json: bool) -> BodySummary {
+    let kind = sniff_kind(bytes, content_type);
+
+    // A hash of the preview prefix would look like the body's hash, so leave it out.
+    let digest = (!truncated).then(|| {
+        let mut digest = String::with_capacity(64);
+        for byte in sha256(bytes) {
+            let _ = write!(&mut digest, "{:02x}", byte);
+        }
+        digest
+    });
+
+    let preview = (with_preview && kind.is_text()).then(|| text_preview(bytes));
+    let json = if with_json && kind == BodyKind::Json {
+        serde_json::from_slice(bytes).ok()
+    } else {
+        None
+    };
+
+    BodySummary {
+        length: bytes.len(),
+        sha256: digest,
+        kind,
+        content_type: content_type.map(|s| s.to_string()),
+        truncated,
+        preview,
+        json,
+        saved_to: None,
+    }
+}
+
+fn sniff_kind(bytes: &[u8], content_type: Option<&str>) -> BodyKind {
+    let trimmed = trim_leading(bytes);
+    if trimmed.is_empty() {
+        return BodyKind::Empty;
+    }
+
+    let head = String::from_utf8_lossy(&trimmed[..trimmed.len().min(512)]).to_ascii_lowercase();
+    if head.starts_with("<!doctype html") || head.starts_with("<html") || head.contains("<head") || head.contains("<body") {
+        return BodyKind::Html;
+    }
+    if head.starts_with("<?xml") || (head.starts_with('<') && content_type.map(|ct| ct.contains("xml")).unwrap_or(false)) {
+        return BodyKind::Xml;
+    }
+    if (head.starts_with('{') || head.starts_with('['))
+        && (serde_json::from_slice::<serde_json::Value>(trimmed).is_ok() || content_type.map(|ct| ct.contains("json")).unwrap_or(false)) {
+        return BodyKind::Json;
+    }
+
+    if looks_like_text(bytes) { BodyKind::Text } else { BodyKind::Binary }
+}
+
+fn trim_leading(bytes: &[u8]) -> &[u8] {
+    let bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+fn looks_like_text(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(4096)];
+    let text = match std::str::from_utf8(sample) {
+        Ok(t) => t,
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&sample[..e.valid_up_to()]).unwrap_or(""),
+        Err(_) => return false,
+    };
+    !text.chars().any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+}
+
+fn text_preview(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let mut out = String::new();
+    for (i, line) in text.lines().enumerate() {
+        if i >= PREVIEW_LINES || out.len() >= PREVIEW_CHARS { break; }
+        if i > 0 { out.push('\n'); }
+        let room = PREVIEW_CHARS - out.len();
+        match line.char_indices().nth(room) {
+            Some((idx, _)) => out.push_str(&line[..idx]),
+            None => out.push_str(line),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniffs_kind_from_content_and_content_type() {
+        assert_eq!(sniff_kind(b"  \r\n", None), BodyKind::Empty);
+        assert_eq!(sniff_kind(b"\xef\xbb\xbf<!DOCTYPE html><title>x</title>", None), BodyKind::Html);
+        assert_eq!(sniff_kind(b"<div><body>", Some("text/plain")), BodyKind::Html);
+        assert_eq!(sniff_kind(b"<?xml version=\"1.0\"?><a/>", None), BodyKind::Xml);
+        assert_eq!(sniff_kind(b"<feed/>", Some("application/atom+xml")), BodyKind::Xml);
+        assert_eq!(sniff_kind(b"<feed/>", None), BodyKind::Text);
+        assert_eq!(sniff_kind(b"\n{\"ok\": true}", None), BodyKind::Json);
+        // Cut-off JSON is still JSON when the server says so.
+        assert_eq!(sniff_kind(b"{\"items\": [1, 2", Some("application/json")), BodyKind::Json);
+        assert_eq!(sniff_kind(b"{not json", None), BodyKind::Text);
+        assert_eq!(sniff_kind("caf\u{e9}\ttab".as_bytes(), None), BodyKind::Text);
+        assert_eq!(sniff_kind(b"\x89PNG\r\n\x1a\n\0\0", None), BodyKind::Binary);
+    }
+
+    #[test]
+    fn preview_is_limited_to_text_bodies_lines_and_chars() {
+        let lines: String = (0..20).map(|i| format!("line {}\n", i)).collect();
+        let body = inspect_body(lines.as_bytes(), Some("text/plain"), false, true, false);
+        let preview = body.preview.unwrap();
+        assert_eq!(preview.lines().count(), PREVIEW_LINES);
+        assert!(preview.ends_with("line 11"));
+
+        let long = "é".repeat(PREVIEW_CHARS * 2);
+        let preview = text_preview(long.as_bytes());
+        assert_eq!(preview.chars().count(), PREVIEW_CHARS);
+
+        assert_eq!(inspect_body(lines.as_bytes(), None, false, false, false).preview, None);
+        assert_eq!(inspect_body(b"\0\x01\x02", None, false, true, false).preview, None);
+    }
+
+    #[test]
+    fn json_is_parsed_only_when_valid_and_requested() {
+        let body = inspect_body(b"{\"a\": [1, 2]}", Some("application/json"), false, false, true);
+        assert_eq!(body.kind, BodyKind::Json);
+        assert_eq!(body.json, Some(serde_json::json!({"a": [1, 2]})));
+
+        assert_eq!(inspect_body(b"{\"a\": [1, 2]}", None, false, false, false).json, None);
+        let broken = inspect_body(b"{\"a\": [1,", Some("application/json"), true, false, true);
+        assert_eq!((broken.kind, broken.json), (BodyKind::Json, None));
+    }
+
+    #[test]
+    fn hash_only_covers_complete_bodies() {
+        let body = inspect_body(b"abc", Some("text/plain"), false, false, false);
+        assert_eq!(body.length, 3);
+        assert_eq!(body.sha256.as_deref(), Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+        assert_eq!(body.content_type.as_deref(), Some("text/plain"));
+
+        let prefix = inspect_body(b"abc", Some("text/plain"), true, false, false);
+        assert!(prefix.truncated);
+        assert_eq!((prefix.length, prefix.sha256), (3, None));
+    }
+}
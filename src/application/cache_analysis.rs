@@ -0,0 +1,213 @@
+use chrono::DateTime;
+use crate::domain::{CacheSummary, HeaderList};
+
+const CACHE_STATUS_WORDS: [&str; 9] = ["REVALIDATED", "EXPIRED", "UPDATING", "BYPASS", "DYNAMIC", "STALE", "REFRESH", "MISS", "HIT"];
+
+pub fn analyze_cache(headers: &HeaderList, status: u16) -> CacheSummary {
+    let cache_control = headers.get_all("cache-control").collect::<Vec<_>>().join(", ");
+    let directives: Vec<(String, Option<String>)> = cache_control.split(',')
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| match d.split_once('=') {
+            Some((k, v)) => (k.trim().to_ascii_lowercase(), Some(v.trim().trim_matches('"').to_string())),
+            None => (d.trim().to_ascii_lowercase(), None),
+        })
+        .collect();
+    let has = |name: &str| directives.iter().any(|(k, _)| k == name);
+    let seconds = |name: &str| directives.iter().find(|(k, _)| k == name).and_then(|(_, v)| v.as_deref()?.parse::<u64>().ok());
+
+    let age_s = headers.get("age").and_then(|v| v.trim().parse::<u64>().ok());
+    let date = headers.get("date").and_then(parse_http_date);
+    let expires = headers.get("expires").and_then(parse_http_date);
+    let last_modified = headers.get("last-modified").map(|s| s.to_string());
+
+    let (freshness_lifetime_s, freshness_source) = if let Some(s) = seconds("s-maxage") {
+        (Some(s), Some("s-maxage"))
+    } else if let Some(s) = seconds("max-age") {
+        (Some(s), Some("max-age"))
+    } else if let (Some(exp), Some(date)) = (expires, date) {
+        (Some((exp - date).max(0) as u64), Some("expires"))
+    } else if let (Some(lm), Some(date)) = (last_modified.as_deref().and_then(parse_http_date), date)
+        && is_heuristically_cacheable(status) {
+        (Some(((date - lm).max(0) / 10) as u64), Some("heuristic"))
+    } else {
+        (None, None)
+    };
+
+    let cacheable = !has("no-store") && !has("private") && (freshness_lifetime_s.unwrap_or(0) > 0 || has("no-cache"));
+    let ttl_s = if has("no-store") || has("no-cache") {
+        Some(0)
+    } else {
+        freshness_lifetime_s.map(|f| f as i64 - age_s.unwrap_or(0) as i64)
+    };
+
+    let (cdn, pop) = detect_cdn(headers);
+    let (status, status_source) = detect_cache_status(headers);
+
+    CacheSummary {
+        cacheable,
+        cache_control: (!cache_control.is_empty()).then_some(cache_control),
+        cdn,
+        status,
+        status_source,
+        pop,
+        age_s,
+        freshness_lifetime_s,
+        freshness_source: freshness_source.map(|s| s.to_string()),
+        ttl_s,
+        etag: headers.get("etag").map(|s| s.to_string()),
+        last_modified,
+        vary: headers.get("vary").map(|s| s.to_string()),
+        via: headers.get("via").map(|s| s.to_string()),
+        revalidation: None,
+    }
+}
+
+fn parse_http_date(value: &str) -> Option<i64> {
+    DateTime::parse_from_rfc2822(value.trim()).ok().map(|d| d.timestamp())
+}
+
+fn is_heuristically_cacheable(status: u16) -> bool {
+    matches!(status, 200 | 203 | 204 | 206 | 300 | 301 | 308 | 404 | 405 | 410 | 414 | 501)
+}
+
+fn detect_cdn(headers: &HeaderList) -> (Option<String>, Option<String>) {
+    if headers.contains("cf-cache-status") || headers.contains("cf-ray") {
+        let pop = headers.get("cf-ray").and_then(|r| r.rsplit('-').next()).map(|s| s.to_string());
+        return (Some("Cloudflare".to_string()), pop);
+    }
+    if let Some(pop) = headers.get("x-amz-cf-pop") {
+        return (Some("CloudFront".to_string()), Some(pop.to_string()));
+    }
+    if headers.iter().any(|(n, _)| n.starts_with("fastly-")) || headers.get("x-served-by").map(|v| v.starts_with("cache-")).unwrap_or(false) {
+        let pop = headers.get("x-served-by")
+            .and_then(|v| v.split(',').next_back())
+            .and_then(|v| v.trim().rsplit('-').next())
+            .map(|s| s.to_string());
+        return (Some("Fastly".to_string()), pop);
+    }
+    if headers.iter().any(|(n, _)| n.starts_with("x-akamai-")) || headers.get("server").map(|s| s.contains("AkamaiGHost")).unwrap_or(false) {
+        return (Some("Akamai".to_string()), None);
+    }
+    if let Some(via) = headers.get("via") {
+        let lower = via.to_ascii_lowercase();
+        if lower.contains("cloudfront") { return (Some("CloudFront".to_string()), None); }
+        if lower.contains("varnish") { return (Some("Varnish".to_string()), None); }
+        if lower.contains("google") { return (Some("Google".to_string()), None); }
+    }
+    if let Some(served_by) = headers.get("x-served-by") {
+        return (None, Some(served_by.to_string()));
+    }
+    (None, None)
+}
+
+fn detect_cache_status(headers: &HeaderList) -> (Option<String>, Option<String>) {
+    for name in ["cf-cache-status", "x-cache", "x-cache-status", "x-proxy-cache", "cdn-cache-control"] {
+        if let Some(word) = headers.get(name).and_then(cache_status_word) {
+            return (Some(word), Some(name.to_string()));
+        }
+    }
+    if let Some(value) = headers.get_all("server-timing").flat_map(|v| v.split(',')).find(|m| m.trim_start().starts_with("cdn-cache"))
+        && let Some(word) = cache_status_word(value) {
+        return (Some(word), Some("server-timing".to_string()));
+    }
+    (None, None)
+}
+
+fn cache_status_word(value: &str) -> Option<String> {
+    let upper = value.to_ascii_uppercase();
+    let first = upper.split(',').next().unwrap_or("");
+    CACHE_STATUS_WORDS.iter().find(|w| first.contains(*w)).map(|w| w.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_max_age_wins_and_age_reduces_ttl() {
+        let cache = analyze_cache(&HeaderList::from_pairs(&[
+            ("Cache-Control", "public, max-age=60"),
+            ("Cache-Control", "s-maxage=\"300\""),
+            ("Age", "100"),
+        ]), 200);
+        assert!(cache.cacheable);
+        assert_eq!(cache.cache_control.as_deref(), Some("public, max-age=60, s-maxage=\"300\""));
+        assert_eq!((cache.freshness_lifetime_s, cache.freshness_source.as_deref()), (Some(300), Some("s-maxage")));
+        assert_eq!((cache.age_s, cache.ttl_s), (Some(100), Some(200)));
+
+        let stale = analyze_cache(&HeaderList::from_pairs(&[("Cache-Control", "max-age=10"), ("Age", "25")]), 200);
+        assert_eq!(stale.ttl_s, Some(-15));
+    }
+
+    #[test]
+    fn no_store_private_and_no_cache() {
+        let no_store = analyze_cache(&HeaderList::from_pairs(&[("Cache-Control", "no-store, max-age=600")]), 200);
+        assert!(!no_store.cacheable);
+        assert_eq!(no_store.ttl_s, Some(0));
+
+        let private = analyze_cache(&HeaderList::from_pairs(&[("Cache-Control", "Private, max-age=600")]), 200);
+        assert!(!private.cacheable);
+        assert_eq!(private.ttl_s, Some(600));
+
+        // no-cache responses may be stored but must be revalidated before every use.
+        let no_cache = analyze_cache(&HeaderList::from_pairs(&[("Cache-Control", "no-cache")]), 200);
+        assert!(no_cache.cacheable);
+        assert_eq!(no_cache.ttl_s, Some(0));
+
+        let none = analyze_cache(&HeaderList::new(), 200);
+        assert!(none.is_empty());
+        assert!(!analyze_cache(&HeaderList::from_pairs(&[("ETag", "\"v1\"")]), 200).is_empty());
+        assert_eq!((none.cache_control, none.freshness_lifetime_s, none.ttl_s), (None, None, None));
+    }
+
+    #[test]
+    fn expires_and_heuristic_freshness() {
+        let date = ("Date", "Tue, 15 Nov 1994 08:12:31 GMT");
+        let expires = analyze_cache(&HeaderList::from_pairs(&[date, ("Expires", "Tue, 15 Nov 1994 09:12:31 GMT")]), 200);
+        assert_eq!((expires.freshness_lifetime_s, expires.freshness_source.as_deref()), (Some(3600), Some("expires")));
+
+        let past = analyze_cache(&HeaderList::from_pairs(&[date, ("Expires", "0")]), 200);
+        assert_eq!(past.freshness_lifetime_s, None);
+
+        let modified = ("Last-Modified", "Sat, 05 Nov 1994 08:12:31 GMT");
+        let heuristic = analyze_cache(&HeaderList::from_pairs(&[date, modified]), 200);
+        assert_eq!((heuristic.freshness_lifetime_s, heuristic.freshness_source.as_deref()), (Some(86_400), Some("heuristic")));
+        assert!(heuristic.cacheable);
+
+        let error = analyze_cache(&HeaderList::from_pairs(&[date, modified]), 500);
+        assert_eq!((error.freshness_lifetime_s, error.freshness_source), (None, None));
+        assert!(!error.cacheable);
+    }
+
+    #[test]
+    fn detects_cdn_and_pop() {
+        let cdn = |pairs: &[(&str, &str)]| {
+            let cache = analyze_cache(&HeaderList::from_pairs(pairs), 200);
+            (cache.cdn, cache.pop)
+        };
+        let some = |cdn: &str, pop: Option<&str>| (Some(cdn.to_string()), pop.map(str::to_string));
+        assert_eq!(cdn(&[("CF-Ray", "8a1b2c3d4e5f-FRA")]), some("Cloudflare", Some("FRA")));
+        assert_eq!(cdn(&[("X-Amz-Cf-Pop", "IAD89-C1")]), some("CloudFront", Some("IAD89-C1")));
+        assert_eq!(cdn(&[("X-Served-By", "cache-iad-kiad7000-IAD, cache-fra-etou8220-FRA")]), some("Fastly", Some("FRA")));
+        assert_eq!(cdn(&[("Server", "AkamaiGHost")]), some("Akamai", None));
+        assert_eq!(cdn(&[("Via", "1.1 varnish (Varnish/7.1)")]), some("Varnish", None));
+        assert_eq!(cdn(&[("X-Served-By", "web-01")]), (None, Some("web-01".to_string())));
+        assert_eq!(cdn(&[("Server", "nginx")]), (None, None));
+    }
+
+    #[test]
+    fn detects_cache_status_from_headers_and_server_timing() {
+        let status = |pairs: &[(&str, &str)]| {
+            let cache = analyze_cache(&HeaderList::from_pairs(pairs), 200);
+            (cache.status, cache.status_source)
+        };
+        let some = |word: &str, source: &str| (Some(word.to_string()), Some(source.to_string()));
+        assert_eq!(status(&[("X-Cache", "Hit from cloudfront")]), some("HIT", "x-cache"));
+        // Only the first (outermost) cache layer counts.
+        assert_eq!(status(&[("X-Cache", "MISS, HIT")]), some("MISS", "x-cache"));
+        assert_eq!(status(&[("X-Cache", "TCP_REFRESH_MISS")]), some("REFRESH", "x-cache"));
+        assert_eq!(status(&[("X-Cache", "unknown"), ("CF-Cache-Status", "DYNAMIC")]), some("DYNAMIC", "cf-cache-status"));
+        assert_eq!(status(&[("Server-Timing", "db;dur=5, cdn-cache; desc=HIT")]), some("HIT", "server-timing"));
+        assert_eq!(status(&[("Server-Timing", "app;desc=hit")]), (None, None));
+    }
+}
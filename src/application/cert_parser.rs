@@ -59,7 +59,7 @@ fn compute_sha256_fingerprint(der: &[u8]) -> String {
     result
 }
 
-fn sha256(data: &[u8]) -> [u8; 32] {
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
     use ring::digest::{Context, SHA256};
     let mut ctx = Context::new(&SHA256);
     ctx.update(data);
@@ -1,11 +1,49 @@
+use std::net::{IpAddr, SocketAddr};
 use std::time::Duration;
+use crate::domain::{Assertion, NoProxy, ProxyProtocolSpec, ProxySpec, StartTlsProtocol, TcpOptions, UdocError, WarningRules, WebSocketOptions};
 
+#[derive(Clone)]
 pub struct Config {
     pub timeout: Duration,
     pub max_redirects: usize,
     pub body_limit: usize,
     pub repeat: usize,
     pub json_output: bool,
+    pub compressed: bool,
+    pub probe_encodings: bool,
+    pub output: Option<String>,
+    pub show_body: bool,
+    pub body_json: bool,
+    pub assertions: Vec<Assertion>,
+    pub warning_rules: WarningRules,
+    pub fail_on_warning: bool,
+    pub revalidate: bool,
+    pub http2_prior_knowledge: bool,
+    pub h2c_upgrade: bool,
+    pub alpn: Vec<String>,
+    pub compare_http_versions: bool,
+    pub http3: bool,
+    pub follow_alt_svc: bool,
+    pub h2_stream_window: u32,
+    pub h2_connection_window: u32,
+    pub h2_max_frame_size: u32,
+    pub no_reuse: bool,
+    pub requests: usize,
+    pub h2_streams: usize,
+    pub h2_paths: Vec<String>,
+    pub proxy: Option<ProxySpec>,
+    pub no_proxy: NoProxy,
+    pub unix_socket: Option<String>,
+    pub local_addr: Option<SocketAddr>,
+    pub interface: Option<String>,
+    pub tcp_options: TcpOptions,
+    pub proxy_protocol: Option<ProxyProtocolSpec>,
+    pub tcping_count: usize,
+    pub tcping_interval: Duration,
+    pub tcping_tls: bool,
+    pub starttls: Option<StartTlsProtocol>,
+    pub websocket: WebSocketOptions,
+    pub grpc_health: Option<String>,
 }
 
 impl Config {
@@ -16,6 +54,41 @@ impl Config {
             body_limit: parse_usize_env("UDOC_BODY_LIMIT", 32 * 1024),
             repeat: parse_usize_env("UDOC_REPEAT", 1),
             json_output: false,
+            compressed: false,
+            probe_encodings: false,
+            output: None,
+            show_body: false,
+            body_json: false,
+            assertions: Vec::new(),
+            warning_rules: WarningRules::default(),
+            fail_on_warning: false,
+            revalidate: false,
+            http2_prior_knowledge: false,
+            h2c_upgrade: false,
+            alpn: vec!["h2".to_string(), "http/1.1".to_string()],
+            compare_http_versions: false,
+            http3: false,
+            follow_alt_svc: false,
+            h2_stream_window: 65535,
+            h2_connection_window: 65535,
+            h2_max_frame_size: 16384,
+            no_reuse: false,
+            requests: 1,
+            h2_streams: 0,
+            h2_paths: Vec::new(),
+            proxy: None,
+            no_proxy: NoProxy::default(),
+            unix_socket: None,
+            local_addr: None,
+            interface: None,
+            tcp_options: TcpOptions::default(),
+            proxy_protocol: None,
+            tcping_count: 5,
+            tcping_interval: Duration::from_secs(1),
+            tcping_tls: false,
+            starttls: None,
+            websocket: WebSocketOptions::default(),
+            grpc_health: None,
         }
     }
 
@@ -23,6 +96,188 @@ impl Config {
         self.json_output = json;
         self
     }
+
+    pub fn with_compressed(mut self, compressed: bool) -> Self {
+        self.compressed = compressed;
+        self
+    }
+
+    pub fn with_probe_encodings(mut self, probe: bool) -> Self {
+        self.probe_encodings = probe;
+        self
+    }
+
+    pub fn with_output(mut self, path: Option<String>) -> Self {
+        self.output = path;
+        self
+    }
+
+    pub fn with_show_body(mut self, show: bool) -> Self {
+        self.show_body = show;
+        self
+    }
+
+    pub fn with_body_json(mut self, body_json: bool) -> Self {
+        self.body_json = body_json;
+        self
+    }
+
+    pub fn with_assertion(mut self, assertion: Assertion) -> Self {
+        self.assertions.push(assertion);
+        self
+    }
+
+    pub fn with_warn_cert_days(mut self, days: i64) -> Self {
+        self.warning_rules.cert_expiry_days = days;
+        self
+    }
+
+    pub fn with_fail_on_warning(mut self, fail: bool) -> Self {
+        self.fail_on_warning = fail;
+        self
+    }
+
+    pub fn with_revalidate(mut self, revalidate: bool) -> Self {
+        self.revalidate = revalidate;
+        self
+    }
+
+    pub fn with_http2_prior_knowledge(mut self, enabled: bool) -> Self {
+        self.http2_prior_knowledge = enabled;
+        self
+    }
+
+    pub fn with_h2c_upgrade(mut self, enabled: bool) -> Self {
+        self.h2c_upgrade = enabled;
+        self
+    }
+
+    pub fn with_alpn(mut self, alpn: Vec<String>) -> Self {
+        self.alpn = alpn;
+        self
+    }
+
+    pub fn with_compare_http_versions(mut self, compare: bool) -> Self {
+        self.compare_http_versions = compare;
+        self
+    }
+
+    pub fn with_http3(mut self, http3: bool) -> Self {
+        self.http3 = http3;
+        self
+    }
+
+    pub fn with_follow_alt_svc(mut self, follow: bool) -> Self {
+        self.follow_alt_svc = follow;
+        self
+    }
+
+    pub fn with_h2_stream_window(mut self, size: u32) -> Self {
+        self.h2_stream_window = size;
+        self
+    }
+
+    pub fn with_h2_connection_window(mut self, size: u32) -> Self {
+        self.h2_connection_window = size;
+        self
+    }
+
+    pub fn with_h2_max_frame_size(mut self, size: u32) -> Self {
+        self.h2_max_frame_size = size;
+        self
+    }
+
+    pub fn with_no_reuse(mut self, no_reuse: bool) -> Self {
+        self.no_reuse = no_reuse;
+        self
+    }
+
+    pub fn with_requests(mut self, requests: usize) -> Self {
+        self.requests = requests;
+        self
+    }
+
+    pub fn with_h2_streams(mut self, streams: usize) -> Self {
+        self.h2_streams = streams;
+        self
+    }
+
+    pub fn with_h2_path(mut self, path: String) -> Self {
+        self.h2_paths.push(path);
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy: Option<ProxySpec>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    pub fn with_no_proxy(mut self, no_proxy: NoProxy) -> Self {
+        self.no_proxy = no_proxy;
+        self
+    }
+
+    pub fn with_unix_socket(mut self, path: Option<String>) -> Self {
+        self.unix_socket = path;
+        self
+    }
+
+    pub fn with_local_addr(mut self, local_addr: Option<SocketAddr>) -> Self {
+        self.local_addr = local_addr;
+        self
+    }
+
+    pub fn with_interface(mut self, interface: Option<String>) -> Self {
+        self.interface = interface;
+        self
+    }
+
+    pub fn with_tcp_options(mut self, options: TcpOptions) -> Self {
+        self.tcp_options = options;
+        self
+    }
+
+    pub fn with_proxy_protocol(mut self, spec: Option<ProxyProtocolSpec>) -> Self {
+        self.proxy_protocol = spec;
+        self
+    }
+
+    pub fn with_tcping(mut self, count: usize, interval: Duration, tls: bool) -> Self {
+        self.tcping_count = count;
+        self.tcping_interval = interval;
+        self.tcping_tls = tls;
+        self
+    }
+
+    pub fn with_starttls(mut self, protocol: Option<StartTlsProtocol>) -> Self {
+        self.starttls = protocol;
+        self
+    }
+
+    pub fn with_websocket(mut self, options: WebSocketOptions) -> Self {
+        self.websocket = options;
+        self
+    }
+
+    pub fn with_grpc_health(mut self, service: Option<String>) -> Self {
+        self.grpc_health = service;
+        self
+    }
+
+    pub fn effective_body_limit(&self) -> usize {
+        let needs_full_body = self.output.is_some()
+            || self.assertions.iter().any(|a| matches!(a, Assertion::BodyContains(_)));
+        if needs_full_body { usize::MAX } else { self.body_limit }
+    }
+
+    /// The first resolved address, preferring the family of `--local-addr` so the bind can succeed.
+    pub fn select_ip(&self, host: &str, ips: &[IpAddr]) -> Result<IpAddr, UdocError> {
+        self.local_addr
+            .and_then(|local| ips.iter().find(|ip| ip.is_ipv4() == local.is_ipv4()))
+            .or(ips.first())
+            .copied()
+            .ok_or_else(|| UdocError::dns(format!("no IP addresses for {}", host)))
+    }
 }
 
 impl Default for Config {
@@ -31,17 +286,21 @@ impl Default for Config {
     }
 }
 
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let v = value.trim();
+    let (num, scale) = if let Some(s) = v.strip_suffix("ms") {
+        (s, 0.001)
+    } else if let Some(s) = v.strip_suffix('s') {
+        (s, 1.0)
+    } else {
+        (v, 1.0)
+    };
+    let secs = num.trim().parse::<f64>().ok()? * scale;
+    (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs))
+}
+
 fn parse_duration_env(key: &str, default: Duration) -> Duration {
-    std::env::var(key).ok().and_then(|v| {
-        let v = v.trim();
-        if let Some(s) = v.strip_suffix("ms") {
-            s.parse::<u64>().ok().map(Duration::from_millis)
-        } else if let Some(s) = v.strip_suffix('s') {
-            s.parse::<u64>().ok().map(Duration::from_secs)
-        } else {
-            v.parse::<u64>().ok().map(Duration::from_secs)
-        }
-    }).unwrap_or(default)
+    std::env::var(key).ok().and_then(|v| parse_duration(&v)).unwrap_or(default)
 }
 
 fn parse_usize_env(key: &str, default: usize) -> usize {
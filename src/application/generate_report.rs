@@ -1,35 +1,76 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Mutex;
 use std::time::Instant;
 use crate::domain::*;
 use crate::ports::*;
-use super::{ParsedUrl, Config, parse_certificate};
+use super::{ParsedUrl, Config, parse_certificate, decode_body, accept_encoding_header, inspect_body, evaluate_assertions, audit_security_headers, analyze_cache, parse_server_timing, parse_alt_svc, grpc_health_request, parse_grpc_health, SUPPORTED_ENCODINGS};
 
-pub struct GenerateReportUseCase<D, T, L, H, C>
+pub struct GenerateReportUseCase<D, T, L, H, Q, C>
 where
     D: DnsResolver,
     T: TcpDialer,
     L: TlsHandshaker,
     H: HttpClient,
+    Q: QuicClient,
     C: Clock,
 {
     dns: D,
     tcp: T,
     tls: L,
     http: H,
+    quic: Q,
     clock: C,
     config: Config,
+    pool: Mutex<HashMap<PoolKey, PooledEntry>>,
 }
 
-impl<D, T, L, H, C> GenerateReportUseCase<D, T, L, H, C>
+type PoolKey = (String, String, u16);
+
+struct PooledEntry {
+    connection: PooledConnection,
+    endpoint: Endpoint,
+}
+
+#[derive(Clone)]
+struct Endpoint {
+    resolved: ResolvedTarget,
+    tls: Option<TlsSummary>,
+    cert: Option<CertSummary>,
+    local_addr: Option<SocketAddr>,
+    tcp_stats: Option<TcpStats>,
+    tcp_probe: Option<SharedTcpInfoProbe>,
+}
+
+impl Endpoint {
+    fn tcp_stats(&self) -> Option<TcpStats> {
+        self.tcp_stats.clone().map(|stats| TcpStats {
+            after_response: self.tcp_probe.as_ref().and_then(|p| p.sample()),
+            ..stats
+        })
+    }
+}
+
+struct Exchange {
+    resolved: ResolvedTarget,
+    hop: HopTiming,
+    response: HttpResponse,
+    tls: Option<TlsSummary>,
+    cert: Option<CertSummary>,
+    quic: Option<QuicSummary>,
+}
+
+impl<D, T, L, H, Q, C> GenerateReportUseCase<D, T, L, H, Q, C>
 where
     D: DnsResolver,
     T: TcpDialer,
     L: TlsHandshaker,
     H: HttpClient,
+    Q: QuicClient,
     C: Clock,
 {
-    pub fn new(dns: D, tcp: T, tls: L, http: H, clock: C, config: Config) -> Self {
-        Self { dns, tcp, tls, http, clock, config }
+    pub fn new(dns: D, tcp: T, tls: L, http: H, quic: Q, clock: C, config: Config) -> Self {
+        Self { dns, tcp, tls, http, quic, clock, config, pool: Mutex::new(HashMap::new()) }
     }
 
     pub async fn execute(&self, input_url: &str) -> Result<Report, UdocError> {
@@ -39,70 +80,51 @@ where
         let mut visited: HashSet<String> = HashSet::new();
         let mut hop_timings: Vec<HopTiming> = Vec::new();
 
-        let mut total_dns_ms = 0.0;
-        let mut total_tcp_ms = 0.0;
-        let mut total_tls_ms: Option<f64> = None;
-        let mut final_ttfb_ms = 0.0;
+        let mut final_exchange = ExchangeTiming::default();
 
         let mut final_http: Option<HttpSummary> = None;
+        let mut final_headers = HeaderList::new();
+        let mut server_timing: Vec<ServerTimingMetric> = Vec::new();
         let mut final_tls: Option<TlsSummary> = None;
         let mut final_cert: Option<CertSummary> = None;
         let mut final_resolved: Option<ResolvedTarget> = None;
+        let mut final_encoding: Option<BodyEncoding> = None;
+        let mut final_body: Option<BodySummary> = None;
+        let mut final_body_bytes: Vec<u8> = Vec::new();
+        let mut final_upgrade: Option<ProtocolUpgrade> = None;
+        let mut final_quic: Option<QuicSummary> = None;
+        let mut final_h2: Option<H2Session> = None;
+        let mut final_websocket: Option<WebSocketUpgrade> = None;
+        let mut final_grpc: Option<GrpcHealth> = None;
         let mut was_downgrade = false;
 
+        let base_headers = self.base_headers();
+
         for hop_idx in 0..=self.config.max_redirects {
             if visited.contains(&current_url.full) {
                 return Err(UdocError::http("redirect loop detected"));
             }
             visited.insert(current_url.full.clone());
 
-            let mut hop = HopTiming::default();
-
-            let (ips, dns_ms) = self.clock.timeout(self.config.timeout, self.dns.resolve(&current_url.host)).await??;
-            hop.dns_ms = dns_ms;
-            total_dns_ms += dns_ms;
-
-            let ip = ips.first().copied().ok_or_else(|| UdocError::dns(format!("no IP addresses for {}", current_url.host)))?;
-            let resolved = ResolvedTarget::new(ip, current_url.port, ips);
-            final_resolved = Some(resolved.clone());
-
-            let tcp_conn = self.clock.timeout(self.config.timeout, self.tcp.connect(ip, current_url.port)).await??;
-            hop.tcp_ms = tcp_conn.tcp_ms;
-            total_tcp_ms += tcp_conn.tcp_ms;
-
-            let (response, tls_summary, cert_summary) = if current_url.is_https() {
-                let tls_session = self.clock.timeout(self.config.timeout, self.tls.handshake(tcp_conn.stream, &current_url.host)).await??;
-                hop.tls_ms = Some(tls_session.tls_ms);
-                total_tls_ms = Some(total_tls_ms.unwrap_or(0.0) + tls_session.tls_ms);
-
-                let cert = tls_session.peer_certs.first().map(|der| parse_certificate(der)).transpose()?;
-                let summary = tls_session.summary.clone();
-
-                let resp = if summary.is_h2() {
-                    self.clock.timeout(self.config.timeout, self.http.request_h2(
-                        tls_session.stream, "GET", &current_url.host, current_url.port, &current_url.path_and_query, self.config.body_limit
-                    )).await??
-                } else {
-                    self.clock.timeout(self.config.timeout, self.http.request_h1(
-                        tls_session.stream, "GET", &current_url.host, current_url.port, &current_url.path_and_query, true, self.config.body_limit
-                    )).await??
-                };
-
-                (resp, Some(summary), cert)
-            } else {
-                let resp = self.clock.timeout(self.config.timeout, self.http.request_h1(
-                    tcp_conn.stream, "GET", &current_url.host, current_url.port, &current_url.path_and_query, false, self.config.body_limit
-                )).await??;
-                (resp, None, None)
-            };
+            let mut exchange = self.fetch(&current_url, &base_headers).await?;
+            if self.config.follow_alt_svc
+                && exchange.quic.is_none()
+                && current_url.is_https()
+                && !is_redirect(exchange.response.summary.status)
+                && let Some(alt) = parse_alt_svc(exchange.response.headers.all.get_all("alt-svc")).into_iter().find(|a| a.is_h3()) {
+                hop_timings.push(exchange.hop);
+                exchange = self.fetch_h3(&current_url, &base_headers, Some(&alt)).await?;
+            }
+            let hop = exchange.hop;
+            let response = exchange.response;
 
-            hop.ttfb_ms = response.ttfb_ms;
-            final_ttfb_ms = response.ttfb_ms;
+            final_exchange = hop.exchange;
+            final_resolved = Some(exchange.resolved);
             hop_timings.push(hop);
 
             let status = response.summary.status;
 
-            if is_redirect(status) {
+            if is_redirect(status) && !current_url.is_websocket() && self.config.grpc_health.is_none() {
                 let location = response.headers.location.as_ref()
                     .ok_or_else(|| UdocError::http(format!("redirect {} without Location header", status)))?;
 
@@ -122,25 +144,88 @@ where
                     return Err(UdocError::http(format!("too many redirects (max {})", self.config.max_redirects)));
                 }
 
-                final_tls = tls_summary.or(final_tls);
-                final_cert = cert_summary.or(final_cert);
+                final_tls = exchange.tls.or(final_tls);
+                final_cert = exchange.cert.or(final_cert);
                 continue;
             }
 
+            let (body_bytes, body_truncated) = match response.headers.content_encoding.as_deref() {
+                Some(ce) => {
+                    let decoded = decode_body(ce, &response.body_preview, response.body_truncated);
+                    let truncated = response.body_truncated || decoded.summary.decode_limit_hit;
+                    final_encoding = Some(decoded.summary);
+                    (decoded.bytes, truncated)
+                }
+                None => (response.body_preview, response.body_truncated),
+            };
+            final_body = Some(inspect_body(
+                &body_bytes,
+                response.headers.content_type.as_deref(),
+                body_truncated,
+                self.config.show_body,
+                self.config.body_json,
+            ));
+            final_grpc = self.config.grpc_health.as_deref()
+                .map(|service| parse_grpc_health(service, status, &response.headers.all, &response.trailers, &body_bytes));
+            final_body_bytes = body_bytes;
+            server_timing = parse_server_timing(
+                response.headers.all.get_all("server-timing").chain(response.trailers.get_all("server-timing"))
+            );
+            final_headers = response.headers.all;
             final_http = Some(response.summary);
-            final_tls = tls_summary.or(final_tls);
-            final_cert = cert_summary.or(final_cert);
+            final_upgrade = response.upgrade;
+            final_websocket = response.websocket;
+            final_h2 = response.h2;
+            final_quic = exchange.quic;
+            final_tls = exchange.tls.or(final_tls);
+            final_cert = exchange.cert.or(final_cert);
             break;
         }
 
+        let connection = match (self.config.requests > 1, final_http.as_ref(), hop_timings.last()) {
+            (true, Some(http), Some(first)) => Some(self.probe_connection(&current_url, &base_headers, http.status, first).await?),
+            _ => None,
+        };
+
+        let multiplex = if self.config.h2_streams > 0 {
+            Some(self.probe_multiplex(&current_url, &base_headers).await?)
+        } else {
+            None
+        };
+
+        let encoding_probe = if self.config.probe_encodings {
+            self.probe_encodings(&current_url).await?
+        } else {
+            Vec::new()
+        };
+
+        let final_status = final_http.as_ref().map(|h| h.status).unwrap_or(0);
+        let mut cache = analyze_cache(&final_headers, final_status);
+        if self.config.revalidate {
+            cache.revalidation = self.revalidate(&current_url, &cache, &final_exchange).await?;
+        }
+
         let total_ms = elapsed_ms(start, self.clock.now());
+        let final_is_https = current_url.is_https();
         let final_resolved = final_resolved.ok_or_else(|| UdocError::other("no connection established"))?;
         let final_http = final_http.ok_or_else(|| UdocError::other("no HTTP response"))?;
 
-        let timings = TimingBreakdown::new(total_dns_ms, total_tcp_ms, total_tls_ms, final_ttfb_ms, total_ms)
+        let total_dns_ms = hop_timings.iter().map(|h| h.dns_ms).sum();
+        let total_tcp_ms = hop_timings.iter().map(|h| h.tcp_ms).sum();
+        let total_tls_ms = sum_optional(hop_timings.iter().map(|h| h.tls_ms));
+        let total_quic_ms = sum_optional(hop_timings.iter().map(|h| h.quic_ms));
+        let total_proxy_ms = sum_optional(hop_timings.iter().map(|h| h.proxy.as_ref().map(|p| p.total())));
+        let last_proxy = hop_timings.iter().rev().find_map(|h| h.proxy.clone());
+        let alt_svc = parse_alt_svc(final_headers.get_all("alt-svc"));
+        let tcp = hop_timings.last().and_then(|h| h.tcp_info.clone());
+
+        let timings = TimingBreakdown::new(total_dns_ms, total_tcp_ms, total_tls_ms, final_exchange, total_ms)
+            .with_quic(total_quic_ms)
+            .with_proxy(total_proxy_ms, last_proxy)
+            .with_server_timing(server_timing)
             .with_hops(hop_timings);
 
-        Ok(Report {
+        let mut report = Report {
             input_url: input_url.to_string(),
             final_url: current_url.full,
             host: current_url.host,
@@ -148,10 +233,281 @@ where
             redirects,
             timings,
             http: final_http,
+            headers: final_headers,
             tls: final_tls,
             cert: final_cert,
             was_downgrade,
-        })
+            encoding: final_encoding,
+            encoding_probe,
+            body: final_body,
+            body_bytes: final_body_bytes,
+            assertions: Vec::new(),
+            warnings: Vec::new(),
+            security: Vec::new(),
+            cache,
+            upgrade: final_upgrade,
+            quic: final_quic,
+            alt_svc,
+            h2: final_h2,
+            connection,
+            multiplex,
+            tcp,
+            websocket: final_websocket,
+            grpc: final_grpc,
+        };
+        report.security = audit_security_headers(&report.headers, final_is_https);
+        report.assertions = evaluate_assertions(&self.config.assertions, &report, &report.body_bytes);
+        report.warnings = self.config.warning_rules.evaluate(&report);
+
+        Ok(report)
+    }
+
+    async fn probe_encodings(&self, url: &ParsedUrl) -> Result<Vec<EncodingProbe>, UdocError> {
+        let mut probes = Vec::with_capacity(SUPPORTED_ENCODINGS.len());
+        for offered in SUPPORTED_ENCODINGS {
+            let exchange = self.fetch(url, &[("Accept-Encoding".to_string(), offered.to_string())]).await?;
+            let response = exchange.response;
+            let content_encoding = response.headers.content_encoding.clone();
+            let supported = content_encoding.as_deref()
+                .map(|ce| ce.split(',').any(|c| c.trim() == offered))
+                .unwrap_or(false);
+            probes.push(EncodingProbe {
+                offered: offered.to_string(),
+                status: response.summary.status,
+                content_encoding,
+                supported,
+                wire_bytes: response.body_preview.len(),
+                ttfb_ms: response.timing.ttfb_ms,
+            });
+        }
+        Ok(probes)
+    }
+
+    async fn probe_connection(&self, url: &ParsedUrl, headers: &[(String, String)], status: u16, first: &HopTiming) -> Result<ConnectionProbe, UdocError> {
+        let mut requests = vec![SequentialRequest {
+            index: 1,
+            status,
+            reused: first.reused,
+            setup_ms: first.setup_ms(),
+            ttfb_ms: first.exchange.ttfb_ms,
+            body_ms: first.exchange.body_ms,
+        }];
+        let mut closed_after = None;
+
+        for index in 2..=self.config.requests {
+            let exchange = self.fetch(url, headers).await?;
+            let hop = exchange.hop;
+            requests.push(SequentialRequest {
+                index,
+                status: exchange.response.summary.status,
+                reused: hop.reused,
+                setup_ms: hop.setup_ms(),
+                ttfb_ms: hop.exchange.ttfb_ms,
+                body_ms: hop.exchange.body_ms,
+            });
+            if !hop.reused {
+                closed_after = Some(index - 1);
+                break;
+            }
+        }
+
+        Ok(ConnectionProbe { requested: self.config.requests, requests, closed_after })
+    }
+
+    async fn probe_multiplex(&self, url: &ParsedUrl, headers: &[(String, String)]) -> Result<MultiplexProbe, UdocError> {
+        let paths = if self.config.h2_paths.is_empty() { vec![url.path_and_query.clone()] } else { self.config.h2_paths.clone() };
+        let base = self.build_request(url, headers);
+        let requests: Vec<HttpRequest> = (0..self.config.h2_streams)
+            .map(|i| HttpRequest { path: paths[i % paths.len()].clone(), ..base.clone() })
+            .collect();
+
+        let (endpoint, stream) = self.connect(url, &mut HopTiming::default()).await?;
+        match endpoint.tls {
+            Some(ref summary) if !summary.is_h2() => return Err(UdocError::http(format!(
+                "--h2-streams needs h2 but the server selected {}",
+                summary.alpn.as_deref().unwrap_or("no ALPN protocol"),
+            ))),
+            None if !self.config.http2_prior_knowledge => return Err(UdocError::http(
+                "--h2-streams needs h2 but the connection is cleartext HTTP/1.1; add --http2-prior-knowledge".to_string(),
+            )),
+            _ => {}
+        }
+        self.clock.timeout(self.config.timeout, self.http.request_h2_multiplex(stream, &requests)).await?
+    }
+
+    async fn revalidate(&self, url: &ParsedUrl, cache: &CacheSummary, original: &ExchangeTiming) -> Result<Option<Revalidation>, UdocError> {
+        let (validator, header, value) = match (&cache.etag, &cache.last_modified) {
+            (Some(etag), _) => ("If-None-Match", "If-None-Match", etag),
+            (None, Some(lm)) => ("If-Modified-Since", "If-Modified-Since", lm),
+            (None, None) => return Ok(None),
+        };
+        let mut headers = self.base_headers();
+        headers.push((header.to_string(), value.clone()));
+
+        let exchange = self.fetch(url, &headers).await?;
+        let response = exchange.response;
+        Ok(Some(Revalidation {
+            validator: validator.to_string(),
+            status: response.summary.status,
+            not_modified: response.summary.status == 304,
+            ttfb_ms: response.timing.ttfb_ms,
+            body_ms: response.timing.body_ms,
+            original_ttfb_ms: original.ttfb_ms,
+            original_body_ms: original.body_ms,
+            body_bytes: response.body_preview.len(),
+        }))
+    }
+
+    fn base_headers(&self) -> Vec<(String, String)> {
+        let mut headers = Vec::new();
+        if self.config.compressed {
+            headers.push(("Accept-Encoding".to_string(), accept_encoding_header()));
+        }
+        headers
+    }
+
+    fn build_request(&self, url: &ParsedUrl, headers: &[(String, String)]) -> HttpRequest {
+        let mut request = HttpRequest::get(&url.host, url.port, &url.path_and_query, url.is_https(), self.config.effective_body_limit())
+            .with_keep_alive(!self.config.no_reuse);
+        for (name, value) in headers {
+            request = request.with_header(name, value);
+        }
+        if let Some(ref service) = self.config.grpc_health {
+            request.path = GRPC_HEALTH_PATH.to_string();
+            request = request.with_body("POST", grpc_health_request(service))
+                .with_header("content-type", "application/grpc")
+                .with_header("te", "trailers");
+        }
+        request
+    }
+
+    async fn fetch_h3(&self, url: &ParsedUrl, headers: &[(String, String)], alt: Option<&AltService>) -> Result<Exchange, UdocError> {
+        if !url.is_https() {
+            return Err(UdocError::input(format!("HTTP/3 requires an https:// URL: {}", url.full)));
+        }
+        let mut hop = HopTiming::default();
+        let host = alt.and_then(|a| a.host.as_deref()).unwrap_or(&url.host);
+        let port = alt.map(|a| a.port).unwrap_or(url.port);
+
+        let (ips, dns_ms) = self.clock.timeout(self.config.timeout, self.dns.resolve(host)).await??;
+        hop.dns_ms = dns_ms;
+
+        let ip = ips.first().copied().ok_or_else(|| UdocError::dns(format!("no IP addresses for {}", host)))?;
+        let resolved = ResolvedTarget::new(ip, port, ips);
+        hop.family = Some(resolved.family);
+
+        let request = self.build_request(url, headers);
+        let exchange = self.clock.timeout(self.config.timeout, self.quic.request_h3(ip, port, &request)).await??;
+        hop.quic_ms = Some(exchange.quic.handshake_ms);
+        hop.exchange = exchange.response.timing;
+
+        let cert = exchange.peer_certs.first().map(|der| parse_certificate(der)).transpose()?;
+        let quic = QuicSummary { via_alt_svc: alt.is_some(), ..exchange.quic };
+        Ok(Exchange { resolved, hop, response: exchange.response, tls: Some(exchange.tls), cert, quic: Some(quic) })
+    }
+
+    async fn fetch(&self, url: &ParsedUrl, headers: &[(String, String)]) -> Result<Exchange, UdocError> {
+        if self.config.http3 {
+            return self.fetch_h3(url, headers, None).await;
+        }
+        let request = self.build_request(url, headers);
+
+        if let Some(PooledEntry { connection, endpoint }) = self.take_pooled(url) {
+            match self.clock.timeout(self.config.timeout, self.http.request_pooled(connection, &request)).await {
+                Ok(Ok(mut response)) => {
+                    let hop = HopTiming {
+                        family: Some(endpoint.resolved.family),
+                        local_addr: endpoint.local_addr,
+                        tcp_info: endpoint.tcp_stats(),
+                        reused: true,
+                        exchange: response.timing,
+                        ..HopTiming::default()
+                    };
+                    self.keep_alive(url, &mut response, &endpoint);
+                    return Ok(Exchange { resolved: endpoint.resolved, hop, response, tls: endpoint.tls, cert: endpoint.cert, quic: None });
+                }
+                // The request may already have reached the server; only idempotent ones are safe to send again.
+                Ok(Err(e)) | Err(e) if !request.is_idempotent() => {
+                    return Err(UdocError::new(e.class, format!("{} (not retried: {} is not idempotent)", e.message, request.method)));
+                }
+                _ => {}
+            }
+        }
+
+        let mut hop = HopTiming::default();
+        let (endpoint, stream) = self.connect(url, &mut hop).await?;
+
+        let websocket = url.is_websocket().then_some(&self.config.websocket);
+        let mut response = match (&endpoint.tls, websocket) {
+            (Some(summary), Some(options)) if summary.is_h2() => self.clock.timeout(self.config.timeout, self.http.request_websocket_h2(stream, &request, options)).await??,
+            (None, Some(options)) if self.config.http2_prior_knowledge => self.clock.timeout(self.config.timeout, self.http.request_websocket_h2(stream, &request, options)).await??,
+            (_, Some(options)) => self.clock.timeout(self.config.timeout, self.http.request_websocket_h1(stream, &request, options)).await??,
+            (Some(summary), None) if summary.is_h2() => self.clock.timeout(self.config.timeout, self.http.request_h2(stream, &request)).await??,
+            (Some(_), None) => self.clock.timeout(self.config.timeout, self.http.request_h1(stream, &request)).await??,
+            (None, None) if self.config.http2_prior_knowledge => self.clock.timeout(self.config.timeout, self.http.request_h2(stream, &request)).await??,
+            (None, None) if self.config.h2c_upgrade => self.clock.timeout(self.config.timeout, self.http.request_h2c_upgrade(stream, &request)).await??,
+            (None, None) => self.clock.timeout(self.config.timeout, self.http.request_h1(stream, &request)).await??,
+        };
+
+        hop.exchange = response.timing;
+        hop.tcp_info = endpoint.tcp_stats();
+        self.keep_alive(url, &mut response, &endpoint);
+        Ok(Exchange { resolved: endpoint.resolved, hop, response, tls: endpoint.tls, cert: endpoint.cert, quic: None })
+    }
+
+    async fn connect(&self, url: &ParsedUrl, hop: &mut HopTiming) -> Result<(Endpoint, BoxedIoStream), UdocError> {
+        let (resolved, tcp_conn) = if self.tcp.tunnels(&url.host, url.port) {
+            let tcp_conn = self.clock.timeout(self.config.timeout, self.tcp.connect_tunnel(&url.host, url.port)).await??;
+            let resolved = match (&tcp_conn.unix_socket, &tcp_conn.proxy) {
+                (Some(path), _) => ResolvedTarget::unix(path, url.port),
+                (None, Some(proxy)) => ResolvedTarget::new(proxy.ip, proxy.port, vec![proxy.ip]),
+                (None, None) => return Err(UdocError::other("tunnel opened without a proxy or socket endpoint")),
+            };
+            (resolved, tcp_conn)
+        } else {
+            let (ips, dns_ms) = self.clock.timeout(self.config.timeout, self.dns.resolve(&url.host)).await??;
+            hop.dns_ms = dns_ms;
+
+            let ip = self.config.select_ip(&url.host, &ips)?;
+            let tcp_conn = self.clock.timeout(self.config.timeout, self.tcp.connect(ip, url.port)).await??;
+            (ResolvedTarget::new(ip, url.port, ips), tcp_conn)
+        };
+        hop.family = Some(resolved.family);
+        hop.tcp_ms = tcp_conn.tcp_ms;
+        hop.tcp_deferred = tcp_conn.tcp_deferred;
+        hop.proxy = tcp_conn.proxy;
+        hop.local_addr = tcp_conn.local_addr;
+        hop.proxy_protocol = tcp_conn.proxy_protocol;
+        let endpoint = Endpoint {
+            resolved,
+            tls: None,
+            cert: None,
+            local_addr: tcp_conn.local_addr,
+            tcp_stats: tcp_conn.tcp_stats,
+            tcp_probe: tcp_conn.tcp_probe,
+        };
+
+        if !url.is_https() {
+            return Ok((endpoint, tcp_conn.stream));
+        }
+        let tls_session = self.clock.timeout(self.config.timeout, self.tls.handshake(tcp_conn.stream, &url.host)).await??;
+        hop.tls_ms = Some(tls_session.tls_ms);
+
+        let cert = tls_session.peer_certs.first().map(|der| parse_certificate(der)).transpose()?;
+        Ok((Endpoint { tls: Some(tls_session.summary), cert, ..endpoint }, tls_session.stream))
+    }
+
+    fn take_pooled(&self, url: &ParsedUrl) -> Option<PooledEntry> {
+        if self.config.no_reuse || url.is_websocket() { return None; }
+        self.pool.lock().ok()?.remove(&pool_key(url))
+    }
+
+    fn keep_alive(&self, url: &ParsedUrl, response: &mut HttpResponse, endpoint: &Endpoint) {
+        let Some(connection) = response.connection.take() else { return };
+        if self.config.no_reuse { return; }
+        if let Ok(mut pool) = self.pool.lock() {
+            pool.insert(pool_key(url), PooledEntry { connection, endpoint: endpoint.clone() });
+        }
     }
 }
 
@@ -159,6 +515,149 @@ fn is_redirect(status: u16) -> bool {
     matches!(status, 301 | 302 | 303 | 307 | 308)
 }
 
+fn pool_key(url: &ParsedUrl) -> PoolKey {
+    (url.scheme.clone(), url.host.clone(), url.port)
+}
+
+fn sum_optional(values: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+    values.flatten().fold(None, |acc, v| Some(acc.unwrap_or(0.0) + v))
+}
+
 fn elapsed_ms(start: Instant, end: Instant) -> f64 {
     end.duration_since(start).as_secs_f64() * 1000.0
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use crate::infrastructure::{HybridHttpClient, PrettyRenderer, QuinnQuicClient, RustlsTlsHandshaker, TokioClock, TokioTcpDialer};
+    use super::*;
+
+    struct Loopback;
+
+    impl DnsResolver for Loopback {
+        async fn resolve(&self, _host: &str) -> Result<(Vec<IpAddr>, f64), UdocError> {
+            Ok((vec![IpAddr::V4(Ipv4Addr::LOCALHOST)], 0.0))
+        }
+    }
+
+    /// Keep-alive server that closes every connection after `per_connection` responses, either
+    /// announcing it with `Connection: close` or silently; yields the accepted connection count.
+    async fn closing_server(per_connection: usize, announce: bool) -> (u16, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = connections.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut buffer = Vec::new();
+                    for served in 1..=per_connection {
+                        while !buffer.windows(4).any(|w| w == b"\r\n\r\n") {
+                            let mut chunk = [0u8; 1024];
+                            match socket.read(&mut chunk).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                            }
+                        }
+                        buffer.clear();
+                        let close = if announce && served == per_connection { "Connection: close\r\n" } else { "" };
+                        let reply = format!("HTTP/1.1 200 OK\r\n{}Content-Length: 2\r\n\r\nok", close);
+                        if socket.write_all(reply.as_bytes()).await.is_err() { return; }
+                    }
+                });
+            }
+        });
+        (port, connections)
+    }
+
+    fn use_case(config: Config) -> GenerateReportUseCase<Loopback, TokioTcpDialer, RustlsTlsHandshaker, HybridHttpClient, QuinnQuicClient, TokioClock> {
+        rustls::crypto::ring::default_provider().install_default().ok();
+        GenerateReportUseCase::new(
+            Loopback,
+            TokioTcpDialer::new(),
+            RustlsTlsHandshaker::new().unwrap(),
+            HybridHttpClient::new(),
+            QuinnQuicClient::new().unwrap(),
+            TokioClock::new(),
+            config,
+        )
+    }
+
+    async fn run(port: u16, requests: usize) -> Report {
+        use_case(Config::default().with_requests(requests)).execute(&format!("http://localhost:{}/", port)).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn sequential_requests_reuse_the_connection() {
+        let (port, connections) = closing_server(10, true).await;
+        let report = run(port, 4).await;
+        let probe = report.connection.unwrap();
+        let reused: Vec<bool> = probe.requests.iter().map(|r| r.reused).collect();
+        assert_eq!(reused, [false, true, true, true]);
+        assert_eq!(probe.closed_after, None);
+        assert!(probe.cold_ms().unwrap() >= probe.requests[0].ttfb_ms);
+        assert!(probe.warm_ttfb_ms().is_some());
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn early_close_is_reported_with_and_without_connection_close() {
+        for announce in [true, false] {
+            let (port, connections) = closing_server(2, announce).await;
+            let report = run(port, 4).await;
+            let probe = report.connection.as_ref().unwrap();
+            let reused: Vec<bool> = probe.requests.iter().map(|r| r.reused).collect();
+            assert_eq!(reused, [false, true, false], "announce={}", announce);
+            assert_eq!(probe.closed_after, Some(2));
+            assert_eq!(connections.load(Ordering::SeqCst), 2);
+            assert!(PrettyRenderer::new().render(&report).contains("closed: server closed the connection after request #2"));
+        }
+    }
+
+    /// h2 server that answers one gRPC health call per connection and then sends GOAWAY;
+    /// yields the number of calls received.
+    async fn grpc_server() -> (u16, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let received = calls.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let received = received.clone();
+                tokio::spawn(async move {
+                    let Ok(mut conn) = h2::server::handshake(socket).await else { return };
+                    if let Some(Ok((_request, mut respond))) = conn.accept().await {
+                        received.fetch_add(1, Ordering::SeqCst);
+                        let response = http::Response::builder().header("content-type", "application/grpc").body(()).unwrap();
+                        let mut stream = respond.send_response(response, false).unwrap();
+                        stream.send_data(bytes::Bytes::from_static(&[0, 0, 0, 0, 2, 0x08, 0x01]), false).unwrap();
+                        let mut trailers = http::HeaderMap::new();
+                        trailers.insert("grpc-status", http::HeaderValue::from_static("0"));
+                        stream.send_trailers(trailers).unwrap();
+                    }
+                    conn.graceful_shutdown();
+                    while conn.accept().await.is_some() {}
+                });
+            }
+        });
+        (port, calls)
+    }
+
+    #[tokio::test]
+    async fn non_idempotent_requests_are_not_resent_after_a_pooled_failure() {
+        let (port, calls) = grpc_server().await;
+        let config = Config::default()
+            .with_grpc_health(Some(String::new()))
+            .with_http2_prior_knowledge(true)
+            .with_requests(2);
+        let err = use_case(config).execute(&format!("http://localhost:{}/", port)).await.err().unwrap();
+        assert!(err.message.contains("not retried: POST is not idempotent"), "{}", err.message);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::domain::{grpc_status_for_http, grpc_status_name, GrpcHealth, GrpcStatusSource, HeaderList, ServingStatus};
+use super::percent_encoding::percent_decode;
+
+const FRAME_PREFIX: usize = 5;
+
+pub fn grpc_health_request(service: &str) -> Vec<u8> {
+    let mut message = Vec::with_capacity(service.len() + 4);
+    if !service.is_empty() {
+        message.push(0x0a);
+        push_varint(&mut message, service.len() as u64);
+        message.extend_from_slice(service.as_bytes());
+    }
+    let mut frame = Vec::with_capacity(FRAME_PREFIX + message.len());
+    frame.push(0);
+    frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    frame.extend(message);
+    frame
+}
+
+pub fn parse_grpc_health(service: &str, http_status: u16, headers: &HeaderList, trailers: &HeaderList, body: &[u8]) -> GrpcHealth {
+    let (status, status_source) = match (trailers.get("grpc-status"), headers.get("grpc-status")) {
+        (Some(code), _) => (code.trim().parse::<u32>().ok(), Some(GrpcStatusSource::Trailers)),
+        (None, Some(code)) => (code.trim().parse::<u32>().ok(), Some(GrpcStatusSource::Headers)),
+        (None, None) if http_status != 200 => (Some(grpc_status_for_http(http_status)), Some(GrpcStatusSource::HttpStatus)),
+        (None, None) => (None, None),
+    };
+    let grpc_message = trailers.get("grpc-message")
+        .or_else(|| headers.get("grpc-message"))
+        .map(percent_decode)
+        .filter(|m| !m.is_empty());
+
+    let (serving_status, decode_error) = match status {
+        Some(0) => match decode_response(body, headers.get("grpc-encoding")) {
+            Ok(serving) => (Some(serving), None),
+            Err(e) => (None, Some(e)),
+        },
+        _ => (None, None),
+    };
+
+    GrpcHealth {
+        service: service.to_string(),
+        content_type: headers.get("content-type").map(|ct| ct.to_string()),
+        grpc_status: status,
+        grpc_status_name: status.map(grpc_status_name),
+        grpc_message,
+        status_source,
+        serving_status,
+        decode_error,
+    }
+}
+
+fn decode_response(body: &[u8], encoding: Option<&str>) -> Result<ServingStatus, String> {
+    let prefix = body.get(..FRAME_PREFIX).ok_or("response carried no gRPC message")?;
+    if prefix[0] != 0 {
+        return Err(format!("response message is compressed ({}), which udoc does not decode", encoding.unwrap_or("unknown grpc-encoding")));
+    }
+    let len = u32::from_be_bytes([prefix[1], prefix[2], prefix[3], prefix[4]]) as usize;
+    let message = body.get(FRAME_PREFIX..FRAME_PREFIX + len)
+        .ok_or_else(|| format!("gRPC message truncated: {} of {} bytes", body.len() - FRAME_PREFIX, len))?;
+
+    let mut status = 0;
+    let mut pos = 0;
+    while pos < message.len() {
+        let key = read_varint(message, &mut pos)?;
+        let skip = match (key >> 3, key & 7) {
+            (1, 0) => { status = read_varint(message, &mut pos)?; 0 }
+            (_, 0) => { read_varint(message, &mut pos)?; 0 }
+            (_, 1) => 8,
+            (_, 2) => read_varint(message, &mut pos)?,
+            (_, 5) => 4,
+            (field, wire) => return Err(format!("unsupported protobuf wire type {} for field {}", wire, field)),
+        };
+        pos = usize::try_from(skip).ok()
+            .and_then(|skip| pos.checked_add(skip))
+            .filter(|end| *end <= message.len())
+            .ok_or("malformed HealthCheckResponse: field runs past the message")?;
+    }
+    ServingStatus::from_wire(status).ok_or_else(|| format!("unknown serving status {}", status))
+}
+
+fn push_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u64, String> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos).ok_or("malformed HealthCheckResponse: truncated varint")?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("malformed HealthCheckResponse: varint too long".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn framed(message: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0];
+        frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        frame.extend_from_slice(message);
+        frame
+    }
+
+    #[test]
+    fn encodes_request_with_and_without_service() {
+        assert_eq!(grpc_health_request(""), vec![0, 0, 0, 0, 0]);
+        assert_eq!(grpc_health_request("my.Svc"), [&[0, 0, 0, 0, 8, 0x0a, 6][..], b"my.Svc"].concat());
+    }
+
+    #[test]
+    fn decodes_serving_and_not_serving() {
+        assert_eq!(decode_response(&framed(&[0x08, 0x01]), None), Ok(ServingStatus::Serving));
+        assert_eq!(decode_response(&framed(&[0x08, 0x02]), None), Ok(ServingStatus::NotServing));
+        assert_eq!(decode_response(&framed(&[]), None), Ok(ServingStatus::Unknown));
+    }
+
+    #[test]
+    fn skips_unknown_fields() {
+        let message = [0x12, 0x03, b'a', b'b', b'c', 0x18, 0x96, 0x01, 0x21, 0, 0, 0, 0, 0, 0, 0, 0, 0x2d, 0, 0, 0, 0, 0x08, 0x01];
+        assert_eq!(decode_response(&framed(&message), None), Ok(ServingStatus::Serving));
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        assert!(decode_response(&[0, 0, 0], None).is_err());
+        assert!(decode_response(&[0, 0, 0, 0, 4, 0x08], None).is_err());
+        assert!(decode_response(&framed(&[0x08]), None).is_err());
+        assert!(decode_response(&framed(&[0x12, 0x05, b'a']), None).is_err());
+        assert!(decode_response(&framed(&[0x21, 0, 0]), None).is_err());
+    }
+
+    #[test]
+    fn rejects_huge_length_without_overflow() {
+        let message = [0x12, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        assert!(decode_response(&framed(&message), None).is_err());
+    }
+
+    #[test]
+    fn rejects_compressed_messages() {
+        assert!(decode_response(&[1, 0, 0, 0, 2, 0x08, 0x01], Some("gzip")).unwrap_err().contains("gzip"));
+    }
+
+    #[test]
+    fn reads_status_from_trailers_headers_or_http_status() {
+        let mut headers = HeaderList::new();
+        headers.push("content-type", "application/grpc");
+        let mut trailers = HeaderList::new();
+        trailers.push("grpc-status", "0");
+        let health = parse_grpc_health("", 200, &headers, &trailers, &framed(&[0x08, 0x01]));
+        assert_eq!((health.grpc_status, health.status_source, health.serving_status), (Some(0), Some(GrpcStatusSource::Trailers), Some(ServingStatus::Serving)));
+
+        headers.push("grpc-status", "12");
+        headers.push("grpc-message", "Method%20not%20found");
+        let health = parse_grpc_health("", 200, &headers, &HeaderList::new(), &[]);
+        assert_eq!((health.grpc_status, health.status_source), (Some(12), Some(GrpcStatusSource::Headers)));
+        assert_eq!(health.grpc_message.as_deref(), Some("Method not found"));
+
+        let health = parse_grpc_health("", 503, &HeaderList::new(), &HeaderList::new(), &[]);
+        assert_eq!((health.grpc_status, health.status_source), (Some(14), Some(GrpcStatusSource::HttpStatus)));
+    }
+}
@@ -2,8 +2,31 @@ mod generate_report;
 mod url_parser;
 mod cert_parser;
 mod config;
+mod body_decoder;
+mod body_inspector;
+mod assertions;
+mod security_audit;
+mod cache_analysis;
+mod server_timing;
+mod alt_svc;
+mod proxy;
+mod percent_encoding;
+mod tcping;
+mod tls_probe;
+mod grpc_health;
 
 pub use generate_report::GenerateReportUseCase;
 pub use url_parser::ParsedUrl;
 pub use cert_parser::parse_certificate;
-pub use config::Config;
+pub use config::{Config, parse_duration};
+pub use body_decoder::{decode_body, accept_encoding_header, DecodedBody, SUPPORTED_ENCODINGS};
+pub use body_inspector::inspect_body;
+pub use assertions::{parse_status_assertion, parse_header_assertion, parse_duration_ms, evaluate_assertions};
+pub use security_audit::audit_security_headers;
+pub use cache_analysis::analyze_cache;
+pub use server_timing::parse_server_timing;
+pub use alt_svc::parse_alt_svc;
+pub use proxy::{parse_proxy_url, proxy_from_env, no_proxy_from_env};
+pub use tcping::{TcpingUseCase, parse_host_port};
+pub use tls_probe::TlsProbeUseCase;
+pub use grpc_health::{grpc_health_request, parse_grpc_health};
@@ -0,0 +1,48 @@
+use url::Url;
+use crate::domain::{NoProxy, ProxyScheme, ProxySpec};
+use super::percent_encoding::percent_decode;
+
+pub fn parse_proxy_url(value: &str) -> Result<ProxySpec, String> {
+    let value = value.trim();
+    let with_scheme = if value.contains("://") { value.to_string() } else { format!("http://{}", value) };
+    let url = Url::parse(&with_scheme).map_err(|e| format!("invalid proxy URL '{}': {}", value, e))?;
+
+    let scheme = match url.scheme() {
+        "http" => ProxyScheme::Http,
+        "https" => ProxyScheme::Https,
+        "socks5h" => ProxyScheme::Socks5h,
+        other => return Err(format!("unsupported proxy scheme '{}', expected http, https or socks5h", other)),
+    };
+    let host = url.host_str()
+        .ok_or_else(|| format!("invalid proxy URL '{}': missing host", value))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = url.port().unwrap_or(scheme.default_port());
+
+    let credentials = (!url.username().is_empty())
+        .then(|| (percent_decode(url.username()), percent_decode(url.password().unwrap_or(""))));
+
+    Ok(ProxySpec { scheme, host, port, credentials })
+}
+
+/// Picks the proxy for the target scheme like curl: `HTTPS_PROXY` for https:// and wss://,
+/// `HTTP_PROXY` for http:// and ws://, then `ALL_PROXY`. The choice is made once for the
+/// input URL; redirects to the other scheme keep using it.
+pub fn proxy_from_env(https: bool) -> Result<Option<ProxySpec>, String> {
+    let scheme_keys = if https { ["HTTPS_PROXY", "https_proxy"] } else { ["HTTP_PROXY", "http_proxy"] };
+    for key in scheme_keys.into_iter().chain(["ALL_PROXY", "all_proxy"]) {
+        if let Ok(value) = std::env::var(key)
+            && !value.trim().is_empty() {
+            return parse_proxy_url(&value).map(Some).map_err(|e| format!("{}: {}", key, e));
+        }
+    }
+    Ok(None)
+}
+
+pub fn no_proxy_from_env() -> NoProxy {
+    ["NO_PROXY", "no_proxy"].iter()
+        .find_map(|key| std::env::var(key).ok())
+        .map(|value| NoProxy::parse(&value))
+        .unwrap_or_default()
+}
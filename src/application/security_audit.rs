@@ -0,0 +1,258 @@
+use crate::domain::{HeaderList, SecurityFinding, Severity};
+
+const HSTS_MIN_MAX_AGE: u64 = 180 * 86400;
+const HSTS_PRELOAD_MAX_AGE: u64 = 365 * 86400;
+
+pub fn audit_security_headers(headers: &HeaderList, is_https: bool) -> Vec<SecurityFinding> {
+    let mut findings = Vec::new();
+
+    audit_hsts(headers, is_https, &mut findings);
+    audit_csp(headers, &mut findings);
+    audit_content_type_options(headers, &mut findings);
+    audit_framing(headers, &mut findings);
+    audit_referrer_policy(headers, &mut findings);
+    audit_permissions_policy(headers, &mut findings);
+    audit_cross_origin(headers, &mut findings);
+    for cookie in headers.get_all("set-cookie") {
+        audit_cookie(cookie, is_https, &mut findings);
+    }
+
+    findings
+}
+
+fn audit_hsts(headers: &HeaderList, is_https: bool, findings: &mut Vec<SecurityFinding>) {
+    let value = match headers.get("strict-transport-security") {
+        Some(v) => v,
+        None if is_https => {
+            findings.push(SecurityFinding::fail("hsts", Severity::Critical, "Strict-Transport-Security missing"));
+            return;
+        }
+        None => {
+            findings.push(SecurityFinding::fail("hsts", Severity::Info, "not applicable over plain HTTP"));
+            return;
+        }
+    };
+    if !is_https {
+        findings.push(SecurityFinding::fail("hsts", Severity::Info, "ignored by browsers over plain HTTP"));
+        return;
+    }
+
+    let directives: Vec<String> = value.split(';').map(|d| d.trim().to_ascii_lowercase()).collect();
+    let max_age = directives.iter()
+        .find_map(|d| d.strip_prefix("max-age="))
+        .and_then(|v| v.trim_matches('"').parse::<u64>().ok());
+    let subdomains = directives.iter().any(|d| d == "includesubdomains");
+    let preload = directives.iter().any(|d| d == "preload");
+
+    let max_age = match max_age {
+        Some(m) => m,
+        None => {
+            findings.push(SecurityFinding::fail("hsts", Severity::Critical, format!("invalid or missing max-age: {}", value)));
+            return;
+        }
+    };
+
+    let mut parts = vec![format!("max-age={}", max_age)];
+    if subdomains { parts.push("includeSubDomains".to_string()); }
+    if preload { parts.push("preload".to_string()); }
+    let eligible = max_age >= HSTS_PRELOAD_MAX_AGE && subdomains && preload;
+    parts.push(if eligible { "preload eligible".to_string() } else { "not preload eligible".to_string() });
+    let summary = parts.join(", ");
+
+    if max_age == 0 {
+        findings.push(SecurityFinding::fail("hsts", Severity::Critical, format!("{} (disables HSTS)", summary)));
+    } else if max_age < HSTS_MIN_MAX_AGE {
+        findings.push(SecurityFinding::fail("hsts", Severity::Warning, format!("{} (max-age below 180 days)", summary)));
+    } else {
+        findings.push(SecurityFinding::pass("hsts", summary));
+    }
+}
+
+fn audit_csp(headers: &HeaderList, findings: &mut Vec<SecurityFinding>) {
+    let policy = match headers.get("content-security-policy") {
+        Some(p) => p,
+        None => {
+            let msg = if headers.contains("content-security-policy-report-only") {
+                "only Content-Security-Policy-Report-Only is set (not enforced)"
+            } else {
+                "Content-Security-Policy missing"
+            };
+            findings.push(SecurityFinding::fail("csp", Severity::Warning, msg));
+            return;
+        }
+    };
+
+    let directive = |name: &str| -> Option<Vec<String>> {
+        policy.split(';').find_map(|d| {
+            let mut tokens = d.split_whitespace();
+            let key = tokens.next()?;
+            key.eq_ignore_ascii_case(name).then(|| tokens.map(|t| t.to_ascii_lowercase()).collect())
+        })
+    };
+
+    let scripts = directive("script-src").or_else(|| directive("default-src"));
+    let mut problems = Vec::new();
+    match scripts {
+        Some(ref sources) => {
+            if sources.iter().any(|s| s == "'unsafe-inline'") { problems.push("'unsafe-inline'"); }
+            if sources.iter().any(|s| s == "'unsafe-eval'") { problems.push("'unsafe-eval'"); }
+            if sources.iter().any(|s| s == "*" || s == "http:" || s == "https:" || s == "data:") { problems.push("wildcard source"); }
+        }
+        None => problems.push("no script-src or default-src"),
+    }
+
+    if problems.is_empty() {
+        findings.push(SecurityFinding::pass("csp", "present, no obvious script-src weaknesses"));
+    } else {
+        findings.push(SecurityFinding::fail("csp", Severity::Warning, format!("weak script policy: {}", problems.join(", "))));
+    }
+}
+
+fn audit_content_type_options(headers: &HeaderList, findings: &mut Vec<SecurityFinding>) {
+    match headers.get("x-content-type-options") {
+        Some(v) if v.eq_ignore_ascii_case("nosniff") => findings.push(SecurityFinding::pass("x-content-type-options", "nosniff")),
+        Some(v) => findings.push(SecurityFinding::fail("x-content-type-options", Severity::Warning, format!("unexpected value '{}'", v))),
+        None => findings.push(SecurityFinding::fail("x-content-type-options", Severity::Warning, "missing (expected nosniff)")),
+    }
+}
+
+fn audit_framing(headers: &HeaderList, findings: &mut Vec<SecurityFinding>) {
+    let frame_ancestors = headers.get("content-security-policy")
+        .map(|p| p.split(';').any(|d| d.trim().to_ascii_lowercase().starts_with("frame-ancestors")))
+        .unwrap_or(false);
+    match headers.get("x-frame-options") {
+        _ if frame_ancestors => findings.push(SecurityFinding::pass("framing", "CSP frame-ancestors")),
+        Some(v) if v.eq_ignore_ascii_case("deny") || v.eq_ignore_ascii_case("sameorigin") => {
+            findings.push(SecurityFinding::pass("framing", format!("X-Frame-Options {}", v.to_ascii_uppercase())));
+        }
+        Some(v) => findings.push(SecurityFinding::fail("framing", Severity::Warning, format!("invalid X-Frame-Options '{}'", v))),
+        None => findings.push(SecurityFinding::fail("framing", Severity::Warning, "no X-Frame-Options or CSP frame-ancestors (clickjacking)")),
+    }
+}
+
+fn audit_referrer_policy(headers: &HeaderList, findings: &mut Vec<SecurityFinding>) {
+    match headers.get("referrer-policy") {
+        Some(v) if v.to_ascii_lowercase().contains("unsafe-url") => {
+            findings.push(SecurityFinding::fail("referrer-policy", Severity::Warning, "unsafe-url leaks full URLs"));
+        }
+        Some(v) => findings.push(SecurityFinding::pass("referrer-policy", v)),
+        None => findings.push(SecurityFinding::fail("referrer-policy", Severity::Info, "missing (browser default applies)")),
+    }
+}
+
+fn audit_permissions_policy(headers: &HeaderList, findings: &mut Vec<SecurityFinding>) {
+    match headers.get("permissions-policy") {
+        Some(_) => findings.push(SecurityFinding::pass("permissions-policy", "present")),
+        None => findings.push(SecurityFinding::fail("permissions-policy", Severity::Info, "missing")),
+    }
+}
+
+fn audit_cross_origin(headers: &HeaderList, findings: &mut Vec<SecurityFinding>) {
+    for (check, header) in [
+        ("coop", "cross-origin-opener-policy"),
+        ("coep", "cross-origin-embedder-policy"),
+        ("corp", "cross-origin-resource-policy"),
+    ] {
+        match headers.get(header) {
+            Some(v) => findings.push(SecurityFinding::pass(check, v)),
+            None => findings.push(SecurityFinding::fail(check, Severity::Info, format!("{} missing", header))),
+        }
+    }
+}
+
+fn audit_cookie(cookie: &str, is_https: bool, findings: &mut Vec<SecurityFinding>) {
+    let mut parts = cookie.split(';');
+    let name = parts.next().and_then(|nv| nv.split('=').next()).unwrap_or("").trim().to_string();
+    let attrs: Vec<String> = parts.map(|a| a.trim().to_ascii_lowercase()).collect();
+
+    let secure = attrs.iter().any(|a| a == "secure");
+    let http_only = attrs.iter().any(|a| a == "httponly");
+    let same_site = attrs.iter().find_map(|a| a.strip_prefix("samesite=").map(|v| v.trim().to_string()));
+
+    let check = format!("cookie {}", name);
+    let mut problems: Vec<(Severity, &str)> = Vec::new();
+    if !secure && is_https { problems.push((Severity::Warning, "missing Secure")); }
+    if !http_only { problems.push((Severity::Info, "missing HttpOnly")); }
+    match same_site.as_deref() {
+        None => problems.push((Severity::Info, "missing SameSite")),
+        Some("none") if !secure => problems.push((Severity::Critical, "SameSite=None without Secure")),
+        _ => {}
+    }
+
+    if problems.is_empty() {
+        findings.push(SecurityFinding::pass(check, format!("Secure, HttpOnly, SameSite={}", same_site.unwrap_or_default())));
+    } else {
+        let severity = problems.iter().map(|(s, _)| *s).max().unwrap_or(Severity::Info);
+        let message = problems.iter().map(|(_, m)| *m).collect::<Vec<_>>().join(", ");
+        findings.push(SecurityFinding::fail(check, severity, message));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding<'a>(findings: &'a [SecurityFinding], check: &str) -> &'a SecurityFinding {
+        findings.iter().find(|f| f.check == check).unwrap()
+    }
+
+    #[test]
+    fn hsts_missing_over_https_is_critical() {
+        let findings = audit_security_headers(&HeaderList::new(), true);
+        let hsts = finding(&findings, "hsts");
+        assert!(!hsts.passed);
+        assert_eq!(hsts.severity, Severity::Critical);
+        assert_eq!(finding(&audit_security_headers(&HeaderList::new(), false), "hsts").severity, Severity::Info);
+    }
+
+    #[test]
+    fn hsts_preload_eligibility_and_short_max_age() {
+        let findings = audit_security_headers(&HeaderList::from_pairs(&[("strict-transport-security", "max-age=31536000; includeSubDomains; preload")]), true);
+        let hsts = finding(&findings, "hsts");
+        assert!(hsts.passed);
+        assert!(hsts.message.ends_with("preload eligible") && !hsts.message.contains("not preload"));
+
+        let findings = audit_security_headers(&HeaderList::from_pairs(&[("strict-transport-security", "max-age=3600")]), true);
+        assert_eq!(finding(&findings, "hsts").severity, Severity::Warning);
+
+        let findings = audit_security_headers(&HeaderList::from_pairs(&[("strict-transport-security", "max-age=0")]), true);
+        assert!(finding(&findings, "hsts").message.contains("disables HSTS"));
+    }
+
+    #[test]
+    fn csp_flags_unsafe_script_sources() {
+        let findings = audit_security_headers(&HeaderList::from_pairs(&[("content-security-policy", "default-src 'self'; script-src 'self' 'unsafe-inline' https:")]), true);
+        let csp = finding(&findings, "csp");
+        assert!(!csp.passed);
+        assert!(csp.message.contains("'unsafe-inline'") && csp.message.contains("wildcard source"));
+
+        let findings = audit_security_headers(&HeaderList::from_pairs(&[("content-security-policy", "default-src 'self'; frame-ancestors 'none'")]), true);
+        assert!(finding(&findings, "csp").passed);
+        assert_eq!(finding(&findings, "framing").message, "CSP frame-ancestors");
+    }
+
+    #[test]
+    fn cookie_flags() {
+        let findings = audit_security_headers(&HeaderList::from_pairs(&[
+            ("set-cookie", "sid=1; Secure; HttpOnly; SameSite=Lax"),
+            ("set-cookie", "track=2; SameSite=None"),
+        ]), true);
+        assert!(finding(&findings, "cookie sid").passed);
+        let track = finding(&findings, "cookie track");
+        assert_eq!(track.severity, Severity::Critical);
+        assert!(track.message.contains("missing Secure") && track.message.contains("SameSite=None without Secure"));
+    }
+
+    #[test]
+    fn simple_headers() {
+        let findings = audit_security_headers(&HeaderList::from_pairs(&[
+            ("x-content-type-options", "nosniff"),
+            ("x-frame-options", "sameorigin"),
+            ("referrer-policy", "unsafe-url"),
+        ]), true);
+        assert!(finding(&findings, "x-content-type-options").passed);
+        assert_eq!(finding(&findings, "framing").message, "X-Frame-Options SAMEORIGIN");
+        assert!(!finding(&findings, "referrer-policy").passed);
+        assert_eq!(finding(&findings, "coop").severity, Severity::Info);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::domain::ServerTimingMetric;
+
+pub fn parse_server_timing<'a>(values: impl Iterator<Item = &'a str>) -> Vec<ServerTimingMetric> {
+    values.flat_map(split_unquoted(','))
+        .filter_map(|entry| parse_metric(&entry))
+        .collect()
+}
+
+fn parse_metric(entry: &str) -> Option<ServerTimingMetric> {
+    let mut params = split_unquoted(';')(entry).into_iter();
+    let name = params.next()?.trim().to_string();
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut dur_ms = None;
+    let mut desc = None;
+    for param in params {
+        let (key, value) = match param.split_once('=') {
+            Some((k, v)) => (k.trim().to_ascii_lowercase(), unquote(v.trim())),
+            None => continue,
+        };
+        match key.as_str() {
+            "dur" if dur_ms.is_none() => dur_ms = value.parse::<f64>().ok().filter(|d| d.is_finite() && *d >= 0.0),
+            "desc" if desc.is_none() => desc = Some(value),
+            _ => {}
+        }
+    }
+
+    Some(ServerTimingMetric { name, dur_ms, desc })
+}
+
+pub(super) fn split_unquoted(sep: char) -> impl Fn(&str) -> Vec<String> {
+    move |input: &str| {
+        let mut parts = Vec::new();
+        let mut current = String::new();
+        let mut in_quotes = false;
+        let mut escaped = false;
+        for c in input.chars() {
+            if escaped {
+                current.push(c);
+                escaped = false;
+            } else if c == '\\' && in_quotes {
+                current.push(c);
+                escaped = true;
+            } else if c == '"' {
+                current.push(c);
+                in_quotes = !in_quotes;
+            } else if c == sep && !in_quotes {
+                parts.push(std::mem::take(&mut current));
+            } else {
+                current.push(c);
+            }
+        }
+        if !current.trim().is_empty() {
+            parts.push(current);
+        }
+        parts
+    }
+}
+
+pub(super) fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => inner.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{ExchangeTiming, TimingBreakdown};
+    use super::*;
+
+    #[test]
+    fn parses_metrics_with_quoted_descriptions() {
+        let metrics = parse_server_timing(["db;dur=53.2;desc=\"a, b; c\", cache;desc=hit", "app;dur=80"].into_iter());
+        assert_eq!(metrics.len(), 3);
+        assert_eq!((metrics[0].name.as_str(), metrics[0].dur_ms, metrics[0].desc.as_deref()), ("db", Some(53.2), Some("a, b; c")));
+        assert_eq!((metrics[1].name.as_str(), metrics[1].dur_ms, metrics[1].desc.as_deref()), ("cache", None, Some("hit")));
+        assert_eq!(metrics[2].dur_ms, Some(80.0));
+    }
+
+    #[test]
+    fn ignores_invalid_durations_and_empty_names() {
+        let metrics = parse_server_timing(["a;dur=-1, b;dur=abc, ;dur=5, c;dur=1;dur=2"].into_iter());
+        let parsed: Vec<_> = metrics.iter().map(|m| (m.name.as_str(), m.dur_ms)).collect();
+        assert_eq!(parsed, [("a", None), ("b", None), ("c", Some(1.0))]);
+    }
+
+    fn server_ms(header: &str, ttfb_ms: f64) -> Option<f64> {
+        let exchange = ExchangeTiming { ttfb_ms, ..ExchangeTiming::default() };
+        TimingBreakdown::new(0.0, 0.0, None, exchange, ttfb_ms)
+            .with_server_timing(parse_server_timing([header].into_iter()))
+            .server_ms
+    }
+
+    #[test]
+    fn server_share_prefers_total_then_largest_metric() {
+        assert_eq!(server_ms("db;dur=20, app;dur=50, total;dur=60", 100.0), Some(60.0));
+        assert_eq!(server_ms("db;dur=20, app;dur=50", 100.0), Some(50.0));
+        assert_eq!(server_ms("app;dur=500", 100.0), Some(100.0));
+        assert_eq!(server_ms("cache;desc=hit", 100.0), None);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::domain::*;
+use crate::ports::*;
+use super::Config;
+
+pub struct TcpingUseCase<D, T, L, C>
+where
+    D: DnsResolver,
+    T: TcpDialer,
+    L: TlsHandshaker,
+    C: Clock,
+{
+    dns: D,
+    tcp: T,
+    tls: L,
+    clock: C,
+    config: Config,
+}
+
+impl<D, T, L, C> TcpingUseCase<D, T, L, C>
+where
+    D: DnsResolver,
+    T: TcpDialer,
+    L: TlsHandshaker,
+    C: Clock,
+{
+    pub fn new(dns: D, tcp: T, tls: L, clock: C, config: Config) -> Self {
+        Self { dns, tcp, tls, clock, config }
+    }
+
+    pub async fn execute(&self, host: &str, port: u16, mut on_attempt: impl FnMut(&TcpingSummary, &TcpingAttempt)) -> Result<TcpingSummary, UdocError> {
+        let (ips, dns_ms) = self.clock.timeout(self.config.timeout, self.dns.resolve(host)).await??;
+        let ip = self.config.select_ip(host, &ips)?;
+
+        let mut summary = TcpingSummary {
+            host: host.to_string(),
+            resolved: ResolvedTarget::new(ip, port, ips),
+            dns_ms,
+            tls: self.config.tcping_tls,
+            attempts: Vec::with_capacity(self.config.tcping_count),
+        };
+
+        for seq in 1..=self.config.tcping_count {
+            if seq > 1 {
+                self.clock.sleep(self.config.tcping_interval).await;
+            }
+            let attempt = self.attempt(seq, host, &summary.resolved).await;
+            on_attempt(&summary, &attempt);
+            summary.attempts.push(attempt);
+        }
+        Ok(summary)
+    }
+
+    async fn attempt(&self, seq: usize, host: &str, target: &ResolvedTarget) -> TcpingAttempt {
+        let mut attempt = TcpingAttempt { seq, connect_ms: None, tls_ms: None, error: None };
+        let Some(ip) = target.ip else {
+            attempt.error = Some("no IP address to connect to".to_string());
+            return attempt;
+        };
+
+        let conn = match self.clock.timeout(self.config.timeout, self.tcp.connect(ip, target.port)).await {
+            Ok(Ok(conn)) => conn,
+            Ok(Err(e)) | Err(e) => {
+                attempt.error = Some(e.message);
+                return attempt;
+            }
+        };
+        attempt.connect_ms = Some(conn.tcp_ms);
+
+        if self.config.tcping_tls {
+            match self.clock.timeout(self.config.timeout, self.tls.handshake(conn.stream, host)).await {
+                Ok(Ok(session)) => attempt.tls_ms = Some(session.tls_ms),
+                Ok(Err(e)) | Err(e) => attempt.error = Some(e.message),
+            }
+        }
+        attempt
+    }
+}
+
+pub fn parse_host_port(value: &str) -> Result<(String, u16), UdocError> {
+    let value = value.trim();
+    let (host, port) = match value.strip_prefix('[') {
+        Some(rest) => {
+            let (host, port) = rest.split_once("]:")
+                .ok_or_else(|| UdocError::input(format!("invalid target '{}': expected [ipv6]:port", value)))?;
+            (host, port)
+        }
+        None => value.rsplit_once(':')
+            .filter(|(host, _)| !host.contains(':'))
+            .ok_or_else(|| UdocError::input(format!("invalid target '{}': expected host:port", value)))?,
+    };
+    if host.is_empty() {
+        return Err(UdocError::input(format!("invalid target '{}': missing host", value)));
+    }
+    let port = port.parse::<u16>().ok()
+        .filter(|p| *p > 0)
+        .ok_or_else(|| UdocError::input(format!("invalid port '{}' in '{}'", port, value)))?;
+    Ok((host.to_string(), port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_host_and_port() {
+        assert_eq!(parse_host_port(" example.com:443 ").unwrap(), ("example.com".to_string(), 443));
+        assert_eq!(parse_host_port("192.0.2.1:22").unwrap(), ("192.0.2.1".to_string(), 22));
+        assert_eq!(parse_host_port("[2001:db8::1]:8443").unwrap(), ("2001:db8::1".to_string(), 8443));
+    }
+
+    #[test]
+    fn select_ip_prefers_the_local_address_family() {
+        let ips: Vec<std::net::IpAddr> = vec!["2001:db8::1".parse().unwrap(), "192.0.2.1".parse().unwrap()];
+        let config = Config::default();
+        assert_eq!(config.select_ip("h", &ips).unwrap(), ips[0]);
+        let config = config.with_local_addr(Some("10.0.0.2:0".parse().unwrap()));
+        assert_eq!(config.select_ip("h", &ips).unwrap(), ips[1]);
+        assert_eq!(config.select_ip("h", &ips[..1]).unwrap(), ips[0]);
+        assert_eq!(config.select_ip("h", &[]).unwrap_err().class, ErrorClass::Dns);
+    }
+
+    #[test]
+    fn rejects_missing_zero_or_ambiguous_ports() {
+        for (input, message) in [
+            ("example.com", "expected host:port"),
+            ("example.com:", "invalid port ''"),
+            ("example.com:0", "invalid port '0'"),
+            ("example.com:65536", "invalid port '65536'"),
+            (":443", "missing host"),
+            ("2001:db8::1:443", "expected host:port"),
+            ("[2001:db8::1]", "expected [ipv6]:port"),
+            ("[]:443", "missing host"),
+        ] {
+            let err = parse_host_port(input).unwrap_err();
+            assert_eq!(err.class, ErrorClass::Input);
+            assert!(err.message.contains(message), "{}: {}", input, err.message);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::domain::*;
+use crate::ports::*;
+use super::{Config, parse_certificate};
+
+pub struct TlsProbeUseCase<D, T, S, L, C>
+where
+    D: DnsResolver,
+    T: TcpDialer,
+    S: StartTlsNegotiator,
+    L: TlsHandshaker,
+    C: Clock,
+{
+    dns: D,
+    tcp: T,
+    starttls: S,
+    tls: L,
+    clock: C,
+    config: Config,
+}
+
+impl<D, T, S, L, C> TlsProbeUseCase<D, T, S, L, C>
+where
+    D: DnsResolver,
+    T: TcpDialer,
+    S: StartTlsNegotiator,
+    L: TlsHandshaker,
+    C: Clock,
+{
+    pub fn new(dns: D, tcp: T, starttls: S, tls: L, clock: C, config: Config) -> Self {
+        Self { dns, tcp, starttls, tls, clock, config }
+    }
+
+    pub async fn execute(&self, host: &str, port: u16) -> Result<TlsProbeReport, UdocError> {
+        let start = self.clock.now();
+        let (ips, dns_ms) = self.clock.timeout(self.config.timeout, self.dns.resolve(host)).await??;
+        let ip = self.config.select_ip(host, &ips)?;
+
+        let mut conn = self.clock.timeout(self.config.timeout, self.tcp.connect(ip, port)).await??;
+        let starttls = match self.config.starttls {
+            Some(protocol) => Some(self.clock.timeout(self.config.timeout, self.starttls.negotiate(&mut conn.stream, protocol, host)).await??),
+            None => None,
+        };
+
+        let session = self.clock.timeout(self.config.timeout, self.tls.handshake(conn.stream, host)).await??;
+        let cert = session.peer_certs.first().map(|der| parse_certificate(der)).transpose()?;
+        let total_ms = self.clock.now().duration_since(start).as_secs_f64() * 1000.0;
+
+        let mut report = TlsProbeReport {
+            host: host.to_string(),
+            resolved: ResolvedTarget::new(ip, port, ips),
+            dns_ms,
+            tcp_ms: conn.tcp_ms,
+            tls_ms: session.tls_ms,
+            total_ms,
+            starttls,
+            tls: session.summary,
+            cert,
+            warnings: Vec::new(),
+        };
+        report.warnings = self.config.warning_rules.evaluate_tls_probe(&report);
+        Ok(report)
+    }
+}
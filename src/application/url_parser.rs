@@ -15,12 +15,12 @@ impl ParsedUrl {
         let url = Url::parse(input).map_err(|e| UdocError::input(format!("invalid URL: {}", e)))?;
 
         let scheme = url.scheme().to_string();
-        if scheme != "http" && scheme != "https" {
-            return Err(UdocError::input(format!("unsupported scheme '{}', expected http or https", scheme)));
+        if !matches!(scheme.as_str(), "http" | "https" | "ws" | "wss") {
+            return Err(UdocError::input(format!("unsupported scheme '{}', expected http, https, ws or wss", scheme)));
         }
 
         let host = url.host_str().ok_or_else(|| UdocError::input("missing host"))?.to_string();
-        let port = url.port_or_known_default().unwrap_or(if scheme == "https" { 443 } else { 80 });
+        let port = url.port_or_known_default().unwrap_or(if matches!(scheme.as_str(), "https" | "wss") { 443 } else { 80 });
 
         let path = url.path();
         let path_and_query = match url.query() {
@@ -33,7 +33,11 @@ impl ParsedUrl {
     }
 
     pub fn is_https(&self) -> bool {
-        self.scheme == "https"
+        self.scheme == "https" || self.scheme == "wss"
+    }
+
+    pub fn is_websocket(&self) -> bool {
+        self.scheme == "ws" || self.scheme == "wss"
     }
 
     pub fn resolve_redirect(&self, location: &str) -> Result<ParsedUrl, UdocError> {
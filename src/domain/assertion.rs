@@ -0,0 +1,40 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeaderMatch {
+    Present,
+    Equals(String),
+    Contains(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Assertion {
+    Status(Vec<String>),
+    Header { name: String, expect: HeaderMatch },
+    BodyContains(String),
+    MaxTtfb(f64),
+    MaxTotal(f64),
+    MinCertDays(i64),
+}
+
+impl Assertion {
+    pub fn describe(&self) -> String {
+        match self {
+            Assertion::Status(codes) => format!("status in {}", codes.join(",")),
+            Assertion::Header { name, expect: HeaderMatch::Present } => format!("header {} present", name),
+            Assertion::Header { name, expect: HeaderMatch::Equals(v) } => format!("header {} = {}", name, v),
+            Assertion::Header { name, expect: HeaderMatch::Contains(v) } => format!("header {} ~ {}", name, v),
+            Assertion::BodyContains(s) => format!("body contains {:?}", s),
+            Assertion::MaxTtfb(ms) => format!("ttfb <= {}ms", ms),
+            Assertion::MaxTotal(ms) => format!("total <= {}ms", ms),
+            Assertion::MinCertDays(days) => format!("cert days_left >= {}", days),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AssertionResult {
+    pub assertion: String,
+    pub passed: bool,
+    pub actual: String,
+}
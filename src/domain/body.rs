@@ -0,0 +1,44 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyKind {
+    Empty,
+    Html,
+    Json,
+    Xml,
+    Text,
+    Binary,
+}
+
+impl BodyKind {
+    pub fn is_text(&self) -> bool {
+        matches!(self, BodyKind::Html | BodyKind::Json | BodyKind::Xml | BodyKind::Text)
+    }
+}
+
+impl std::fmt::Display for BodyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            BodyKind::Empty => "empty",
+            BodyKind::Html => "html",
+            BodyKind::Json => "json",
+            BodyKind::Xml => "xml",
+            BodyKind::Text => "text",
+            BodyKind::Binary => "binary",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BodySummary {
+    pub length: usize,
+    pub sha256: Option<String>,
+    pub kind: BodyKind,
+    pub content_type: Option<String>,
+    pub truncated: bool,
+    pub preview: Option<String>,
+    pub json: Option<serde_json::Value>,
+    pub saved_to: Option<String>,
+}
@@ -0,0 +1,56 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Revalidation {
+    pub validator: String,
+    pub status: u16,
+    pub not_modified: bool,
+    pub ttfb_ms: f64,
+    pub body_ms: f64,
+    pub original_ttfb_ms: f64,
+    pub original_body_ms: f64,
+    pub body_bytes: usize,
+}
+
+impl Revalidation {
+    pub fn saved_ms(&self) -> f64 {
+        self.original_body_ms - self.body_ms
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CacheSummary {
+    pub cacheable: bool,
+    pub cache_control: Option<String>,
+    pub cdn: Option<String>,
+    pub status: Option<String>,
+    pub status_source: Option<String>,
+    pub pop: Option<String>,
+    pub age_s: Option<u64>,
+    pub freshness_lifetime_s: Option<u64>,
+    pub freshness_source: Option<String>,
+    pub ttl_s: Option<i64>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub vary: Option<String>,
+    pub via: Option<String>,
+    pub revalidation: Option<Revalidation>,
+}
+
+impl CacheSummary {
+    /// No caching headers, CDN hints or revalidation result: nothing worth a CACHE section.
+    pub fn is_empty(&self) -> bool {
+        !self.cacheable
+            && self.cache_control.is_none()
+            && self.cdn.is_none()
+            && self.status.is_none()
+            && self.pop.is_none()
+            && self.age_s.is_none()
+            && self.freshness_lifetime_s.is_none()
+            && self.etag.is_none()
+            && self.last_modified.is_none()
+            && self.vary.is_none()
+            && self.via.is_none()
+            && self.revalidation.is_none()
+    }
+}
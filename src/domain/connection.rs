@@ -0,0 +1,29 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SequentialRequest {
+    pub index: usize,
+    pub status: u16,
+    pub reused: bool,
+    pub setup_ms: f64,
+    pub ttfb_ms: f64,
+    pub body_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionProbe {
+    pub requested: usize,
+    pub requests: Vec<SequentialRequest>,
+    pub closed_after: Option<usize>,
+}
+
+impl ConnectionProbe {
+    pub fn cold_ms(&self) -> Option<f64> {
+        self.requests.first().map(|r| r.setup_ms + r.ttfb_ms)
+    }
+
+    pub fn warm_ttfb_ms(&self) -> Option<f64> {
+        let warm: Vec<f64> = self.requests.iter().skip(1).filter(|r| r.reused).map(|r| r.ttfb_ms).collect();
+        (!warm.is_empty()).then(|| warm.iter().sum::<f64>() / warm.len() as f64)
+    }
+}
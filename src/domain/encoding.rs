@@ -0,0 +1,37 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BodyEncoding {
+    pub encoding: String,
+    pub wire_bytes: usize,
+    pub decoded_bytes: usize,
+    pub ratio: Option<f64>,
+    pub truncated: bool,
+    pub decode_limit_hit: bool,
+    pub error: Option<String>,
+}
+
+impl BodyEncoding {
+    pub fn new(encoding: String, wire_bytes: usize, decoded_bytes: usize, truncated: bool, error: Option<String>) -> Self {
+        let ratio = if wire_bytes > 0 && decoded_bytes > 0 {
+            Some(decoded_bytes as f64 / wire_bytes as f64)
+        } else {
+            None
+        };
+        Self { encoding, wire_bytes, decoded_bytes, ratio, truncated, decode_limit_hit: false, error }
+    }
+
+    pub fn savings_percent(&self) -> Option<f64> {
+        self.ratio.map(|r| (1.0 - 1.0 / r) * 100.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EncodingProbe {
+    pub offered: String,
+    pub status: u16,
+    pub content_encoding: Option<String>,
+    pub supported: bool,
+    pub wire_bytes: usize,
+    pub ttfb_ms: f64,
+}
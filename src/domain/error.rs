@@ -8,6 +8,8 @@ pub enum ErrorClass {
     Tls,
     Http,
     Timeout,
+    Assertion,
+    Warning,
     Other,
 }
 
@@ -20,6 +22,8 @@ impl ErrorClass {
             ErrorClass::Tls => 5,
             ErrorClass::Http => 6,
             ErrorClass::Timeout => 7,
+            ErrorClass::Assertion => 8,
+            ErrorClass::Warning => 9,
             ErrorClass::Other => 1,
         }
     }
@@ -32,6 +36,8 @@ impl ErrorClass {
             ErrorClass::Tls => "TLS",
             ErrorClass::Http => "HTTP",
             ErrorClass::Timeout => "TIMEOUT",
+            ErrorClass::Assertion => "ASSERT",
+            ErrorClass::Warning => "WARN",
             ErrorClass::Other => "ERROR",
         }
     }
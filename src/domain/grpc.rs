@@ -0,0 +1,109 @@
+use serde::Serialize;
+
+pub const GRPC_HEALTH_PATH: &str = "/grpc.health.v1.Health/Check";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ServingStatus {
+    Unknown,
+    Serving,
+    NotServing,
+    ServiceUnknown,
+}
+
+impl ServingStatus {
+    pub fn from_wire(value: u64) -> Option<Self> {
+        match value {
+            0 => Some(ServingStatus::Unknown),
+            1 => Some(ServingStatus::Serving),
+            2 => Some(ServingStatus::NotServing),
+            3 => Some(ServingStatus::ServiceUnknown),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ServingStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServingStatus::Unknown => write!(f, "UNKNOWN"),
+            ServingStatus::Serving => write!(f, "SERVING"),
+            ServingStatus::NotServing => write!(f, "NOT_SERVING"),
+            ServingStatus::ServiceUnknown => write!(f, "SERVICE_UNKNOWN"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrpcStatusSource {
+    Trailers,
+    Headers,
+    HttpStatus,
+}
+
+impl std::fmt::Display for GrpcStatusSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GrpcStatusSource::Trailers => write!(f, "trailers"),
+            GrpcStatusSource::Headers => write!(f, "headers, trailers-only"),
+            GrpcStatusSource::HttpStatus => write!(f, "mapped from HTTP status"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GrpcHealth {
+    pub service: String,
+    pub content_type: Option<String>,
+    pub grpc_status: Option<u32>,
+    pub grpc_status_name: Option<&'static str>,
+    pub grpc_message: Option<String>,
+    pub status_source: Option<GrpcStatusSource>,
+    pub serving_status: Option<ServingStatus>,
+    pub decode_error: Option<String>,
+}
+
+impl GrpcHealth {
+    pub fn is_grpc_response(&self) -> bool {
+        self.content_type.as_deref().is_some_and(|ct| ct.starts_with("application/grpc"))
+    }
+
+    pub fn service_label(&self) -> &str {
+        if self.service.is_empty() { "(server)" } else { &self.service }
+    }
+}
+
+pub fn grpc_status_name(code: u32) -> &'static str {
+    match code {
+        0 => "OK",
+        1 => "CANCELLED",
+        2 => "UNKNOWN",
+        3 => "INVALID_ARGUMENT",
+        4 => "DEADLINE_EXCEEDED",
+        5 => "NOT_FOUND",
+        6 => "ALREADY_EXISTS",
+        7 => "PERMISSION_DENIED",
+        8 => "RESOURCE_EXHAUSTED",
+        9 => "FAILED_PRECONDITION",
+        10 => "ABORTED",
+        11 => "OUT_OF_RANGE",
+        12 => "UNIMPLEMENTED",
+        13 => "INTERNAL",
+        14 => "UNAVAILABLE",
+        15 => "DATA_LOSS",
+        16 => "UNAUTHENTICATED",
+        _ => "UNRECOGNIZED",
+    }
+}
+
+pub fn grpc_status_for_http(status: u16) -> u32 {
+    match status {
+        400 => 13,
+        401 => 16,
+        403 => 7,
+        404 => 12,
+        429 | 502 | 503 | 504 => 14,
+        _ => 2,
+    }
+}
@@ -0,0 +1,57 @@
+use serde::Serialize;
+use serde::ser::{SerializeSeq, Serializer};
+
+#[derive(Debug, Clone, Default)]
+pub struct HeaderList(Vec<(String, String)>);
+
+impl HeaderList {
+    pub fn new() -> Self { Self(Vec::new()) }
+
+    pub fn from_pairs(pairs: &[(&str, &str)]) -> Self {
+        let mut list = Self::new();
+        for (name, value) in pairs {
+            list.push(name, value);
+        }
+        list
+    }
+
+    pub fn push(&mut self, name: &str, value: &str) {
+        self.0.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.0.iter().filter(move |(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+
+    pub fn len(&self) -> usize { self.0.len() }
+
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+}
+
+#[derive(Serialize)]
+struct HeaderEntry<'a> {
+    name: &'a str,
+    value: &'a str,
+}
+
+impl Serialize for HeaderList {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for (name, value) in &self.0 {
+            seq.serialize_element(&HeaderEntry { name, value })?;
+        }
+        seq.end()
+    }
+}
@@ -20,3 +20,36 @@ impl HttpSummary {
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtocolUpgrade {
+    pub protocol: String,
+    pub switched: bool,
+    pub status: u16,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct H2Settings {
+    pub header_table_size: Option<u32>,
+    pub enable_push: Option<u32>,
+    pub max_concurrent_streams: Option<u32>,
+    pub initial_window_size: Option<u32>,
+    pub max_frame_size: Option<u32>,
+    pub max_header_list_size: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct H2ErrorFrame {
+    pub stream_id: u32,
+    pub code: u32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct H2Session {
+    pub server_settings: H2Settings,
+    pub goaway: Option<H2ErrorFrame>,
+    pub rst_stream: Vec<H2ErrorFrame>,
+    pub data_frames: u64,
+    pub data_bytes: u64,
+}
@@ -6,12 +6,46 @@ mod cert;
 mod redirect;
 mod target;
 mod error;
+mod encoding;
+mod body;
+mod headers;
+mod assertion;
+mod warning;
+mod security;
+mod cache;
+mod quic;
+mod connection;
+mod multiplex;
+mod proxy;
+mod proxy_protocol;
+mod tcp;
+mod tcping;
+mod tls_probe;
+mod websocket;
+mod grpc;
 
 pub use report::Report;
-pub use timing::{TimingBreakdown, HopTiming};
-pub use http::HttpSummary;
+pub use timing::{TimingBreakdown, HopTiming, ExchangeTiming, ServerTimingMetric};
+pub use http::{H2ErrorFrame, H2Session, H2Settings, HttpSummary, ProtocolUpgrade};
 pub use tls::TlsSummary;
 pub use cert::CertSummary;
 pub use redirect::RedirectHop;
-pub use target::{ResolvedTarget, IpFamily};
+pub use target::{ResolvedTarget, IpFamily, host_port};
 pub use error::{ErrorClass, UdocError};
+pub use encoding::{BodyEncoding, EncodingProbe};
+pub use body::{BodyKind, BodySummary};
+pub use headers::HeaderList;
+pub use assertion::{Assertion, AssertionResult, HeaderMatch};
+pub use warning::{Severity, Warning, WarningRules};
+pub use security::SecurityFinding;
+pub use cache::{CacheSummary, Revalidation};
+pub use quic::{AltService, QuicSummary};
+pub use connection::{ConnectionProbe, SequentialRequest};
+pub use multiplex::{MultiplexProbe, StreamTiming};
+pub use proxy::{NoProxy, ProxyScheme, ProxySpec, ProxyTiming};
+pub use proxy_protocol::{ProxyProtocolHeader, ProxyProtocolSpec, ProxyProtocolVersion};
+pub use tcp::{FastOpenFailure, TcpInfo, TcpOptions, TcpStats};
+pub use tcping::{LatencyStats, TcpingAttempt, TcpingSummary};
+pub use tls_probe::{StartTlsOutcome, StartTlsProtocol, TlsProbeReport};
+pub use websocket::{WebSocketOptions, WebSocketTransport, WebSocketUpgrade};
+pub use grpc::{grpc_status_for_http, grpc_status_name, GrpcHealth, GrpcStatusSource, ServingStatus, GRPC_HEALTH_PATH};
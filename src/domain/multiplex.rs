@@ -0,0 +1,133 @@
+use serde::Serialize;
+use super::H2ErrorFrame;
+
+const OVERLAP_TOLERANCE_MS: f64 = 0.5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamTiming {
+    pub index: usize,
+    pub path: String,
+    pub status: Option<u16>,
+    pub started_ms: f64,
+    pub ttfb_ms: Option<f64>,
+    pub done_ms: Option<f64>,
+    pub bytes: u64,
+    pub error: Option<String>,
+}
+
+impl StreamTiming {
+    pub fn wait_ms(&self) -> Option<f64> {
+        self.ttfb_ms.map(|t| t - self.started_ms)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MultiplexProbe {
+    pub requested: usize,
+    pub max_concurrent_streams: Option<u32>,
+    pub in_flight: usize,
+    pub total_ms: f64,
+    pub streams: Vec<StreamTiming>,
+    pub goaway: Option<H2ErrorFrame>,
+    pub rst_stream: Vec<H2ErrorFrame>,
+}
+
+impl MultiplexProbe {
+    pub fn completed(&self) -> usize {
+        self.streams.iter().filter(|s| s.done_ms.is_some()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.streams.len() - self.completed()
+    }
+
+    pub fn limited_by_server(&self) -> bool {
+        self.in_flight < self.requested
+    }
+
+    pub fn ttfb_spread_ms(&self) -> Option<f64> {
+        let waits: Vec<f64> = self.streams.iter().filter_map(|s| s.wait_ms()).collect();
+        let min = waits.iter().copied().reduce(f64::min)?;
+        let max = waits.iter().copied().reduce(f64::max)?;
+        Some(max - min)
+    }
+
+    // Streams opened together whose responses only start once the previous one has
+    // finished are being served one at a time, whatever the server advertises.
+    pub fn head_of_line_blocked(&self) -> bool {
+        let Some(first_done) = self.streams.iter().filter_map(|s| s.done_ms).reduce(f64::min) else { return false };
+        let mut concurrent: Vec<&StreamTiming> = self.streams.iter()
+            .filter(|s| s.done_ms.is_some() && s.started_ms < first_done)
+            .collect();
+        if concurrent.len() < 2 { return false; }
+        concurrent.sort_by(|a, b| a.ttfb_ms.partial_cmp(&b.ttfb_ms).unwrap_or(std::cmp::Ordering::Equal));
+
+        let serialized = concurrent.windows(2).all(|pair| match (pair[0].done_ms, pair[1].ttfb_ms) {
+            (Some(done), Some(ttfb)) => ttfb + OVERLAP_TOLERANCE_MS >= done,
+            _ => false,
+        });
+        serialized && self.ttfb_spread_ms().unwrap_or(0.0) >= 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(index: usize, started_ms: f64, ttfb_ms: f64, done_ms: f64) -> StreamTiming {
+        StreamTiming {
+            index,
+            path: "/".to_string(),
+            status: Some(200),
+            started_ms,
+            ttfb_ms: Some(ttfb_ms),
+            done_ms: Some(done_ms),
+            bytes: 2,
+            error: None,
+        }
+    }
+
+    fn probe(streams: Vec<StreamTiming>) -> MultiplexProbe {
+        MultiplexProbe {
+            requested: streams.len(),
+            max_concurrent_streams: Some(100),
+            in_flight: streams.len(),
+            total_ms: streams.iter().filter_map(|s| s.done_ms).fold(0.0, f64::max),
+            streams,
+            goaway: None,
+            rst_stream: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn serialized_responses_are_head_of_line_blocked() {
+        let mux = probe(vec![stream(1, 0.0, 10.0, 20.0), stream(2, 0.1, 20.0, 30.0), stream(3, 0.2, 30.5, 40.0)]);
+        assert!(mux.head_of_line_blocked());
+    }
+
+    #[test]
+    fn overlapping_responses_are_not() {
+        let mux = probe(vec![stream(1, 0.0, 10.0, 20.0), stream(2, 0.1, 12.0, 22.0), stream(3, 0.2, 21.0, 30.0)]);
+        assert!(!mux.head_of_line_blocked());
+    }
+
+    #[test]
+    fn overlap_tolerance_edge() {
+        // The next response may start up to 0.5 ms before the previous one finished.
+        assert!(probe(vec![stream(1, 0.0, 10.0, 20.0), stream(2, 0.0, 19.5, 30.0)]).head_of_line_blocked());
+        assert!(!probe(vec![stream(1, 0.0, 10.0, 20.0), stream(2, 0.0, 19.25, 30.0)]).head_of_line_blocked());
+    }
+
+    #[test]
+    fn needs_two_concurrent_completed_streams_and_a_real_spread() {
+        // Opened after the first stream finished, so it never competed with it.
+        assert!(!probe(vec![stream(1, 0.0, 10.0, 20.0), stream(2, 25.0, 30.0, 40.0)]).head_of_line_blocked());
+
+        let mut failed = stream(2, 0.0, 20.0, 30.0);
+        failed.done_ms = None;
+        assert!(!probe(vec![stream(1, 0.0, 10.0, 20.0), failed]).head_of_line_blocked());
+
+        // Tiny bodies finish back to back; under 1 ms of spread is noise, not blocking.
+        assert!(!probe(vec![stream(1, 0.0, 1.0, 1.2), stream(2, 0.0, 1.3, 1.5)]).head_of_line_blocked());
+    }
+}
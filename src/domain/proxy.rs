@@ -0,0 +1,196 @@
+use std::net::IpAddr;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyScheme {
+    Http,
+    Https,
+    Socks5h,
+}
+
+impl ProxyScheme {
+    pub fn default_port(&self) -> u16 {
+        match self {
+            ProxyScheme::Http => 80,
+            ProxyScheme::Https => 443,
+            ProxyScheme::Socks5h => 1080,
+        }
+    }
+}
+
+impl std::fmt::Display for ProxyScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProxyScheme::Http => write!(f, "http"),
+            ProxyScheme::Https => write!(f, "https"),
+            ProxyScheme::Socks5h => write!(f, "socks5h"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProxySpec {
+    pub scheme: ProxyScheme,
+    pub host: String,
+    pub port: u16,
+    pub credentials: Option<(String, String)>,
+}
+
+impl ProxySpec {
+    pub fn display_url(&self) -> String {
+        let host = if self.host.contains(':') { format!("[{}]", self.host) } else { self.host.clone() };
+        format!("{}://{}:{}", self.scheme, host, self.port)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum NoProxyEntry {
+    Any,
+    Domain { name: String, port: Option<u16> },
+    Network { addr: IpAddr, prefix: u8 },
+}
+
+/// Hosts that bypass the proxy, parsed like curl's `NO_PROXY`: `*`, domain names
+/// (matching the name and its subdomains, with or without a leading `.` or `*.`),
+/// optionally restricted to one port with `host:port`, and IP addresses or CIDR ranges.
+/// IP entries only match literal IP targets; names are never resolved to check them.
+#[derive(Debug, Clone, Default)]
+pub struct NoProxy {
+    entries: Vec<NoProxyEntry>,
+}
+
+impl NoProxy {
+    pub fn parse(value: &str) -> Self {
+        let entries = value.split([',', ' '])
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+            .map(parse_no_proxy_entry)
+            .collect();
+        Self { entries }
+    }
+
+    pub fn matches(&self, host: &str, port: u16) -> bool {
+        let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+        let ip = host.parse::<IpAddr>().ok();
+        self.entries.iter().any(|entry| match entry {
+            NoProxyEntry::Any => true,
+            NoProxyEntry::Domain { name, port: entry_port } => {
+                entry_port.is_none_or(|p| p == port)
+                    && (host == *name || host.strip_suffix(name.as_str()).is_some_and(|rest| rest.ends_with('.')))
+            }
+            NoProxyEntry::Network { addr, prefix } => ip.is_some_and(|ip| in_network(ip, *addr, *prefix)),
+        })
+    }
+}
+
+fn parse_no_proxy_entry(entry: &str) -> NoProxyEntry {
+    if entry == "*" {
+        return NoProxyEntry::Any;
+    }
+    if let Some((addr, prefix)) = entry.split_once('/')
+        && let Ok(addr) = addr.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>()
+        && let Ok(prefix) = prefix.parse::<u8>()
+        && prefix <= max_prefix(addr) {
+        return NoProxyEntry::Network { addr, prefix };
+    }
+    let bare = entry.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(addr) = bare.parse::<IpAddr>() {
+        return NoProxyEntry::Network { addr, prefix: max_prefix(addr) };
+    }
+
+    let (name, port) = match entry.rsplit_once(':') {
+        Some((name, port)) if !name.contains(':') => match port.parse::<u16>() {
+            Ok(port) => (name, Some(port)),
+            Err(_) => (entry, None),
+        },
+        Some((name, port)) if name.starts_with('[') && name.ends_with(']') => match (name[1..name.len() - 1].parse::<IpAddr>(), port.parse::<u16>()) {
+            (Ok(addr), Ok(port)) => return NoProxyEntry::Domain { name: addr.to_string(), port: Some(port) },
+            _ => (entry, None),
+        },
+        _ => (entry, None),
+    };
+    NoProxyEntry::Domain {
+        name: name.trim_start_matches("*.").trim_start_matches('.').to_ascii_lowercase(),
+        port,
+    }
+}
+
+fn max_prefix(addr: IpAddr) -> u8 {
+    if addr.is_ipv4() { 32 } else { 128 }
+}
+
+fn in_network(ip: IpAddr, network: IpAddr, prefix: u8) -> bool {
+    let (ip, network, bits) = match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => (u128::from(ip.to_bits()), u128::from(network.to_bits()), 32),
+        (IpAddr::V6(ip), IpAddr::V6(network)) => (ip.to_bits(), network.to_bits(), 128),
+        _ => return false,
+    };
+    let shift = bits - u32::from(prefix);
+    shift >= bits || (ip >> shift) == (network >> shift)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyTiming {
+    pub url: String,
+    pub scheme: ProxyScheme,
+    pub ip: IpAddr,
+    pub port: u16,
+    pub dns_ms: f64,
+    pub tcp_ms: f64,
+    pub tcp_deferred: bool,
+    pub tls_ms: Option<f64>,
+    pub connect_ms: f64,
+    pub status: Option<u16>,
+    pub reply: String,
+}
+
+impl ProxyTiming {
+    pub fn total(&self) -> f64 {
+        self.dns_ms + self.tcp_ms + self.tls_ms.unwrap_or(0.0) + self.connect_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_domains_and_subdomains() {
+        let no_proxy = NoProxy::parse("example.com, .corp.net *.svc");
+        assert!(no_proxy.matches("example.com", 443));
+        assert!(no_proxy.matches("API.Example.com", 443));
+        assert!(!no_proxy.matches("notexample.com", 443));
+        assert!(no_proxy.matches("corp.net", 80));
+        assert!(no_proxy.matches("a.b.corp.net", 80));
+        assert!(no_proxy.matches("db.svc", 5432));
+        assert!(NoProxy::parse("*").matches("anything", 1));
+        assert!(!NoProxy::default().matches("example.com", 443));
+    }
+
+    #[test]
+    fn restricts_entries_with_a_port() {
+        let no_proxy = NoProxy::parse("example.com:8443,10.0.0.1:80,[::1]:8080");
+        assert!(no_proxy.matches("example.com", 8443));
+        assert!(!no_proxy.matches("example.com", 443));
+        assert!(no_proxy.matches("10.0.0.1", 80));
+        assert!(!no_proxy.matches("10.0.0.1", 443));
+        assert!(no_proxy.matches("[::1]", 8080));
+        assert!(!no_proxy.matches("::1", 80));
+    }
+
+    #[test]
+    fn matches_ip_literals_and_cidr_ranges() {
+        let no_proxy = NoProxy::parse("192.168.0.0/16, 127.0.0.1, fd00::/8, ::1, 0.0.0.0/0x");
+        assert!(no_proxy.matches("192.168.44.1", 443));
+        assert!(!no_proxy.matches("192.169.0.1", 443));
+        assert!(no_proxy.matches("127.0.0.1", 80));
+        assert!(!no_proxy.matches("127.0.0.2", 80));
+        assert!(no_proxy.matches("[fd12:3456::1]", 443));
+        assert!(no_proxy.matches("::1", 443));
+        assert!(!no_proxy.matches("2001:db8::1", 443));
+        assert!(NoProxy::parse("0.0.0.0/0").matches("8.8.8.8", 53));
+        assert!(!NoProxy::parse("0.0.0.0/0").matches("::1", 53));
+        assert!(!NoProxy::parse("10.0.0.0/8").matches("ten.example.com", 80));
+    }
+}
@@ -0,0 +1,39 @@
+use std::net::SocketAddr;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyProtocolVersion {
+    V1,
+    V2,
+}
+
+impl std::fmt::Display for ProxyProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProxyProtocolVersion::V1 => write!(f, "v1"),
+            ProxyProtocolVersion::V2 => write!(f, "v2"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ProxyProtocolSpec {
+    pub version: ProxyProtocolVersion,
+    pub source: Option<SocketAddr>,
+    pub destination: Option<SocketAddr>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyProtocolHeader {
+    pub version: ProxyProtocolVersion,
+    pub source: Option<SocketAddr>,
+    pub destination: Option<SocketAddr>,
+    pub bytes: usize,
+}
+
+impl ProxyProtocolHeader {
+    pub fn is_unknown(&self) -> bool {
+        self.source.is_none() || self.destination.is_none()
+    }
+}
@@ -0,0 +1,29 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuicSummary {
+    pub handshake_ms: f64,
+    pub zero_rtt_attempted: bool,
+    pub early_data_accepted: Option<bool>,
+    pub via_alt_svc: bool,
+}
+
+impl QuicSummary {
+    pub fn mode(&self) -> &'static str {
+        if self.early_data_accepted == Some(true) { "0-RTT" } else { "1-RTT" }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AltService {
+    pub protocol: String,
+    pub host: Option<String>,
+    pub port: u16,
+    pub max_age: Option<u64>,
+}
+
+impl AltService {
+    pub fn is_h3(&self) -> bool {
+        self.protocol == "h3"
+    }
+}
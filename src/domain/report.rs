@@ -1,4 +1,4 @@
-use super::{CertSummary, HttpSummary, RedirectHop, ResolvedTarget, TimingBreakdown, TlsSummary};
+use super::{AltService, AssertionResult, ConnectionProbe, H2Session, BodyEncoding, BodySummary, CacheSummary, CertSummary, EncodingProbe, HeaderList, HttpSummary, MultiplexProbe, ProtocolUpgrade, QuicSummary, RedirectHop, ResolvedTarget, SecurityFinding, TcpStats, TimingBreakdown, TlsSummary, Warning, WebSocketUpgrade, GrpcHealth};
 use serde::Serialize;
 
 #[derive(Debug, Clone, Serialize)]
@@ -10,24 +10,95 @@ pub struct Report {
     pub redirects: Vec<RedirectHop>,
     pub timings: TimingBreakdown,
     pub http: HttpSummary,
+    pub headers: HeaderList,
     pub tls: Option<TlsSummary>,
     pub cert: Option<CertSummary>,
     pub was_downgrade: bool,
+    pub encoding: Option<BodyEncoding>,
+    pub encoding_probe: Vec<EncodingProbe>,
+    pub body: Option<BodySummary>,
+    #[serde(skip)]
+    pub body_bytes: Vec<u8>,
+    pub assertions: Vec<AssertionResult>,
+    pub warnings: Vec<Warning>,
+    pub security: Vec<SecurityFinding>,
+    pub cache: CacheSummary,
+    pub upgrade: Option<ProtocolUpgrade>,
+    pub quic: Option<QuicSummary>,
+    pub alt_svc: Vec<AltService>,
+    pub h2: Option<H2Session>,
+    pub connection: Option<ConnectionProbe>,
+    pub multiplex: Option<MultiplexProbe>,
+    pub tcp: Option<TcpStats>,
+    pub websocket: Option<WebSocketUpgrade>,
+    pub grpc: Option<GrpcHealth>,
 }
 
 impl Report {
-    pub fn bottleneck(&self) -> &'static str {
+    pub fn assertions_failed(&self) -> usize {
+        self.assertions.iter().filter(|a| !a.passed).count()
+    }
+
+    pub fn bottleneck(&self) -> String {
         let dns = self.timings.dns_ms;
         let tcp = self.timings.tcp_ms;
         let tls = self.timings.tls_ms.unwrap_or(0.0);
+        let quic = self.timings.quic_ms.unwrap_or(0.0);
         let ttfb = self.timings.ttfb_ms;
+        let proxy = self.timings.proxy_ms.unwrap_or(0.0);
+
+        let max = dns.max(tcp).max(tls).max(quic).max(ttfb).max(proxy);
+        if max < 10.0 { return "none (fast)".to_string(); }
+        if proxy >= max { return "proxy".to_string(); }
 
-        let max = dns.max(tcp).max(tls).max(ttfb);
-        if max < 10.0 { return "none (fast)"; }
+        if ttfb >= max {
+            return match self.timings.slowest_server_metric() {
+                Some(m) => format!("ttfb (server: {} {:.0}ms)", m.name, m.dur_ms.unwrap_or(0.0)),
+                None => "ttfb (server)".to_string(),
+            };
+        }
+        if dns >= max { return "dns".to_string(); }
+        if quic >= max { return "quic".to_string(); }
+        if tls >= max { return "tls".to_string(); }
+        "tcp".to_string()
+    }
+}
 
-        if ttfb >= dns && ttfb >= tcp && ttfb >= tls { return "ttfb (server)"; }
-        if dns >= tcp && dns >= tls { return "dns"; }
-        if tls >= tcp { return "tls"; }
-        "tcp"
+#[cfg(test)]
+impl Report {
+    /// A plain-HTTP 200 report with empty headers and body, for rule and assertion tests.
+    pub(crate) fn fixture() -> Self {
+        use super::{ExchangeTiming, ResolvedTarget};
+        let ip = std::net::IpAddr::from([127, 0, 0, 1]);
+        Self {
+            input_url: "http://example.test/".to_string(),
+            final_url: "http://example.test/".to_string(),
+            host: "example.test".to_string(),
+            resolved: ResolvedTarget::new(ip, 80, vec![ip]),
+            redirects: Vec::new(),
+            timings: TimingBreakdown::new(0.0, 0.0, None, ExchangeTiming::default(), 0.0),
+            http: HttpSummary::new(200, Some("OK".to_string()), "http/1.1".to_string(), "HTTP".to_string()),
+            headers: HeaderList::new(),
+            tls: None,
+            cert: None,
+            was_downgrade: false,
+            encoding: None,
+            encoding_probe: Vec::new(),
+            body: None,
+            body_bytes: Vec::new(),
+            assertions: Vec::new(),
+            warnings: Vec::new(),
+            security: Vec::new(),
+            cache: CacheSummary::default(),
+            upgrade: None,
+            quic: None,
+            alt_svc: Vec::new(),
+            h2: None,
+            connection: None,
+            multiplex: None,
+            tcp: None,
+            websocket: None,
+            grpc: None,
+        }
     }
 }
@@ -0,0 +1,20 @@
+use serde::Serialize;
+use super::Severity;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityFinding {
+    pub check: String,
+    pub passed: bool,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl SecurityFinding {
+    pub fn pass(check: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { check: check.into(), passed: true, severity: Severity::Info, message: message.into() }
+    }
+
+    pub fn fail(check: impl Into<String>, severity: Severity, message: impl Into<String>) -> Self {
+        Self { check: check.into(), passed: false, severity, message: message.into() }
+    }
+}
@@ -5,6 +5,7 @@ use serde::Serialize;
 pub enum IpFamily {
     IPv4,
     IPv6,
+    Unix,
 }
 
 impl std::fmt::Display for IpFamily {
@@ -12,16 +13,23 @@ impl std::fmt::Display for IpFamily {
         match self {
             IpFamily::IPv4 => write!(f, "ipv4"),
             IpFamily::IPv6 => write!(f, "ipv6"),
+            IpFamily::Unix => write!(f, "unix"),
         }
     }
 }
 
+/// `host:port`, bracketing IPv6 literals.
+pub fn host_port(host: &str, port: u16) -> String {
+    if host.contains(':') { format!("[{}]:{}", host, port) } else { format!("{}:{}", host, port) }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct ResolvedTarget {
-    pub ip: IpAddr,
+    pub ip: Option<IpAddr>,
     pub port: u16,
     pub family: IpFamily,
     pub all_ips: Vec<IpAddr>,
+    pub unix_socket: Option<String>,
 }
 
 impl ResolvedTarget {
@@ -30,21 +38,35 @@ impl ResolvedTarget {
             IpAddr::V4(_) => IpFamily::IPv4,
             IpAddr::V6(_) => IpFamily::IPv6,
         };
-        Self { ip, port, family, all_ips }
+        Self { ip: Some(ip), port, family, all_ips, unix_socket: None }
+    }
+
+    pub fn unix(path: &str, port: u16) -> Self {
+        Self { ip: None, port, family: IpFamily::Unix, all_ips: Vec::new(), unix_socket: Some(path.to_string()) }
+    }
+
+    pub fn address(&self) -> String {
+        match (&self.unix_socket, self.ip) {
+            (Some(path), _) => path.clone(),
+            (None, Some(ip)) => ip.to_string(),
+            (None, None) => "-".to_string(),
+        }
     }
 
     pub fn as_socket_str(&self) -> String {
-        match self.ip {
-            IpAddr::V4(v4) => format!("{}:{}", v4, self.port),
-            IpAddr::V6(v6) => format!("[{}]:{}", v6, self.port),
+        match (&self.unix_socket, self.ip) {
+            (Some(path), _) => format!("unix:{}", path),
+            (None, Some(IpAddr::V4(v4))) => format!("{}:{}", v4, self.port),
+            (None, Some(IpAddr::V6(v6))) => format!("[{}]:{}", v6, self.port),
+            (None, None) => "-".to_string(),
         }
     }
 
     pub fn ips_short(&self) -> String {
         if self.all_ips.len() <= 1 {
-            return self.ip.to_string();
+            return self.address();
         }
         let extra = self.all_ips.len() - 1;
-        format!("{} (+{})", self.ip, extra)
+        format!("{} (+{})", self.address(), extra)
     }
 }
@@ -0,0 +1,97 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TcpInfo {
+    pub rtt_ms: f64,
+    pub rttvar_ms: f64,
+    pub min_rtt_ms: f64,
+    pub retransmits: u32,
+    pub lost: u32,
+    pub cwnd: u32,
+    pub mss: u32,
+    pub pmtu: u32,
+    pub delivery_rate: u64,
+    pub syn_data: bool,
+    pub fastopen_fail: Option<FastOpenFailure>,
+}
+
+/// Why the kernel did not complete a TCP Fast Open connect (`tcpi_fastopen_client_fail`, Linux 5.5+).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FastOpenFailure {
+    CookieUnavailable,
+    DataNotAcked,
+    SynRetransmitted,
+}
+
+impl FastOpenFailure {
+    pub fn from_kernel(code: u8) -> Option<Self> {
+        match code {
+            1 => Some(FastOpenFailure::CookieUnavailable),
+            2 => Some(FastOpenFailure::DataNotAcked),
+            3 => Some(FastOpenFailure::SynRetransmitted),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for FastOpenFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FastOpenFailure::CookieUnavailable => write!(f, "no cookie cached yet, one was requested for next time"),
+            FastOpenFailure::DataNotAcked => write!(f, "cookie sent but the server did not accept the data in the SYN"),
+            FastOpenFailure::SynRetransmitted => write!(f, "the SYN with data was retransmitted, likely dropped on the path"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TcpOptions {
+    pub nodelay: bool,
+    pub keepalive: bool,
+    pub fastopen: bool,
+    pub send_buffer: Option<u32>,
+    pub recv_buffer: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TcpStats {
+    pub options: TcpOptions,
+    pub after_connect: TcpInfo,
+    pub after_response: Option<TcpInfo>,
+}
+
+impl TcpStats {
+    pub fn latest(&self) -> &TcpInfo {
+        self.after_response.as_ref().unwrap_or(&self.after_connect)
+    }
+
+    pub fn fastopen_used(&self) -> Option<bool> {
+        self.options.fastopen.then(|| self.latest().syn_data)
+    }
+
+    pub fn fastopen_failure(&self) -> Option<FastOpenFailure> {
+        self.options.fastopen.then(|| self.latest().fastopen_fail).flatten()
+    }
+
+    pub fn delivery_rate_mbps(&self) -> f64 {
+        self.latest().delivery_rate as f64 * 8.0 / 1_000_000.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_kernel_fastopen_fail_codes() {
+        assert_eq!(FastOpenFailure::from_kernel(0), None);
+        assert_eq!(FastOpenFailure::from_kernel(1), Some(FastOpenFailure::CookieUnavailable));
+        assert_eq!(FastOpenFailure::from_kernel(2), Some(FastOpenFailure::DataNotAcked));
+        assert_eq!(FastOpenFailure::from_kernel(3), Some(FastOpenFailure::SynRetransmitted));
+        assert_eq!(
+            serde_json::to_string(&FastOpenFailure::CookieUnavailable).unwrap(),
+            "\"cookie_unavailable\""
+        );
+    }
+}
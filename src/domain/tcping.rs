@@ -0,0 +1,143 @@
+use serde::Serialize;
+use super::{ErrorClass, ResolvedTarget, host_port};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TcpingAttempt {
+    pub seq: usize,
+    pub connect_ms: Option<f64>,
+    pub tls_ms: Option<f64>,
+    pub error: Option<String>,
+}
+
+impl TcpingAttempt {
+    pub fn ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct LatencyStats {
+    pub min_ms: f64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    pub p95_ms: f64,
+}
+
+impl LatencyStats {
+    pub fn from_samples(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() { return None; }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        // Nearest rank: the smallest sample with at least 95% of samples at or below it.
+        let p95 = sorted[(sorted.len() * 95).div_ceil(100) - 1];
+        Some(Self {
+            min_ms: sorted[0],
+            avg_ms: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            max_ms: sorted[sorted.len() - 1],
+            p95_ms: p95,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TcpingSummary {
+    pub host: String,
+    pub resolved: ResolvedTarget,
+    pub dns_ms: f64,
+    pub tls: bool,
+    pub attempts: Vec<TcpingAttempt>,
+}
+
+impl TcpingSummary {
+    pub fn target(&self) -> String {
+        host_port(&self.host, self.resolved.port)
+    }
+
+    pub fn sent(&self) -> usize {
+        self.attempts.len()
+    }
+
+    pub fn received(&self) -> usize {
+        self.attempts.iter().filter(|a| a.ok()).count()
+    }
+
+    pub fn loss_pct(&self) -> f64 {
+        if self.attempts.is_empty() { return 0.0; }
+        (self.sent() - self.received()) as f64 * 100.0 / self.sent() as f64
+    }
+
+    pub fn connect_stats(&self) -> Option<LatencyStats> {
+        let samples: Vec<f64> = self.attempts.iter().filter_map(|a| a.connect_ms).collect();
+        LatencyStats::from_samples(&samples)
+    }
+
+    pub fn tls_stats(&self) -> Option<LatencyStats> {
+        let samples: Vec<f64> = self.attempts.iter().filter_map(|a| a.tls_ms).collect();
+        LatencyStats::from_samples(&samples)
+    }
+
+    pub fn failure_class(&self) -> Option<ErrorClass> {
+        if self.received() > 0 { return None; }
+        let connected = self.attempts.iter().any(|a| a.connect_ms.is_some());
+        Some(if self.tls && connected { ErrorClass::Tls } else { ErrorClass::Tcp })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv6Addr};
+    use super::*;
+
+    fn attempt(seq: usize, connect_ms: Option<f64>, tls_ms: Option<f64>, error: Option<&str>) -> TcpingAttempt {
+        TcpingAttempt { seq, connect_ms, tls_ms, error: error.map(str::to_string) }
+    }
+
+    fn summary(host: &str, tls: bool, attempts: Vec<TcpingAttempt>) -> TcpingSummary {
+        let ip = host.parse().unwrap_or(IpAddr::from([192, 0, 2, 1]));
+        TcpingSummary { host: host.to_string(), resolved: ResolvedTarget::new(ip, 443, vec![ip]), dns_ms: 0.0, tls, attempts }
+    }
+
+    #[test]
+    fn p95_uses_the_nearest_rank() {
+        let samples: Vec<f64> = (1..=20).map(f64::from).collect();
+        let stats = LatencyStats::from_samples(&samples).unwrap();
+        assert_eq!((stats.min_ms, stats.avg_ms, stats.max_ms, stats.p95_ms), (1.0, 10.5, 20.0, 19.0));
+
+        let hundred: Vec<f64> = (1..=100).rev().map(f64::from).collect();
+        assert_eq!(LatencyStats::from_samples(&hundred).unwrap().p95_ms, 95.0);
+        assert_eq!(LatencyStats::from_samples(&[3.0, 1.0]).unwrap().p95_ms, 3.0);
+        assert_eq!(LatencyStats::from_samples(&[7.0]).unwrap().p95_ms, 7.0);
+        assert!(LatencyStats::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn loss_counts_every_failed_attempt() {
+        let s = summary("example.com", true, vec![
+            attempt(1, Some(10.0), Some(20.0), None),
+            attempt(2, None, None, Some("timed out")),
+            attempt(3, Some(12.0), None, Some("handshake failed")),
+            attempt(4, Some(14.0), Some(30.0), None),
+        ]);
+        assert_eq!((s.sent(), s.received(), s.loss_pct()), (4, 2, 50.0));
+        assert_eq!(s.connect_stats().unwrap().max_ms, 14.0);
+        assert_eq!(s.tls_stats().unwrap().avg_ms, 25.0);
+        assert_eq!(s.failure_class(), None);
+        assert_eq!(summary("example.com", false, Vec::new()).loss_pct(), 0.0);
+    }
+
+    #[test]
+    fn failure_class_blames_tls_only_after_a_connect() {
+        let refused = summary("example.com", true, vec![attempt(1, None, None, Some("refused"))]);
+        assert_eq!(refused.failure_class(), Some(ErrorClass::Tcp));
+        let handshake = summary("example.com", true, vec![attempt(1, Some(5.0), None, Some("bad cert"))]);
+        assert_eq!(handshake.failure_class(), Some(ErrorClass::Tls));
+        let plain = summary("example.com", false, vec![attempt(1, None, None, Some("timed out"))]);
+        assert_eq!(plain.failure_class(), Some(ErrorClass::Tcp));
+    }
+
+    #[test]
+    fn target_brackets_ipv6_hosts() {
+        assert_eq!(summary("example.com", false, Vec::new()).target(), "example.com:443");
+        assert_eq!(summary(&Ipv6Addr::LOCALHOST.to_string(), false, Vec::new()).target(), "[::1]:443");
+    }
+}
@@ -1,35 +1,119 @@
+use std::net::SocketAddr;
 use serde::Serialize;
+use super::{IpFamily, ProxyProtocolHeader, ProxyTiming, TcpStats};
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ExchangeTiming {
+    pub request_sent_ms: f64,
+    pub ttfb_ms: f64,
+    pub headers_ms: f64,
+    pub body_ms: f64,
+}
 
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct HopTiming {
+    pub family: Option<IpFamily>,
+    pub local_addr: Option<SocketAddr>,
+    pub reused: bool,
     pub dns_ms: f64,
     pub tcp_ms: f64,
+    pub tcp_deferred: bool,
     pub tls_ms: Option<f64>,
-    pub ttfb_ms: f64,
+    pub quic_ms: Option<f64>,
+    pub proxy: Option<ProxyTiming>,
+    pub tcp_info: Option<TcpStats>,
+    pub proxy_protocol: Option<ProxyProtocolHeader>,
+    #[serde(flatten)]
+    pub exchange: ExchangeTiming,
 }
 
 impl HopTiming {
     pub fn total(&self) -> f64 {
-        self.dns_ms + self.tcp_ms + self.tls_ms.unwrap_or(0.0) + self.ttfb_ms
+        self.setup_ms() + self.exchange.body_ms
+    }
+
+    pub fn setup_ms(&self) -> f64 {
+        self.dns_ms + self.tcp_ms + self.tls_ms.unwrap_or(0.0) + self.quic_ms.unwrap_or(0.0) + self.proxy.as_ref().map(|p| p.total()).unwrap_or(0.0)
     }
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerTimingMetric {
+    pub name: String,
+    pub dur_ms: Option<f64>,
+    pub desc: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct TimingBreakdown {
     pub dns_ms: f64,
     pub tcp_ms: f64,
+    pub tcp_deferred: bool,
     pub tls_ms: Option<f64>,
+    pub quic_ms: Option<f64>,
+    pub proxy_ms: Option<f64>,
+    pub proxy: Option<ProxyTiming>,
+    pub request_sent_ms: f64,
     pub ttfb_ms: f64,
+    pub headers_ms: f64,
+    pub body_ms: f64,
     pub total_ms: f64,
+    pub server_ms: Option<f64>,
+    pub network_ms: Option<f64>,
+    pub server_timing: Vec<ServerTimingMetric>,
     pub hops: Vec<HopTiming>,
 }
 
 impl TimingBreakdown {
-    pub fn new(dns_ms: f64, tcp_ms: f64, tls_ms: Option<f64>, ttfb_ms: f64, total_ms: f64) -> Self {
-        Self { dns_ms, tcp_ms, tls_ms, ttfb_ms, total_ms, hops: Vec::new() }
+    pub fn new(dns_ms: f64, tcp_ms: f64, tls_ms: Option<f64>, exchange: ExchangeTiming, total_ms: f64) -> Self {
+        Self {
+            dns_ms,
+            tcp_ms,
+            tcp_deferred: false,
+            tls_ms,
+            quic_ms: None,
+            proxy_ms: None,
+            proxy: None,
+            request_sent_ms: exchange.request_sent_ms,
+            ttfb_ms: exchange.ttfb_ms,
+            headers_ms: exchange.headers_ms,
+            body_ms: exchange.body_ms,
+            total_ms,
+            server_ms: None,
+            network_ms: None,
+            server_timing: Vec::new(),
+            hops: Vec::new(),
+        }
+    }
+
+    pub fn with_server_timing(mut self, metrics: Vec<ServerTimingMetric>) -> Self {
+        let total = metrics.iter().find(|m| m.name.eq_ignore_ascii_case("total")).and_then(|m| m.dur_ms);
+        let server_ms = total.or_else(|| metrics.iter().filter_map(|m| m.dur_ms).reduce(f64::max));
+        self.server_ms = server_ms.map(|s| s.min(self.ttfb_ms));
+        self.network_ms = self.server_ms.map(|s| self.ttfb_ms - s);
+        self.server_timing = metrics;
+        self
+    }
+
+    pub fn slowest_server_metric(&self) -> Option<&ServerTimingMetric> {
+        self.server_timing.iter()
+            .filter(|m| m.dur_ms.is_some() && !m.name.eq_ignore_ascii_case("total"))
+            .max_by(|a, b| a.dur_ms.partial_cmp(&b.dur_ms).unwrap_or(std::cmp::Ordering::Equal))
+    }
+
+    pub fn with_quic(mut self, quic_ms: Option<f64>) -> Self {
+        self.quic_ms = quic_ms;
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy_ms: Option<f64>, proxy: Option<ProxyTiming>) -> Self {
+        self.proxy_ms = proxy_ms;
+        self.proxy = proxy;
+        self
     }
 
     pub fn with_hops(mut self, hops: Vec<HopTiming>) -> Self {
+        self.tcp_deferred = hops.iter().any(|h| h.tcp_deferred);
         self.hops = hops;
         self
     }
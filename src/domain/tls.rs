@@ -4,14 +4,24 @@ use serde::Serialize;
 pub struct TlsSummary {
     pub version: String,
     pub alpn: Option<String>,
-    pub cipher: String,
+    pub alpn_offered: Vec<String>,
+    pub cipher: Option<String>,
     pub chain_len: usize,
     pub verified: bool,
 }
 
 impl TlsSummary {
-    pub fn new(version: String, alpn: Option<String>, cipher: String, chain_len: usize, verified: bool) -> Self {
-        Self { version, alpn, cipher, chain_len, verified }
+    pub fn new(version: String, alpn: Option<String>, cipher: Option<String>, chain_len: usize, verified: bool) -> Self {
+        Self { version, alpn, alpn_offered: Vec::new(), cipher, chain_len, verified }
+    }
+
+    pub fn with_alpn_offered(mut self, offered: Vec<String>) -> Self {
+        self.alpn_offered = offered;
+        self
+    }
+
+    pub fn alpn_missing(&self) -> bool {
+        self.alpn.is_none() && !self.alpn_offered.is_empty()
     }
 
     pub fn is_h2(&self) -> bool {
@@ -0,0 +1,75 @@
+use serde::Serialize;
+use super::{CertSummary, ResolvedTarget, TlsSummary, Warning, host_port};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StartTlsProtocol {
+    Smtp,
+    Imap,
+    Pop3,
+    Ftp,
+    Xmpp,
+    Postgres,
+    Ldap,
+}
+
+impl StartTlsProtocol {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "smtp" => Some(StartTlsProtocol::Smtp),
+            "imap" => Some(StartTlsProtocol::Imap),
+            "pop3" => Some(StartTlsProtocol::Pop3),
+            "ftp" => Some(StartTlsProtocol::Ftp),
+            "xmpp" => Some(StartTlsProtocol::Xmpp),
+            "postgres" | "postgresql" => Some(StartTlsProtocol::Postgres),
+            "ldap" => Some(StartTlsProtocol::Ldap),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for StartTlsProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartTlsProtocol::Smtp => write!(f, "smtp"),
+            StartTlsProtocol::Imap => write!(f, "imap"),
+            StartTlsProtocol::Pop3 => write!(f, "pop3"),
+            StartTlsProtocol::Ftp => write!(f, "ftp"),
+            StartTlsProtocol::Xmpp => write!(f, "xmpp"),
+            StartTlsProtocol::Postgres => write!(f, "postgres"),
+            StartTlsProtocol::Ldap => write!(f, "ldap"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StartTlsOutcome {
+    pub protocol: StartTlsProtocol,
+    pub greeting: Option<String>,
+    pub reply: String,
+    pub negotiate_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TlsProbeReport {
+    pub host: String,
+    pub resolved: ResolvedTarget,
+    pub dns_ms: f64,
+    pub tcp_ms: f64,
+    pub tls_ms: f64,
+    pub total_ms: f64,
+    pub starttls: Option<StartTlsOutcome>,
+    pub tls: TlsSummary,
+    pub cert: Option<CertSummary>,
+    pub warnings: Vec<Warning>,
+}
+
+impl TlsProbeReport {
+    pub fn target(&self) -> String {
+        host_port(&self.host, self.resolved.port)
+    }
+
+    pub fn starttls_ms(&self) -> Option<f64> {
+        self.starttls.as_ref().map(|s| s.negotiate_ms)
+    }
+}
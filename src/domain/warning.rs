@@ -0,0 +1,311 @@
+use serde::Serialize;
+use super::{CertSummary, GrpcHealth, GrpcStatusSource, HeaderList, HttpSummary, IpFamily, MultiplexProbe, Report, ServingStatus, TcpStats, TlsProbeReport, TlsSummary, WebSocketTransport, WebSocketUpgrade};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warn"),
+            Severity::Critical => write!(f, "crit"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Warning {
+    pub code: &'static str,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(code: &'static str, severity: Severity, message: impl Into<String>) -> Self {
+        Self { code, severity, message: message.into() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WarningRules {
+    pub cert_expiry_days: i64,
+    pub slow_dns_ms: f64,
+    pub max_redirects: usize,
+}
+
+impl Default for WarningRules {
+    fn default() -> Self {
+        Self { cert_expiry_days: 14, slow_dns_ms: 200.0, max_redirects: 3 }
+    }
+}
+
+impl WarningRules {
+    pub fn evaluate(&self, report: &Report) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+
+        self.tls_warnings(report.cert.as_ref(), report.tls.as_ref(), &mut warnings);
+        if report.tls.is_some() && report.websocket.is_none() && report.grpc.is_none() && !report.headers.contains("strict-transport-security") {
+            warnings.push(Warning::new("missing_hsts", Severity::Warning, "HTTPS response without Strict-Transport-Security"));
+        }
+        self.navigation_warnings(report, &mut warnings);
+        if let Some(ref mux) = report.multiplex {
+            multiplex_warnings(mux, &mut warnings);
+        }
+        if let Some(ref ws) = report.websocket {
+            websocket_warnings(ws, &report.headers, &mut warnings);
+        }
+        if let Some(ref grpc) = report.grpc {
+            grpc_warnings(grpc, &report.http, &mut warnings);
+        }
+        if let Some(ref tcp) = report.tcp {
+            tcp_warnings(tcp, &mut warnings);
+        }
+
+        warnings.sort_by_key(|w| std::cmp::Reverse(w.severity));
+        warnings
+    }
+
+    pub fn evaluate_tls_probe(&self, report: &TlsProbeReport) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        self.tls_warnings(report.cert.as_ref(), Some(&report.tls), &mut warnings);
+        self.dns_warning(report.dns_ms, &mut warnings);
+        warnings.sort_by_key(|w| std::cmp::Reverse(w.severity));
+        warnings
+    }
+
+    fn tls_warnings(&self, cert: Option<&CertSummary>, tls: Option<&TlsSummary>, warnings: &mut Vec<Warning>) {
+        match cert {
+            Some(cert) if cert.days_left < 0 => {
+                warnings.push(Warning::new("cert_expired", Severity::Critical, format!("certificate expired {} days ago", -cert.days_left)));
+            }
+            Some(cert) if cert.days_left < self.cert_expiry_days => {
+                warnings.push(Warning::new("cert_expiring", Severity::Warning, format!("certificate expires in {} days", cert.days_left)));
+            }
+            _ => {}
+        }
+        if let Some(tls) = tls
+            && tls.alpn_missing() {
+            warnings.push(Warning::new("no_alpn", Severity::Warning, format!("server selected no ALPN protocol (offered {})", tls.alpn_offered.join(","))));
+        }
+    }
+
+    fn dns_warning(&self, dns_ms: f64, warnings: &mut Vec<Warning>) {
+        if dns_ms > self.slow_dns_ms {
+            warnings.push(Warning::new("slow_dns", Severity::Warning, format!("DNS took {:.1}ms (threshold {:.0}ms)", dns_ms, self.slow_dns_ms)));
+        }
+    }
+
+    fn navigation_warnings(&self, report: &Report, warnings: &mut Vec<Warning>) {
+        if report.was_downgrade {
+            warnings.push(Warning::new("downgrade", Severity::Critical, "HTTPS→HTTP downgrade in redirect chain"));
+        }
+        self.dns_warning(report.timings.dns_ms, warnings);
+        if report.redirects.len() > self.max_redirects {
+            warnings.push(Warning::new("many_redirects", Severity::Warning, format!("{} redirects before the final response", report.redirects.len())));
+        }
+        let has_v4 = report.timings.hops.iter().any(|h| h.family == Some(IpFamily::IPv4));
+        let has_v6 = report.timings.hops.iter().any(|h| h.family == Some(IpFamily::IPv6));
+        if has_v4 && has_v6 {
+            warnings.push(Warning::new("mixed_ip_families", Severity::Info, "redirect chain connected over both IPv4 and IPv6"));
+        }
+    }
+}
+
+fn multiplex_warnings(mux: &MultiplexProbe, warnings: &mut Vec<Warning>) {
+    if mux.failed() > 0 {
+        warnings.push(Warning::new("h2_stream_reset", Severity::Warning, format!("{} of {} concurrent h2 streams failed or were reset", mux.failed(), mux.requested)));
+    }
+    if mux.head_of_line_blocked() {
+        warnings.push(Warning::new("h2_hol_blocking", Severity::Warning, "concurrent h2 streams were answered one at a time"));
+    }
+}
+
+fn websocket_warnings(ws: &WebSocketUpgrade, headers: &HeaderList, warnings: &mut Vec<Warning>) {
+    if !ws.switched {
+        warnings.push(Warning::new("ws_upgrade_failed", Severity::Critical, format!("WebSocket handshake answered with {} instead of {}", ws.status, if ws.transport == WebSocketTransport::Http1Upgrade { 101 } else { 200 })));
+    }
+    if ws.accept_valid == Some(false) {
+        warnings.push(Warning::new("ws_bad_accept", Severity::Critical, "Sec-WebSocket-Accept missing or not derived from the request key"));
+    }
+    if ws.switched
+        && ws.transport == WebSocketTransport::Http1Upgrade
+        && !headers.get("upgrade").is_some_and(|u| u.trim().eq_ignore_ascii_case("websocket")) {
+        warnings.push(Warning::new("ws_bad_upgrade_header", Severity::Critical, "101 response without Upgrade: websocket"));
+    }
+    if let Some(protocol) = ws.unoffered_subprotocol() {
+        warnings.push(Warning::new("ws_unoffered_protocol", Severity::Warning, format!("server selected subprotocol '{}' that was not offered", protocol)));
+    }
+    if let Some(ref error) = ws.ping_error {
+        warnings.push(Warning::new("ws_ping_failed", Severity::Warning, format!("WebSocket ping failed: {}", error)));
+    }
+}
+
+fn grpc_warnings(grpc: &GrpcHealth, http: &HttpSummary, warnings: &mut Vec<Warning>) {
+    if !http.version.starts_with("h2") {
+        warnings.push(Warning::new("grpc_not_h2", Severity::Critical, format!("gRPC needs h2 but the call went over {}", http.version)));
+    }
+    if !grpc.is_grpc_response() {
+        warnings.push(Warning::new("grpc_not_grpc", Severity::Critical, format!(
+            "HTTP {} with content-type {} instead of application/grpc; the endpoint or a proxy in front of it is not speaking gRPC",
+            http.status,
+            grpc.content_type.as_deref().unwrap_or("(none)"),
+        )));
+    }
+    match (grpc.grpc_status, grpc.status_source) {
+        (None, _) => warnings.push(Warning::new("grpc_missing_status", Severity::Critical, "response carried no grpc-status in trailers or headers")),
+        (Some(code), Some(GrpcStatusSource::HttpStatus)) => warnings.push(Warning::new("grpc_error", Severity::Critical, format!(
+            "HTTP {} without grpc-status maps to {} ({})", http.status, code, grpc.grpc_status_name.unwrap_or("UNRECOGNIZED"),
+        ))),
+        (Some(0), _) => {}
+        (Some(code), _) => warnings.push(Warning::new("grpc_error", Severity::Critical, format!(
+            "grpc-status {} {}{}{}",
+            code,
+            grpc.grpc_status_name.unwrap_or("UNRECOGNIZED"),
+            grpc.grpc_message.as_deref().map(|m| format!(": {}", m)).unwrap_or_default(),
+            if code == 12 { " (server does not implement grpc.health.v1.Health)" } else { "" },
+        ))),
+    }
+    if let Some(status) = grpc.serving_status
+        && status != ServingStatus::Serving {
+        warnings.push(Warning::new("grpc_not_serving", Severity::Critical, format!("health check for {} returned {}", grpc.service_label(), status)));
+    }
+    if let Some(ref error) = grpc.decode_error {
+        warnings.push(Warning::new("grpc_bad_response", Severity::Warning, format!("could not decode HealthCheckResponse: {}", error)));
+    }
+}
+
+fn tcp_warnings(tcp: &TcpStats, warnings: &mut Vec<Warning>) {
+    let info = tcp.latest();
+    if info.retransmits > 0 {
+        warnings.push(Warning::new("tcp_retransmits", Severity::Warning, format!("{} TCP retransmits on the final connection (rtt {:.1}ms)", info.retransmits, info.rtt_ms)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{HopTiming, RedirectHop};
+
+    fn cert(days_left: i64) -> CertSummary {
+        CertSummary {
+            subject_cn: None,
+            issuer: "test".to_string(),
+            san_short: String::new(),
+            not_before: String::new(),
+            not_after: String::new(),
+            days_left,
+            sha256_fp: String::new(),
+        }
+    }
+
+    fn evaluate_cert(days_left: i64) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        WarningRules::default().tls_warnings(Some(&cert(days_left)), None, &mut warnings);
+        warnings
+    }
+
+    #[test]
+    fn expiring_certificate_is_a_warning_and_expired_is_critical() {
+        assert!(evaluate_cert(30).is_empty());
+        let expiring = evaluate_cert(5);
+        assert_eq!((expiring[0].code, expiring[0].severity), ("cert_expiring", Severity::Warning));
+        let expired = evaluate_cert(-2);
+        assert_eq!((expired[0].code, expired[0].severity), ("cert_expired", Severity::Critical));
+        assert_eq!(expired[0].message, "certificate expired 2 days ago");
+    }
+
+    fn codes(report: &Report) -> Vec<&'static str> {
+        WarningRules::default().evaluate(report).iter().map(|w| w.code).collect()
+    }
+
+    fn https(report: &mut Report) {
+        report.tls = Some(TlsSummary::new("TLSv1.3".to_string(), Some("h2".to_string()), None, 2, true)
+            .with_alpn_offered(vec!["h2".to_string(), "http/1.1".to_string()]));
+    }
+
+    #[test]
+    fn missing_hsts_on_https_responses_except_websockets() {
+        let mut report = Report::fixture();
+        assert!(codes(&report).is_empty());
+
+        https(&mut report);
+        assert_eq!(codes(&report), ["missing_hsts"]);
+
+        report.headers.push("Strict-Transport-Security", "max-age=31536000");
+        assert!(codes(&report).is_empty());
+
+        let mut ws = Report::fixture();
+        https(&mut ws);
+        ws.websocket = Some(WebSocketUpgrade {
+            switched: true,
+            accept_valid: Some(true),
+            ..WebSocketUpgrade::new(WebSocketTransport::Http1Upgrade, 101, Vec::new(), 1.0)
+        });
+        ws.headers.push("Upgrade", "websocket");
+        assert!(!codes(&ws).contains(&"missing_hsts"));
+    }
+
+    #[test]
+    fn navigation_rules() {
+        let mut report = Report::fixture();
+        report.timings.dns_ms = 250.0;
+        report.was_downgrade = true;
+        report.redirects = (0..4).map(|i| RedirectHop::new(301, format!("/{}", i), format!("/{}", i + 1))).collect();
+        report.timings.hops = vec![
+            HopTiming { family: Some(IpFamily::IPv4), ..Default::default() },
+            HopTiming { family: Some(IpFamily::IPv6), ..Default::default() },
+        ];
+        // Sorted by severity, most severe first.
+        assert_eq!(codes(&report), ["downgrade", "slow_dns", "many_redirects", "mixed_ip_families"]);
+
+        report.timings.dns_ms = 200.0;
+        report.was_downgrade = false;
+        report.redirects.truncate(3);
+        report.timings.hops.truncate(1);
+        assert!(codes(&report).is_empty());
+    }
+
+    #[test]
+    fn no_alpn_when_the_server_ignores_the_offer() {
+        let mut report = Report::fixture();
+        https(&mut report);
+        report.headers.push("Strict-Transport-Security", "max-age=31536000");
+        if let Some(ref mut tls) = report.tls {
+            tls.alpn = None;
+        }
+        assert_eq!(codes(&report), ["no_alpn"]);
+
+        // Nothing offered, nothing missing.
+        report.tls = Some(TlsSummary::new("TLSv1.2".to_string(), None, None, 1, true));
+        assert!(codes(&report).is_empty());
+    }
+
+    #[test]
+    fn grpc_rules() {
+        let http = HttpSummary::new(200, None, "h2".to_string(), "HTTPS".to_string());
+        let mut grpc = GrpcHealth {
+            service: String::new(),
+            content_type: Some("application/grpc".to_string()),
+            grpc_status: Some(0),
+            grpc_status_name: Some("OK"),
+            grpc_message: None,
+            status_source: Some(GrpcStatusSource::Trailers),
+            serving_status: Some(ServingStatus::Serving),
+            decode_error: None,
+        };
+        let mut warnings = Vec::new();
+        grpc_warnings(&grpc, &http, &mut warnings);
+        assert!(warnings.is_empty());
+
+        grpc.serving_status = Some(ServingStatus::NotServing);
+        grpc_warnings(&grpc, &HttpSummary { version: "http/1.1".to_string(), ..http }, &mut warnings);
+        let codes: Vec<_> = warnings.iter().map(|w| w.code).collect();
+        assert_eq!(codes, ["grpc_not_h2", "grpc_not_serving"]);
+    }
+}
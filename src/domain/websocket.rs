@@ -0,0 +1,59 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WebSocketOptions {
+    pub protocols: Vec<String>,
+    pub extensions: Option<String>,
+    pub ping: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebSocketTransport {
+    Http1Upgrade,
+    H2ExtendedConnect,
+}
+
+impl std::fmt::Display for WebSocketTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebSocketTransport::Http1Upgrade => write!(f, "HTTP/1.1 Upgrade"),
+            WebSocketTransport::H2ExtendedConnect => write!(f, "h2 extended CONNECT"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebSocketUpgrade {
+    pub transport: WebSocketTransport,
+    pub switched: bool,
+    pub status: u16,
+    pub accept_valid: Option<bool>,
+    pub offered_protocols: Vec<String>,
+    pub subprotocol: Option<String>,
+    pub extensions: Vec<String>,
+    pub upgrade_ms: f64,
+    pub ping_rtt_ms: Option<f64>,
+    pub ping_error: Option<String>,
+}
+
+impl WebSocketUpgrade {
+    pub fn new(transport: WebSocketTransport, status: u16, offered_protocols: Vec<String>, upgrade_ms: f64) -> Self {
+        Self {
+            transport,
+            switched: false,
+            status,
+            accept_valid: None,
+            offered_protocols,
+            subprotocol: None,
+            extensions: Vec::new(),
+            upgrade_ms,
+            ping_rtt_ms: None,
+            ping_error: None,
+        }
+    }
+
+    pub fn unoffered_subprotocol(&self) -> Option<&str> {
+        self.subprotocol.as_deref().filter(|p| !self.offered_protocols.iter().any(|o| o == p))
+    }
+}
@@ -2,6 +2,7 @@ use std::time::{Duration, Instant};
 use crate::domain::UdocError;
 use crate::ports::Clock;
 
+#[derive(Default)]
 pub struct TokioClock;
 
 impl TokioClock {
@@ -12,6 +13,10 @@ impl TokioClock {
 impl Clock for TokioClock {
     fn now(&self) -> Instant { Instant::now() }
 
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await
+    }
+
     async fn timeout<F, T>(&self, duration: Duration, future: F) -> Result<T, UdocError>
     where
         F: std::future::Future<Output = T> + Send,
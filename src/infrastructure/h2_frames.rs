@@ -0,0 +1,368 @@
+pub const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+pub const FRAME_HEADER_LEN: usize = 9;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use crate::domain::{H2ErrorFrame, H2Session, UdocError};
+use crate::ports::BoxedIoStream;
+
+pub const DATA: u8 = 0x0;
+pub const HEADERS: u8 = 0x1;
+pub const RST_STREAM: u8 = 0x3;
+pub const SETTINGS: u8 = 0x4;
+pub const PING: u8 = 0x6;
+pub const GOAWAY: u8 = 0x7;
+pub const WINDOW_UPDATE: u8 = 0x8;
+pub const CONTINUATION: u8 = 0x9;
+
+pub const FLAG_END_STREAM: u8 = 0x1;
+pub const FLAG_ACK: u8 = 0x1;
+pub const FLAG_END_HEADERS: u8 = 0x4;
+pub const FLAG_PADDED: u8 = 0x8;
+pub const FLAG_PRIORITY: u8 = 0x20;
+
+pub const SETTINGS_HEADER_TABLE_SIZE: u16 = 0x1;
+pub const SETTINGS_ENABLE_PUSH: u16 = 0x2;
+pub const SETTINGS_MAX_CONCURRENT_STREAMS: u16 = 0x3;
+pub const SETTINGS_INITIAL_WINDOW_SIZE: u16 = 0x4;
+pub const SETTINGS_MAX_FRAME_SIZE: u16 = 0x5;
+pub const SETTINGS_MAX_HEADER_LIST_SIZE: u16 = 0x6;
+
+#[derive(Debug, Clone, Copy)]
+pub struct H2Windows {
+    pub stream: u32,
+    pub connection: u32,
+    pub max_frame_size: u32,
+}
+
+impl Default for H2Windows {
+    fn default() -> Self {
+        Self { stream: 65535, connection: 65535, max_frame_size: 16384 }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FrameHeader {
+    pub length: usize,
+    pub kind: u8,
+    pub flags: u8,
+    pub stream_id: u32,
+}
+
+impl FrameHeader {
+    pub fn parse(buf: &[u8]) -> Option<Self> {
+        if buf.len() < FRAME_HEADER_LEN { return None; }
+        Some(Self {
+            length: ((buf[0] as usize) << 16) | ((buf[1] as usize) << 8) | buf[2] as usize,
+            kind: buf[3],
+            flags: buf[4],
+            stream_id: u32::from_be_bytes([buf[5], buf[6], buf[7], buf[8]]) & 0x7fff_ffff,
+        })
+    }
+
+    pub fn has(&self, flag: u8) -> bool {
+        self.flags & flag != 0
+    }
+}
+
+pub fn encode_frame(kind: u8, flags: u8, stream_id: u32, payload: &[u8]) -> Vec<u8> {
+    let len = payload.len();
+    let mut out = Vec::with_capacity(FRAME_HEADER_LEN + len);
+    out.extend_from_slice(&[(len >> 16) as u8, (len >> 8) as u8, len as u8, kind, flags]);
+    out.extend_from_slice(&(stream_id & 0x7fff_ffff).to_be_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+pub fn settings_payload(settings: &[(u16, u32)]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(settings.len() * 6);
+    for (id, value) in settings {
+        out.extend_from_slice(&id.to_be_bytes());
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+    out
+}
+
+pub fn parse_settings(payload: &[u8]) -> Vec<(u16, u32)> {
+    payload.chunks_exact(6)
+        .map(|c| (u16::from_be_bytes([c[0], c[1]]), u32::from_be_bytes([c[2], c[3], c[4], c[5]])))
+        .collect()
+}
+
+pub fn strip_padding(header: &FrameHeader, payload: &[u8]) -> Option<Vec<u8>> {
+    let mut start = 0;
+    let mut end = payload.len();
+    if header.has(FLAG_PADDED) {
+        let pad = *payload.first()? as usize;
+        start = 1;
+        end = end.checked_sub(pad)?;
+    }
+    if header.kind == HEADERS && header.has(FLAG_PRIORITY) {
+        start += 5;
+    }
+    (start <= end).then(|| payload[start..end].to_vec())
+}
+
+pub fn error_code_name(code: u32) -> &'static str {
+    match code {
+        0x0 => "NO_ERROR",
+        0x1 => "PROTOCOL_ERROR",
+        0x2 => "INTERNAL_ERROR",
+        0x3 => "FLOW_CONTROL_ERROR",
+        0x4 => "SETTINGS_TIMEOUT",
+        0x5 => "STREAM_CLOSED",
+        0x6 => "FRAME_SIZE_ERROR",
+        0x7 => "REFUSED_STREAM",
+        0x8 => "CANCEL",
+        0x9 => "COMPRESSION_ERROR",
+        0xa => "CONNECT_ERROR",
+        0xb => "ENHANCE_YOUR_CALM",
+        0xc => "INADEQUATE_SECURITY",
+        0xd => "HTTP_1_1_REQUIRED",
+        _ => "UNKNOWN",
+    }
+}
+
+#[derive(Default)]
+pub struct FrameTracer {
+    buffer: Vec<u8>,
+    skip: usize,
+    settings_received: bool,
+    session: H2Session,
+}
+
+impl FrameTracer {
+    pub fn feed(&mut self, data: &[u8]) {
+        let mut data = data;
+        if self.skip > 0 {
+            let n = self.skip.min(data.len());
+            self.skip -= n;
+            data = &data[n..];
+        }
+        self.buffer.extend_from_slice(data);
+
+        while let Some(header) = FrameHeader::parse(&self.buffer) {
+            let available = self.buffer.len() - FRAME_HEADER_LEN;
+            if header.kind == DATA {
+                self.session.data_frames += 1;
+                self.session.data_bytes += header.length as u64;
+                if available < header.length {
+                    self.skip = header.length - available;
+                    self.buffer.clear();
+                    break;
+                }
+                self.buffer.drain(..FRAME_HEADER_LEN + header.length);
+                continue;
+            }
+            if available < header.length { break; }
+            let payload: Vec<u8> = self.buffer.drain(..FRAME_HEADER_LEN + header.length).skip(FRAME_HEADER_LEN).collect();
+            self.on_frame(&header, &payload);
+        }
+    }
+
+    fn on_frame(&mut self, header: &FrameHeader, payload: &[u8]) {
+        match header.kind {
+            SETTINGS if !header.has(FLAG_ACK) => {
+                self.settings_received = true;
+                let settings = &mut self.session.server_settings;
+                for (id, value) in parse_settings(payload) {
+                    match id {
+                        SETTINGS_HEADER_TABLE_SIZE => settings.header_table_size = Some(value),
+                        SETTINGS_ENABLE_PUSH => settings.enable_push = Some(value),
+                        SETTINGS_MAX_CONCURRENT_STREAMS => settings.max_concurrent_streams = Some(value),
+                        SETTINGS_INITIAL_WINDOW_SIZE => settings.initial_window_size = Some(value),
+                        SETTINGS_MAX_FRAME_SIZE => settings.max_frame_size = Some(value),
+                        SETTINGS_MAX_HEADER_LIST_SIZE => settings.max_header_list_size = Some(value),
+                        _ => {}
+                    }
+                }
+            }
+            GOAWAY if payload.len() >= 8 => {
+                let last_stream = u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]) & 0x7fff_ffff;
+                self.session.goaway = Some(error_frame(last_stream, goaway_code(payload)));
+            }
+            RST_STREAM if payload.len() >= 4 => {
+                self.session.rst_stream.push(error_frame(header.stream_id, rst_code(payload)));
+            }
+            _ => {}
+        }
+    }
+
+    pub fn settings_received(&self) -> bool {
+        self.settings_received
+    }
+
+    pub fn session(&self) -> H2Session {
+        self.session.clone()
+    }
+}
+
+fn error_frame(stream_id: u32, code: u32) -> H2ErrorFrame {
+    H2ErrorFrame { stream_id, code, name: error_code_name(code).to_string() }
+}
+
+pub fn goaway_code(payload: &[u8]) -> u32 {
+    payload.get(4..8).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]])).unwrap_or(0)
+}
+
+pub fn rst_code(payload: &[u8]) -> u32 {
+    payload.get(0..4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]])).unwrap_or(0)
+}
+
+/// Stream and connection WINDOW_UPDATE frames returning `length` bytes of credit.
+pub fn window_update(stream_id: u32, length: usize) -> Vec<u8> {
+    let increment = (length as u32).to_be_bytes();
+    let mut update = encode_frame(WINDOW_UPDATE, 0, stream_id, &increment);
+    update.extend(encode_frame(WINDOW_UPDATE, 0, 0, &increment));
+    update
+}
+
+/// What the hand-rolled clients see of one stream once connection-level frames are handled.
+pub enum StreamFrame {
+    /// A complete, decoded header block (HEADERS plus any CONTINUATION frames).
+    Headers { fields: Vec<(Vec<u8>, Vec<u8>)>, end_stream: bool },
+    /// DATA with padding removed; `length` is the flow-controlled frame length.
+    Data { data: Vec<u8>, length: usize, end_stream: bool },
+    Reset(u32),
+    GoAway(u32),
+    /// The server's SETTINGS, already acknowledged.
+    Settings(Vec<(u16, u32)>),
+}
+
+pub struct FrameReader {
+    stream: BoxedIoStream,
+    buffer: Vec<u8>,
+    tracer: FrameTracer,
+    decoder: hpack::Decoder<'static>,
+    block: Vec<u8>,
+    block_ends_stream: bool,
+}
+
+impl FrameReader {
+    pub fn new(stream: BoxedIoStream, buffer: Vec<u8>, tracer: FrameTracer) -> Self {
+        Self { stream, buffer, tracer, decoder: hpack::Decoder::new(), block: Vec::new(), block_ends_stream: false }
+    }
+
+    pub fn session(&self) -> H2Session {
+        self.tracer.session()
+    }
+
+    pub async fn write(&mut self, bytes: &[u8]) -> Result<(), UdocError> {
+        self.stream.write_all(bytes).await.map_err(|e| UdocError::http(format!("h2 write failed: {}", e)))?;
+        self.stream.flush().await.map_err(|e| UdocError::http(format!("h2 write failed: {}", e)))
+    }
+
+    pub async fn next_frame(&mut self) -> Result<(FrameHeader, Vec<u8>), UdocError> {
+        let mut chunk = [0u8; 16 * 1024];
+        loop {
+            if let Some(header) = FrameHeader::parse(&self.buffer) {
+                let total = FRAME_HEADER_LEN + header.length;
+                if self.buffer.len() >= total {
+                    let payload = self.buffer[FRAME_HEADER_LEN..total].to_vec();
+                    self.buffer.drain(..total);
+                    return Ok((header, payload));
+                }
+            }
+            let n = self.stream.read(&mut chunk).await.map_err(|e| UdocError::http(format!("h2 read failed: {}", e)))?;
+            if n == 0 { return Err(UdocError::http("h2 connection closed mid-stream")); }
+            self.tracer.feed(&chunk[..n]);
+            self.buffer.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    /// Reads until something happens on `stream_id` or to the connection. SETTINGS and PING
+    /// are acknowledged as soon as they arrive, and header blocks are reassembled and decoded.
+    pub async fn next_stream_frame(&mut self, stream_id: u32) -> Result<StreamFrame, UdocError> {
+        loop {
+            let (header, payload) = self.next_frame().await?;
+            match header.kind {
+                SETTINGS if !header.has(FLAG_ACK) => {
+                    self.write(&encode_frame(SETTINGS, FLAG_ACK, 0, &[])).await?;
+                    return Ok(StreamFrame::Settings(parse_settings(&payload)));
+                }
+                PING if !header.has(FLAG_ACK) => self.write(&encode_frame(PING, FLAG_ACK, 0, &payload)).await?,
+                GOAWAY => return Ok(StreamFrame::GoAway(goaway_code(&payload))),
+                RST_STREAM if header.stream_id == stream_id => return Ok(StreamFrame::Reset(rst_code(&payload))),
+                HEADERS | CONTINUATION if header.stream_id == stream_id => {
+                    let fragment = if header.kind == HEADERS {
+                        self.block_ends_stream = header.has(FLAG_END_STREAM);
+                        strip_padding(&header, &payload).ok_or_else(|| UdocError::http("malformed h2 HEADERS frame"))?
+                    } else {
+                        payload
+                    };
+                    self.block.extend_from_slice(&fragment);
+                    if !header.has(FLAG_END_HEADERS) { continue; }
+                    let fields = self.decoder.decode(&self.block).map_err(|e| UdocError::http(format!("h2 HPACK decode failed: {:?}", e)))?;
+                    self.block.clear();
+                    return Ok(StreamFrame::Headers { fields, end_stream: self.block_ends_stream });
+                }
+                DATA if header.stream_id == stream_id => {
+                    let data = strip_padding(&header, &payload).ok_or_else(|| UdocError::http("malformed h2 DATA frame"))?;
+                    return Ok(StreamFrame::Data { data, length: header.length, end_stream: header.has(FLAG_END_STREAM) });
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_frame_header() {
+        let frame = encode_frame(HEADERS, FLAG_END_HEADERS | FLAG_END_STREAM, 0x8000_0003, b"abc");
+        assert_eq!(&frame[..FRAME_HEADER_LEN], &[0, 0, 3, HEADERS, 0x5, 0, 0, 0, 3]);
+        let header = FrameHeader::parse(&frame).unwrap();
+        assert_eq!((header.length, header.kind, header.stream_id), (3, HEADERS, 3));
+        assert!(header.has(FLAG_END_STREAM) && header.has(FLAG_END_HEADERS) && !header.has(FLAG_PADDED));
+        assert!(FrameHeader::parse(&frame[..8]).is_none());
+    }
+
+    #[test]
+    fn round_trips_settings() {
+        let settings = [(SETTINGS_MAX_CONCURRENT_STREAMS, 100), (SETTINGS_INITIAL_WINDOW_SIZE, 1 << 20)];
+        let payload = settings_payload(&settings);
+        assert_eq!(payload.len(), 12);
+        assert_eq!(parse_settings(&payload), settings);
+        assert_eq!(parse_settings(&payload[..8]), settings[..1]);
+    }
+
+    #[test]
+    fn strips_padding_and_priority() {
+        let header = |kind, flags| FrameHeader { length: 0, kind, flags, stream_id: 1 };
+        assert_eq!(strip_padding(&header(DATA, FLAG_PADDED), &[2, b'h', b'i', 0, 0]).unwrap(), b"hi");
+        assert_eq!(strip_padding(&header(HEADERS, FLAG_PRIORITY), &[0, 0, 0, 0, 16, b'h']).unwrap(), b"h");
+        assert_eq!(strip_padding(&header(HEADERS, FLAG_PADDED | FLAG_PRIORITY), &[1, 0, 0, 0, 0, 16, b'h', 0]).unwrap(), b"h");
+        assert!(strip_padding(&header(DATA, FLAG_PADDED), &[9, b'h']).is_none());
+        assert!(strip_padding(&header(DATA, FLAG_PADDED), &[]).is_none());
+    }
+
+    #[test]
+    fn tracer_collects_session_across_split_reads() {
+        let mut wire = encode_frame(SETTINGS, 0, 0, &settings_payload(&[(SETTINGS_MAX_CONCURRENT_STREAMS, 8)]));
+        wire.extend(encode_frame(DATA, 0, 1, &[0; 40]));
+        wire.extend(encode_frame(RST_STREAM, 0, 3, &7u32.to_be_bytes()));
+        wire.extend(encode_frame(GOAWAY, 0, 0, &[0, 0, 0, 5, 0, 0, 0, 0xb]));
+
+        let mut tracer = FrameTracer::default();
+        for chunk in wire.chunks(7) {
+            tracer.feed(chunk);
+        }
+        assert!(tracer.settings_received());
+        let session = tracer.session();
+        assert_eq!(session.server_settings.max_concurrent_streams, Some(8));
+        assert_eq!((session.data_frames, session.data_bytes), (1, 40));
+        assert_eq!(session.rst_stream.len(), 1);
+        assert_eq!((session.rst_stream[0].stream_id, session.rst_stream[0].name.as_str()), (3, "REFUSED_STREAM"));
+        let goaway = session.goaway.unwrap();
+        assert_eq!((goaway.stream_id, goaway.code, goaway.name.as_str()), (5, 0xb, "ENHANCE_YOUR_CALM"));
+    }
+
+    #[test]
+    fn settings_ack_is_not_server_settings() {
+        let mut tracer = FrameTracer::default();
+        tracer.feed(&encode_frame(SETTINGS, FLAG_ACK, 0, &[]));
+        assert!(!tracer.settings_received());
+    }
+}
@@ -0,0 +1,143 @@
+use std::time::Instant;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use tokio::io::AsyncReadExt;
+use crate::domain::{ExchangeTiming, HeaderList, HttpSummary, ProtocolUpgrade, UdocError};
+use crate::ports::{BoxedIoStream, HttpRequest, HttpResponse, ResponseHeaders};
+use super::h2_frames::*;
+use super::http::{elapsed_ms, find_header_end, h1_request_head, parse_headers, read_h1_response, write_request};
+
+const UPGRADE_STREAM: u32 = 1;
+const DEFAULT_WINDOW: u32 = 65535;
+
+pub async fn upgrade_exchange(mut stream: BoxedIoStream, request: &HttpRequest, windows: H2Windows) -> Result<HttpResponse, UdocError> {
+    let settings = [
+        (SETTINGS_ENABLE_PUSH, 0),
+        (SETTINGS_INITIAL_WINDOW_SIZE, windows.stream),
+        (SETTINGS_MAX_FRAME_SIZE, windows.max_frame_size),
+    ];
+    let settings_payload = settings_payload(&settings);
+    let head = h1_request_head(request, "Upgrade, HTTP2-Settings", &[
+        ("Upgrade", "h2c".to_string()),
+        ("HTTP2-Settings", URL_SAFE_NO_PAD.encode(&settings_payload)),
+    ]);
+
+    let start = Instant::now();
+    write_request(&mut stream, head.as_bytes()).await?;
+    let request_sent_ms = elapsed_ms(start);
+
+    let mut buffer = Vec::new();
+    let mut chunk = vec![0u8; 16 * 1024];
+    let mut first_byte_ms = None;
+    let head_end = loop {
+        if let Some(pos) = find_header_end(&buffer) { break pos; }
+        let n = stream.read(&mut chunk).await.map_err(|e| UdocError::http(format!("failed to read response: {}", e)))?;
+        if n == 0 { return Err(UdocError::http("connection closed before upgrade response")); }
+        if first_byte_ms.is_none() { first_byte_ms = Some(elapsed_ms(start)); }
+        buffer.extend_from_slice(&chunk[..n]);
+    };
+
+    let (first, _) = parse_headers(&buffer[..head_end], "HTTP")?;
+    if first.status != 101 {
+        let (mut response, _) = read_h1_response(&mut stream, request, start, request_sent_ms, buffer, first_byte_ms).await?;
+        response.upgrade = Some(ProtocolUpgrade { protocol: "h2c".to_string(), switched: false, status: response.summary.status });
+        return Ok(response);
+    }
+
+    let leftover = buffer.split_off(head_end + 4);
+    let mut tracer = FrameTracer::default();
+    tracer.feed(&leftover);
+    let mut conn = FrameReader::new(stream, leftover, tracer);
+
+    let mut preface = PREFACE.to_vec();
+    preface.extend(encode_frame(SETTINGS, 0, 0, &settings_payload));
+    if windows.connection > DEFAULT_WINDOW {
+        preface.extend(encode_frame(WINDOW_UPDATE, 0, 0, &(windows.connection - DEFAULT_WINDOW).to_be_bytes()));
+    }
+    conn.write(&preface).await?;
+
+    let mut response_head: Option<(HttpSummary, ResponseHeaders)> = None;
+    let mut trailers = HeaderList::new();
+    let mut headers_ms = None;
+    let mut body_preview = Vec::new();
+    let mut body_truncated = false;
+
+    loop {
+        match conn.next_stream_frame(UPGRADE_STREAM).await? {
+            StreamFrame::GoAway(code) => {
+                if response_head.is_none() {
+                    return Err(UdocError::http(format!("h2c GOAWAY before response: {}", error_code_name(code))));
+                }
+                break;
+            }
+            StreamFrame::Reset(code) => return Err(UdocError::http(format!("h2c stream reset: {}", error_code_name(code)))),
+            StreamFrame::Headers { fields, end_stream } => {
+                if response_head.is_none() {
+                    let head = response_from_fields(&fields, "h2c", "HTTP")?;
+                    if head.0.status >= 200 {
+                        headers_ms = Some(elapsed_ms(start));
+                        response_head = Some(head);
+                    }
+                } else {
+                    for (name, value) in &fields {
+                        trailers.push(&String::from_utf8_lossy(name), &String::from_utf8_lossy(value));
+                    }
+                }
+                if end_stream && response_head.is_some() { break; }
+            }
+            StreamFrame::Data { data, length, end_stream } => {
+                let room = request.body_limit.saturating_sub(body_preview.len());
+                body_preview.extend_from_slice(&data[..data.len().min(room)]);
+                if end_stream { break; }
+                if body_preview.len() >= request.body_limit { body_truncated = true; break; }
+                if length > 0 { conn.write(&window_update(UPGRADE_STREAM, length)).await?; }
+            }
+            StreamFrame::Settings(_) => {}
+        }
+    }
+    let body_ms = elapsed_ms(start);
+
+    let (summary, headers) = response_head.ok_or_else(|| UdocError::http("h2c stream ended without response headers"))?;
+    Ok(HttpResponse {
+        summary,
+        headers,
+        timing: ExchangeTiming {
+            request_sent_ms,
+            ttfb_ms: first_byte_ms.unwrap_or(body_ms),
+            headers_ms: headers_ms.unwrap_or(body_ms),
+            body_ms,
+        },
+        body_preview,
+        body_truncated,
+        trailers,
+        upgrade: Some(ProtocolUpgrade { protocol: "h2c".to_string(), switched: true, status: first.status }),
+        websocket: None,
+        h2: Some(conn.session()),
+        connection: None,
+    })
+}
+
+pub(super) fn response_from_fields(fields: &[(Vec<u8>, Vec<u8>)], version: &str, proto: &str) -> Result<(HttpSummary, ResponseHeaders), UdocError> {
+    let mut status = None;
+    let mut headers = ResponseHeaders::default();
+    for (name, value) in fields {
+        let name = String::from_utf8_lossy(name).to_ascii_lowercase();
+        let value = String::from_utf8_lossy(value).to_string();
+        if name == ":status" {
+            status = value.parse::<u16>().ok();
+            continue;
+        }
+        headers.all.push(&name, &value);
+        match name.as_str() {
+            "location" => headers.location = Some(value),
+            "server" => headers.server = Some(value),
+            "content-type" => headers.content_type = Some(value),
+            "content-length" => headers.content_length = value.parse().ok(),
+            "content-encoding" => headers.content_encoding = Some(value.to_ascii_lowercase()),
+            _ => {}
+        }
+    }
+    let status = status.ok_or_else(|| UdocError::http(format!("{} response without :status", version)))?;
+    let reason = hyper::StatusCode::from_u16(status).ok().and_then(|s| s.canonical_reason()).map(|s| s.to_string());
+    Ok((HttpSummary::new(status, reason, version.to_string(), proto.to_string()), headers))
+}
@@ -1,143 +1,449 @@
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use tokio::io::{AsyncRead, AsyncWrite, AsyncReadExt, AsyncWriteExt};
 use bytes::Bytes;
-use http_body_util::{BodyExt, Empty};
+use http_body_util::{BodyExt, Full};
+use hyper::body::Body;
 use hyper::client::conn::http2;
 use hyper_util::rt::TokioExecutor;
-use crate::domain::{HttpSummary, UdocError};
-use crate::ports::{HttpClient, HttpResponse, ResponseHeaders, BoxedIoStream};
+use crate::domain::{ExchangeTiming, HeaderList, HttpSummary, MultiplexProbe, StreamTiming, UdocError, WebSocketOptions};
+use crate::ports::{H2Handle, HttpClient, HttpRequest, HttpResponse, PooledConnection, ResponseHeaders, BoxedIoStream};
+use super::{h2c, websocket};
+use super::h2_frames::{FrameTracer, H2Windows};
 
 const HEADER_LIMIT: usize = 32 * 1024;
+const READ_CHUNK: usize = 16 * 1024;
+const SETTINGS_WAIT: std::time::Duration = std::time::Duration::from_millis(250);
 
-pub struct HybridHttpClient;
+#[derive(Default)]
+pub struct HybridHttpClient {
+    windows: H2Windows,
+}
 
 impl HybridHttpClient {
-    pub fn new() -> Self { Self }
+    pub fn new() -> Self { Self::default() }
+
+    async fn h2_handshake(&self, stream: BoxedIoStream) -> Result<H2Connection, UdocError> {
+        let marks = Arc::new(Mutex::new(WriteMarks::default()));
+        let tracer = Arc::new(Mutex::new(FrameTracer::default()));
+        let settings = Arc::new(tokio::sync::Notify::new());
+        let io = TokioIo { inner: stream, marks: marks.clone(), tracer: tracer.clone(), settings: settings.clone() };
+        let (sender, conn) = http2::Builder::new(TokioExecutor::new())
+            .initial_stream_window_size(self.windows.stream)
+            .initial_connection_window_size(self.windows.connection)
+            .max_frame_size(self.windows.max_frame_size)
+            .handshake(io).await
+            .map_err(|e| UdocError::http(format!("h2 handshake failed: {}", e)))?;
+
+        tokio::spawn(async move { let _ = conn.await; });
+
+        Ok(H2Connection { sender, marks, tracer, settings })
+    }
+
+    pub fn with_h2_windows(mut self, stream: u32, connection: u32, max_frame_size: u32) -> Self {
+        self.windows = H2Windows { stream, connection, max_frame_size };
+        self
+    }
 }
 
 impl HttpClient for HybridHttpClient {
-    async fn request_h1(&self, mut stream: BoxedIoStream, method: &str, host: &str, port: u16, path: &str, is_https: bool, body_limit: usize) -> Result<HttpResponse, UdocError> {
-        let host_header = if (is_https && port == 443) || (!is_https && port == 80) {
-            host.to_string()
-        } else {
-            format!("{}:{}", host, port)
-        };
+    async fn request_h1(&self, stream: BoxedIoStream, request: &HttpRequest) -> Result<HttpResponse, UdocError> {
+        h1_exchange(stream, request).await
+    }
+
+    async fn request_h2(&self, stream: BoxedIoStream, request: &HttpRequest) -> Result<HttpResponse, UdocError> {
+        let conn = self.h2_handshake(stream).await?;
+        h2_exchange(conn, request).await
+    }
 
-        let request = format!(
-            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nUser-Agent: udoc/0.2\r\nAccept: */*\r\n\r\n",
-            method, path, host_header
-        );
+    async fn request_h2c_upgrade(&self, stream: BoxedIoStream, request: &HttpRequest) -> Result<HttpResponse, UdocError> {
+        h2c::upgrade_exchange(stream, request, self.windows).await
+    }
+
+    async fn request_h2_multiplex(&self, stream: BoxedIoStream, requests: &[HttpRequest]) -> Result<MultiplexProbe, UdocError> {
+        let mut conn = self.h2_handshake(stream).await?;
+        conn.sender.ready().await
+            .map_err(|e| UdocError::http(format!("h2 connection not ready: {}", e)))?;
+        if !conn.tracer.lock().map(|t| t.settings_received()).unwrap_or(true) {
+            let _ = tokio::time::timeout(SETTINGS_WAIT, conn.settings.notified()).await;
+        }
+        let max_concurrent_streams = conn.tracer.lock().ok().and_then(|t| t.session().server_settings.max_concurrent_streams);
+        let in_flight = max_concurrent_streams.map(|m| (m as usize).max(1)).unwrap_or(usize::MAX).min(requests.len());
 
         let start = Instant::now();
-        stream.write_all(request.as_bytes()).await.map_err(|e| UdocError::http(format!("failed to send request: {}", e)))?;
+        let mut pending = requests.iter().cloned().enumerate();
+        let mut tasks = tokio::task::JoinSet::new();
+        for (index, request) in pending.by_ref().take(in_flight) {
+            tasks.spawn(stream_exchange(conn.sender.clone(), index + 1, request, start));
+        }
+        let mut streams = Vec::with_capacity(requests.len());
+        while let Some(joined) = tasks.join_next().await {
+            streams.push(joined.map_err(|e| UdocError::other(format!("h2 stream task failed: {}", e)))?);
+            if let Some((index, request)) = pending.next() {
+                tasks.spawn(stream_exchange(conn.sender.clone(), index + 1, request, start));
+            }
+        }
+        streams.sort_by_key(|s| s.index);
+        let total_ms = elapsed_ms(start);
+
+        let session = conn.tracer.lock().map(|t| t.session()).unwrap_or_default();
+        Ok(MultiplexProbe {
+            requested: requests.len(),
+            max_concurrent_streams,
+            in_flight,
+            total_ms,
+            streams,
+            goaway: session.goaway,
+            rst_stream: session.rst_stream,
+        })
+    }
 
-        let mut buffer = vec![0u8; HEADER_LIMIT + body_limit];
-        let mut total_read = 0;
-        let mut first_byte_time: Option<f64> = None;
+    async fn request_websocket_h1(&self, stream: BoxedIoStream, request: &HttpRequest, options: &WebSocketOptions) -> Result<HttpResponse, UdocError> {
+        websocket::h1_upgrade(stream, request, options).await
+    }
 
-        loop {
-            let n = stream.read(&mut buffer[total_read..]).await.map_err(|e| UdocError::http(format!("failed to read response: {}", e)))?;
-            if n == 0 { break; }
-            if first_byte_time.is_none() { first_byte_time = Some(start.elapsed().as_secs_f64() * 1000.0); }
-            total_read += n;
+    async fn request_websocket_h2(&self, stream: BoxedIoStream, request: &HttpRequest, options: &WebSocketOptions) -> Result<HttpResponse, UdocError> {
+        websocket::h2_connect(stream, request, options, self.windows).await
+    }
 
-            if let Some(pos) = find_header_end(&buffer[..total_read]) {
-                let body_so_far = total_read.saturating_sub(pos + 4);
-                if body_so_far >= body_limit { break; }
+    async fn request_pooled(&self, connection: PooledConnection, request: &HttpRequest) -> Result<HttpResponse, UdocError> {
+        match connection {
+            PooledConnection::H1(stream) => h1_exchange(stream, request).await,
+            PooledConnection::H2(handle) => {
+                let conn = handle.0.downcast::<H2Connection>()
+                    .map_err(|_| UdocError::other("pooled h2 connection of unknown type"))?;
+                if conn.sender.is_closed() {
+                    return Err(UdocError::http("pooled h2 connection was closed by the server"));
+                }
+                h2_exchange(*conn, request).await
             }
-            if total_read >= buffer.len() { break; }
         }
-        buffer.truncate(total_read);
+    }
+}
+
+struct H2Connection {
+    sender: http2::SendRequest<Full<Bytes>>,
+    marks: Arc<Mutex<WriteMarks>>,
+    tracer: Arc<Mutex<FrameTracer>>,
+    settings: Arc<tokio::sync::Notify>,
+}
+
+async fn h1_exchange(mut stream: BoxedIoStream, request: &HttpRequest) -> Result<HttpResponse, UdocError> {
+    let head = h1_request_head(request, if request.keep_alive { "keep-alive" } else { "close" }, &[]);
+
+    let mut bytes = head.into_bytes();
+    bytes.extend_from_slice(&request.body);
+
+    let start = Instant::now();
+    write_request(&mut stream, &bytes).await?;
+    let request_sent_ms = elapsed_ms(start);
+
+    let (mut response, reusable) = read_h1_response(&mut stream, request, start, request_sent_ms, Vec::new(), None).await?;
+    if reusable && request.keep_alive {
+        response.connection = Some(PooledConnection::H1(stream));
+    }
+    Ok(response)
+}
 
-        let (summary, headers, body_start) = parse_response_with_1xx_skip(&buffer, if is_https { "HTTPS" } else { "HTTP" })?;
+async fn h2_exchange(mut conn: H2Connection, request: &HttpRequest) -> Result<HttpResponse, UdocError> {
+    let body_limit = request.body_limit;
+    let (proto, version) = if request.is_https { ("HTTPS", "h2") } else { ("HTTP", "h2c") };
+    let req = h2_request(request)?;
+
+    let start = Instant::now();
+    conn.marks.lock().map_err(|_| UdocError::other("h2 write marks poisoned"))?.arm(start);
+
+    conn.sender.ready().await
+        .map_err(|e| UdocError::http(format!("h2 connection not ready: {}", e)))?;
+    let res = conn.sender.send_request(req).await
+        .map_err(|e| UdocError::http(format!("h2 request failed: {}", e)))?;
+
+    let headers_ms = elapsed_ms(start);
+
+    let status = res.status().as_u16();
+    let reason = res.status().canonical_reason().map(|s| s.to_string());
+
+    let headers = response_headers(res.headers());
+
+    let mut body_preview = Vec::with_capacity(body_limit.min(8192));
+    let mut body = res.into_body();
+    let mut body_truncated = false;
+    let mut trailers = HeaderList::new();
+    while body_preview.len() < body_limit {
+        match body.frame().await {
+            Some(Ok(frame)) => {
+                if let Some(chunk) = frame.data_ref() {
+                    let remaining = body_limit - body_preview.len();
+                    let to_copy = chunk.len().min(remaining);
+                    body_preview.extend_from_slice(&chunk[..to_copy]);
+                    body_truncated = to_copy < chunk.len();
+                } else if let Some(map) = frame.trailers_ref() {
+                    for (key, value) in map {
+                        trailers.push(key.as_str(), value.to_str().unwrap_or(""));
+                    }
+                }
+            }
+            Some(Err(e)) => return Err(UdocError::http(format!("failed to read h2 body: {}", e))),
+            None => break,
+        }
+    }
+    if body_preview.len() >= body_limit && !body.is_end_stream() { body_truncated = true; }
+    drop(body);
+    let body_ms = elapsed_ms(start);
+
+    let request_sent_ms = conn.marks.lock().ok()
+        .and_then(|m| m.sent)
+        .map(|t| t.duration_since(start).as_secs_f64() * 1000.0)
+        .unwrap_or(headers_ms);
+
+    let session = conn.tracer.lock().map(|t| t.session()).unwrap_or_default();
+    Ok(HttpResponse {
+        summary: HttpSummary::new(status, reason, version.to_string(), proto.to_string()),
+        headers,
+        timing: ExchangeTiming { request_sent_ms, ttfb_ms: headers_ms, headers_ms, body_ms },
+        body_preview,
+        body_truncated,
+        trailers,
+        upgrade: None,
+        websocket: None,
+        h2: Some(session),
+        connection: request.keep_alive.then(|| PooledConnection::H2(H2Handle(Box::new(conn)))),
+    })
+}
 
-        let body_preview = if body_start < buffer.len() {
-            let raw_body = &buffer[body_start..];
-            if headers.transfer_encoding.as_ref().map(|t| t.contains("chunked")).unwrap_or(false) {
-                decode_chunked_preview(raw_body, body_limit)
-            } else {
-                raw_body[..raw_body.len().min(body_limit)].to_vec()
+async fn stream_exchange(mut sender: http2::SendRequest<Full<Bytes>>, index: usize, request: HttpRequest, start: Instant) -> StreamTiming {
+    let mut timing = StreamTiming {
+        index,
+        path: request.path.clone(),
+        status: None,
+        started_ms: elapsed_ms(start),
+        ttfb_ms: None,
+        done_ms: None,
+        bytes: 0,
+        error: None,
+    };
+    let req = match h2_request(&request) {
+        Ok(req) => req,
+        Err(e) => { timing.error = Some(e.message); return timing; }
+    };
+    let result: Result<(), hyper::Error> = async {
+        sender.ready().await?;
+        let res = sender.send_request(req).await?;
+        timing.ttfb_ms = Some(elapsed_ms(start));
+        timing.status = Some(res.status().as_u16());
+        let mut body = res.into_body();
+        while let Some(frame) = body.frame().await {
+            if let Some(chunk) = frame?.data_ref() {
+                timing.bytes += chunk.len() as u64;
             }
-        } else {
-            Vec::new()
-        };
+        }
+        Ok(())
+    }.await;
+    match result {
+        Ok(()) => timing.done_ms = Some(elapsed_ms(start)),
+        Err(e) => timing.error = Some(error_chain(&e)),
+    }
+    timing
+}
 
-        Ok(HttpResponse { summary, headers, ttfb_ms: first_byte_time.unwrap_or(0.0), body_preview })
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
     }
+    message
+}
 
-    async fn request_h2(&self, stream: BoxedIoStream, method: &str, host: &str, port: u16, path: &str, body_limit: usize) -> Result<HttpResponse, UdocError> {
-        let authority = if port == 443 { host.to_string() } else { format!("{}:{}", host, port) };
-        let uri = format!("https://{}{}", authority, path);
+fn h2_request(request: &HttpRequest) -> Result<hyper::Request<Full<Bytes>>, UdocError> {
+    let scheme = if request.is_https { "https" } else { "http" };
+    let uri = format!("{}://{}{}", scheme, request.authority(), request.path);
+
+    let mut builder = hyper::Request::builder()
+        .method(request.method.as_str())
+        .uri(&uri)
+        .header("user-agent", "udoc/0.2.1")
+        .header("accept", "*/*");
+    for (name, value) in &request.headers {
+        builder = builder.header(name.to_ascii_lowercase(), value.as_str());
+    }
+    builder
+        .body(Full::new(Bytes::copy_from_slice(&request.body)))
+        .map_err(|e| UdocError::http(format!("failed to build request: {}", e)))
+}
 
-        let start = Instant::now();
+pub(super) fn response_headers(map: &hyper::HeaderMap) -> ResponseHeaders {
+    let mut headers = ResponseHeaders::default();
+    for (key, value) in map {
+        let key_str = key.as_str();
+        let val_str = value.to_str().unwrap_or("");
+        headers.all.push(key_str, val_str);
+        match key_str {
+            "location" => headers.location = Some(val_str.to_string()),
+            "server" => headers.server = Some(val_str.to_string()),
+            "content-type" => headers.content_type = Some(val_str.to_string()),
+            "content-length" => headers.content_length = val_str.parse().ok(),
+            "content-encoding" => headers.content_encoding = Some(val_str.to_ascii_lowercase()),
+            "transfer-encoding" => headers.transfer_encoding = Some(val_str.to_string()),
+            _ => {}
+        }
+    }
+    headers
+}
 
-        let io = TokioIo(stream);
-        let (mut sender, conn) = http2::Builder::new(TokioExecutor::new())
-            .initial_stream_window_size(65535)
-            .initial_connection_window_size(65535)
-            .max_frame_size(16384)
-            .handshake(io).await
-            .map_err(|e| UdocError::http(format!("h2 handshake failed: {}", e)))?;
+pub(super) fn h1_request_head(request: &HttpRequest, connection: &str, extra: &[(&str, String)]) -> String {
+    let mut head = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: {}\r\nUser-Agent: udoc/0.2\r\nAccept: */*\r\n",
+        request.method, request.path, request.authority(), connection
+    );
+    for (name, value) in extra {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    for (name, value) in &request.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if !request.body.is_empty() {
+        head.push_str(&format!("Content-Length: {}\r\n", request.body.len()));
+    }
+    head.push_str("\r\n");
+    head
+}
 
-        tokio::spawn(async move { let _ = conn.await; });
+pub(super) async fn write_request(stream: &mut BoxedIoStream, bytes: &[u8]) -> Result<(), UdocError> {
+    stream.write_all(bytes).await.map_err(|e| UdocError::http(format!("failed to send request: {}", e)))?;
+    stream.flush().await.map_err(|e| UdocError::http(format!("failed to send request: {}", e)))
+}
 
-        let req = hyper::Request::builder()
-            .method(method)
-            .uri(&uri)
-            .header("user-agent", "udoc/0.2.1")
-            .header("accept", "*/*")
-            .body(Empty::<Bytes>::new())
-            .map_err(|e| UdocError::http(format!("failed to build request: {}", e)))?;
-
-        let res = sender.send_request(req).await
-            .map_err(|e| UdocError::http(format!("h2 request failed: {}", e)))?;
-
-        let ttfb_ms = start.elapsed().as_secs_f64() * 1000.0;
-
-        let status = res.status().as_u16();
-        let reason = res.status().canonical_reason().map(|s| s.to_string());
-
-        let mut headers = ResponseHeaders::default();
-        for (key, value) in res.headers() {
-            let key_str = key.as_str();
-            let val_str = value.to_str().unwrap_or("");
-            match key_str {
-                "location" => headers.location = Some(val_str.to_string()),
-                "server" => headers.server = Some(val_str.to_string()),
-                "content-type" => headers.content_type = Some(val_str.to_string()),
-                "content-length" => headers.content_length = val_str.parse().ok(),
-                "transfer-encoding" => headers.transfer_encoding = Some(val_str.to_string()),
-                _ => {}
-            }
+pub(super) async fn read_h1_response(
+    stream: &mut BoxedIoStream,
+    request: &HttpRequest,
+    start: Instant,
+    request_sent_ms: f64,
+    mut buffer: Vec<u8>,
+    mut first_byte_ms: Option<f64>,
+) -> Result<(HttpResponse, bool), UdocError> {
+    let body_limit = request.body_limit;
+    let proto = if request.is_https { "HTTPS" } else { "HTTP" };
+
+    let mut chunk = vec![0u8; READ_CHUNK];
+    let mut headers_ms: Option<f64> = None;
+    let mut framing: Option<(usize, BodyFraming)> = None;
+    let mut body_truncated = false;
+    let mut body_end: Option<usize> = None;
+
+    loop {
+        if framing.is_none()
+            && let Ok((summary, headers, pos)) = parse_response_with_1xx_skip(&buffer, proto) {
+            headers_ms = Some(elapsed_ms(start));
+            framing = Some((pos, BodyFraming::of(&request.method, summary.status, &headers)));
+        }
+        if let Some((pos, ref f)) = framing
+            && let Some(end) = f.end(&buffer[pos..]) {
+            body_end = Some(pos + end);
+            break;
+        }
+        match framing {
+            Some((pos, _)) if buffer.len() - pos >= body_limit => { body_truncated = true; break; }
+            None if buffer.len() >= HEADER_LIMIT => break,
+            _ => {}
         }
 
-        let mut body_preview = Vec::with_capacity(body_limit.min(8192));
-        let mut body = res.into_body();
-        while body_preview.len() < body_limit {
-            match body.frame().await {
-                Some(Ok(frame)) => {
-                    if let Some(chunk) = frame.data_ref() {
-                        let remaining = body_limit - body_preview.len();
-                        let to_copy = chunk.len().min(remaining);
-                        body_preview.extend_from_slice(&chunk[..to_copy]);
-                    }
-                }
-                Some(Err(e)) => return Err(UdocError::http(format!("failed to read h2 body: {}", e))),
-                None => break,
+        let n = stream.read(&mut chunk).await.map_err(|e| UdocError::http(format!("failed to read response: {}", e)))?;
+        if n == 0 { break; }
+        if first_byte_ms.is_none() { first_byte_ms = Some(elapsed_ms(start)); }
+        buffer.extend_from_slice(&chunk[..n]);
+    }
+    let body_ms = elapsed_ms(start);
+
+    let (summary, headers, body_start) = parse_response_with_1xx_skip(&buffer, proto)?;
+
+    let body_preview = if body_start < buffer.len() {
+        let raw_body = &buffer[body_start..];
+        if headers.transfer_encoding.as_ref().map(|t| t.contains("chunked")).unwrap_or(false) {
+            decode_chunked_preview(raw_body, body_limit)
+        } else {
+            raw_body[..raw_body.len().min(body_limit)].to_vec()
+        }
+    } else {
+        Vec::new()
+    };
+
+    let timing = ExchangeTiming {
+        request_sent_ms,
+        ttfb_ms: first_byte_ms.unwrap_or(body_ms),
+        headers_ms: headers_ms.unwrap_or(body_ms),
+        body_ms,
+    };
+
+    let persistent = match headers.all.get("connection").map(|c| c.to_ascii_lowercase()) {
+        Some(c) if c.contains("close") => false,
+        Some(c) if c.contains("keep-alive") => true,
+        _ => summary.version == "http/1.1",
+    };
+    let reusable = persistent && body_end == Some(buffer.len());
+
+    let response = HttpResponse { summary, headers, timing, body_preview, body_truncated, trailers: HeaderList::new(), upgrade: None, websocket: None, h2: None, connection: None };
+    Ok((response, reusable))
+}
+
+enum BodyFraming {
+    Empty,
+    Length(u64),
+    Chunked,
+    UntilClose,
+}
+
+impl BodyFraming {
+    fn of(method: &str, status: u16, headers: &ResponseHeaders) -> Self {
+        if method.eq_ignore_ascii_case("HEAD") || status == 204 || status == 304 {
+            BodyFraming::Empty
+        } else if headers.transfer_encoding.as_ref().map(|t| t.contains("chunked")).unwrap_or(false) {
+            BodyFraming::Chunked
+        } else if let Some(len) = headers.content_length {
+            BodyFraming::Length(len)
+        } else {
+            BodyFraming::UntilClose
+        }
+    }
+
+    fn end(&self, body: &[u8]) -> Option<usize> {
+        match self {
+            BodyFraming::Empty => Some(0),
+            BodyFraming::Length(len) => (body.len() as u64 >= *len).then_some(*len as usize),
+            BodyFraming::Chunked => chunked_body_end(body),
+            BodyFraming::UntilClose => None,
+        }
+    }
+}
+
+fn chunked_body_end(data: &[u8]) -> Option<usize> {
+    let mut pos = 0;
+    loop {
+        let line_end = pos + data[pos..].iter().position(|&b| b == b'\n')?;
+        let size_str = std::str::from_utf8(&data[pos..line_end]).ok()?.trim_end_matches('\r');
+        let chunk_size = usize::from_str_radix(size_str.split(';').next()?.trim(), 16).ok()?;
+        pos = line_end + 1;
+
+        if chunk_size == 0 {
+            loop {
+                let line_end = pos + data[pos..].iter().position(|&b| b == b'\n')?;
+                let line = &data[pos..line_end];
+                pos = line_end + 1;
+                if line.is_empty() || line == b"\r" { return Some(pos); }
             }
         }
-        drop(body);
 
-        Ok(HttpResponse {
-            summary: HttpSummary::new(status, reason, "h2".to_string(), "HTTPS".to_string()),
-            headers,
-            ttfb_ms,
-            body_preview,
-        })
+        pos = pos.checked_add(chunk_size)?.checked_add(2)?;
+        if pos > data.len() { return None; }
     }
 }
 
-fn find_header_end(data: &[u8]) -> Option<usize> {
+pub(super) fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+pub(super) fn find_header_end(data: &[u8]) -> Option<usize> {
     for i in 0..data.len().saturating_sub(3) {
         if &data[i..i+4] == b"\r\n\r\n" { return Some(i); }
     }
@@ -161,7 +467,7 @@ fn parse_response_with_1xx_skip(data: &[u8], proto: &str) -> Result<(HttpSummary
     }
 }
 
-fn parse_headers(header_bytes: &[u8], proto: &str) -> Result<(HttpSummary, ResponseHeaders), UdocError> {
+pub(super) fn parse_headers(header_bytes: &[u8], proto: &str) -> Result<(HttpSummary, ResponseHeaders), UdocError> {
     let mut lines = header_bytes.split(|&b| b == b'\n');
     let status_line = lines.next().ok_or_else(|| UdocError::http("missing status line"))?;
     let status_line = std::str::from_utf8(status_line).map_err(|_| UdocError::http("invalid status line encoding"))?;
@@ -188,11 +494,13 @@ fn parse_headers(header_bytes: &[u8], proto: &str) -> Result<(HttpSummary, Respo
         if let Some((key, value)) = line.split_once(':') {
             let key_lower = key.trim().to_ascii_lowercase();
             let value = value.trim();
+            headers.all.push(&key_lower, value);
             match key_lower.as_str() {
                 "location" => headers.location = Some(value.to_string()),
                 "server" => headers.server = Some(value.to_string()),
                 "content-type" => headers.content_type = Some(value.to_string()),
                 "content-length" => headers.content_length = value.parse().ok(),
+                "content-encoding" => headers.content_encoding = Some(value.to_ascii_lowercase()),
                 "transfer-encoding" => headers.transfer_encoding = Some(value.to_ascii_lowercase()),
                 _ => {}
             }
@@ -228,14 +536,50 @@ fn decode_chunked_preview(data: &[u8], limit: usize) -> Vec<u8> {
     result
 }
 
-struct TokioIo(BoxedIoStream);
+#[derive(Default)]
+struct WriteMarks {
+    armed: Option<Instant>,
+    wrote: bool,
+    sent: Option<Instant>,
+}
+
+impl WriteMarks {
+    fn arm(&mut self, at: Instant) {
+        self.armed = Some(at);
+        self.wrote = false;
+        self.sent = None;
+    }
+
+    fn on_write(&mut self) {
+        if self.armed.is_some() { self.wrote = true; }
+    }
+
+    fn on_flush(&mut self) {
+        if self.wrote && self.sent.is_none() { self.sent = Some(Instant::now()); }
+    }
+}
+
+struct TokioIo {
+    inner: BoxedIoStream,
+    marks: Arc<Mutex<WriteMarks>>,
+    tracer: Arc<Mutex<FrameTracer>>,
+    /// Signalled once, when the server's first SETTINGS frame has been read.
+    settings: Arc<tokio::sync::Notify>,
+}
 
 impl hyper::rt::Read for TokioIo {
     fn poll_read(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>, mut buf: hyper::rt::ReadBufCursor<'_>) -> std::task::Poll<std::io::Result<()>> {
         let mut tbuf = tokio::io::ReadBuf::uninit(unsafe { buf.as_mut() });
-        match std::pin::Pin::new(&mut self.0).poll_read(cx, &mut tbuf) {
+        match std::pin::Pin::new(&mut self.inner).poll_read(cx, &mut tbuf) {
             std::task::Poll::Ready(Ok(())) => {
                 let n = tbuf.filled().len();
+                if let Ok(mut t) = self.tracer.lock() {
+                    let had_settings = t.settings_received();
+                    t.feed(tbuf.filled());
+                    if !had_settings && t.settings_received() {
+                        self.settings.notify_one();
+                    }
+                }
                 unsafe { buf.advance(n); }
                 std::task::Poll::Ready(Ok(()))
             }
@@ -247,12 +591,164 @@ impl hyper::rt::Read for TokioIo {
 
 impl hyper::rt::Write for TokioIo {
     fn poll_write(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>, buf: &[u8]) -> std::task::Poll<std::io::Result<usize>> {
-        std::pin::Pin::new(&mut self.0).poll_write(cx, buf)
+        let res = std::pin::Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let std::task::Poll::Ready(Ok(n)) = res
+            && n > 0
+            && let Ok(mut m) = self.marks.lock() {
+            m.on_write();
+        }
+        res
     }
     fn poll_flush(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
-        std::pin::Pin::new(&mut self.0).poll_flush(cx)
+        let res = std::pin::Pin::new(&mut self.inner).poll_flush(cx);
+        if let std::task::Poll::Ready(Ok(())) = res
+            && let Ok(mut m) = self.marks.lock() {
+            m.on_flush();
+        }
+        res
     }
     fn poll_shutdown(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
-        std::pin::Pin::new(&mut self.0).poll_shutdown(cx)
+        std::pin::Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+    use tokio::net::TcpListener;
+    use crate::ports::TcpDialer;
+    use super::super::TokioTcpDialer;
+    use super::*;
+
+    #[test]
+    fn chunked_body_end_waits_for_the_last_chunk() {
+        let body = b"5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+        for split in 0..body.len() {
+            assert_eq!(chunked_body_end(&body[..split]), None, "split at {}", split);
+        }
+        assert_eq!(chunked_body_end(body), Some(body.len()));
+    }
+
+    #[test]
+    fn chunked_body_end_handles_extensions_and_trailers() {
+        assert_eq!(chunked_body_end(b"5;name=\"v\"\r\nhello\r\n0;last\r\n\r\n"), Some(29));
+        let body = b"3\r\nabc\r\n0\r\nX-Checksum: 1\r\nServer-Timing: db;dur=3\r\n\r\n";
+        assert_eq!(chunked_body_end(body), Some(body.len()));
+        assert_eq!(chunked_body_end(&body[..body.len() - 2]), None);
+        // Bytes of a pipelined next response are not part of this body.
+        assert_eq!(chunked_body_end(b"0\r\n\r\nHTTP/1.1 200 OK\r\n"), Some(5));
+        assert_eq!(chunked_body_end(b"zz\r\nhello\r\n0\r\n\r\n"), None);
+    }
+
+    /// Answers each request head with the next reply, over as many connections as the client
+    /// opens, and closes after the last one; yields the number of connections accepted.
+    async fn h1_server(replies: Vec<&'static [u8]>) -> (u16, tokio::task::JoinHandle<usize>) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = tokio::spawn(async move {
+            let mut replies = replies.into_iter().peekable();
+            let mut connections = 0;
+            'accept: while replies.peek().is_some() {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                connections += 1;
+                let mut buffer = Vec::new();
+                while let Some(reply) = replies.peek() {
+                    while find_header_end(&buffer).is_none() {
+                        let mut chunk = [0u8; 1024];
+                        let n = socket.read(&mut chunk).await.unwrap();
+                        if n == 0 { continue 'accept; }
+                        buffer.extend_from_slice(&chunk[..n]);
+                    }
+                    buffer.drain(..find_header_end(&buffer).unwrap() + 4);
+                    socket.write_all(reply).await.unwrap();
+                    replies.next();
+                }
+            }
+            connections
+        });
+        (port, handle)
+    }
+
+    async fn connect(port: u16) -> BoxedIoStream {
+        TokioTcpDialer::new().connect(IpAddr::V4(Ipv4Addr::LOCALHOST), port).await.unwrap().stream
+    }
+
+    #[tokio::test]
+    async fn keep_alive_reuses_the_connection_until_close() {
+        let (port, server) = h1_server(vec![
+            b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n",
+            b"HTTP/1.1 304 Not Modified\r\nETag: \"a\"\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2\r\nde\r\n0\r\nX-Trailer: 1\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 3\r\n\r\nbye",
+        ]).await;
+        let client = HybridHttpClient::new();
+        let get = HttpRequest::get("localhost", port, "/", false, 1024).with_keep_alive(true);
+        let head = HttpRequest { method: "HEAD".to_string(), ..get.clone() };
+
+        let response = client.request_h1(connect(port).await, &get).await.unwrap();
+        assert_eq!(response.body_preview, b"ok");
+        let mut bodies = Vec::new();
+        let mut connection = response.connection;
+        for request in [&head, &get, &get] {
+            let response = client.request_pooled(connection.expect("connection was not pooled"), request).await.unwrap();
+            bodies.push(response.body_preview);
+            connection = response.connection;
+        }
+        assert_eq!(bodies, [b"".to_vec(), b"".to_vec(), b"abcde".to_vec()]);
+
+        let response = client.request_pooled(connection.expect("connection was not pooled"), &get).await.unwrap();
+        assert_eq!(response.body_preview, b"bye");
+        assert!(response.connection.is_none(), "Connection: close must not be pooled");
+        assert_eq!(server.await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn close_delimited_and_http10_responses_are_not_pooled() {
+        let (port, server) = h1_server(vec![b"HTTP/1.1 200 OK\r\n\r\nuntil close"]).await;
+        let get = HttpRequest::get("localhost", port, "/", false, 1024).with_keep_alive(true);
+        let response = HybridHttpClient::new().request_h1(connect(port).await, &get).await.unwrap();
+        assert_eq!(response.body_preview, b"until close");
+        assert!(response.connection.is_none());
+        server.await.unwrap();
+
+        let (port, server) = h1_server(vec![b"HTTP/1.0 200 OK\r\nContent-Length: 2\r\n\r\nok"]).await;
+        let response = HybridHttpClient::new().request_h1(connect(port).await, &get).await.unwrap();
+        assert!(response.connection.is_none());
+        server.await.unwrap();
+    }
+
+    async fn h2_server(io: tokio::io::DuplexStream, max_concurrent_streams: u32) {
+        let mut conn = h2::server::Builder::new()
+            .max_concurrent_streams(max_concurrent_streams)
+            .handshake::<_, Bytes>(io).await.unwrap();
+        while let Some(Ok((request, mut respond))) = conn.accept().await {
+            tokio::spawn(async move {
+                let status = if request.uri().path() == "/missing" { 404 } else { 200 };
+                let response = http::Response::builder().status(status).body(()).unwrap();
+                let mut body = respond.send_response(response, false).unwrap();
+                body.send_data(Bytes::from_static(b"ok"), true).unwrap();
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn multiplex_respects_server_max_concurrent_streams() {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(h2_server(server, 2));
+        let base = HttpRequest::get("localhost", 80, "/", false, 1024);
+        let requests: Vec<HttpRequest> = ["/", "/missing", "/", "/"].iter()
+            .map(|path| HttpRequest { path: path.to_string(), ..base.clone() })
+            .collect();
+
+        let probe = HybridHttpClient::new()
+            .request_h2_multiplex(BoxedIoStream(Box::new(client)), &requests).await.unwrap();
+
+        assert_eq!(probe.max_concurrent_streams, Some(2));
+        assert_eq!(probe.in_flight, 2);
+        assert_eq!(probe.completed(), 4);
+        let statuses: Vec<Option<u16>> = probe.streams.iter().map(|s| s.status).collect();
+        assert_eq!(statuses, vec![Some(200), Some(404), Some(200), Some(200)]);
+        assert!(probe.streams.iter().all(|s| s.bytes == 2));
     }
 }
@@ -2,12 +2,25 @@ mod dns;
 mod tcp;
 mod tls;
 mod http;
+mod h2c;
+mod h2_frames;
+mod websocket;
+mod quic;
+mod proxy;
+mod unix;
+mod proxy_protocol;
+mod starttls;
 mod clock;
 mod renderer;
 
 pub use dns::HickoryDnsResolver;
 pub use tcp::TokioTcpDialer;
+pub use proxy::ProxyTcpDialer;
+pub use unix::UnixSocketDialer;
+pub use proxy_protocol::ProxyProtocolDialer;
+pub use starttls::PlaintextStartTls;
 pub use tls::RustlsTlsHandshaker;
 pub use http::HybridHttpClient;
+pub use quic::QuinnQuicClient;
 pub use clock::TokioClock;
 pub use renderer::{PrettyRenderer, JsonRenderer};
@@ -0,0 +1,207 @@
+use std::net::{IpAddr, SocketAddr};
+use tokio::io::AsyncWriteExt;
+use crate::domain::{ProxyProtocolHeader, ProxyProtocolSpec, ProxyProtocolVersion, UdocError};
+use crate::ports::{TcpConnection, TcpDialer};
+
+const V2_SIGNATURE: [u8; 12] = [0x0D, 0x0A, 0x0D, 0x0A, 0x00, 0x0D, 0x0A, 0x51, 0x55, 0x49, 0x54, 0x0A];
+
+pub struct ProxyProtocolDialer<T> {
+    inner: T,
+    spec: Option<ProxyProtocolSpec>,
+}
+
+impl<T: TcpDialer> ProxyProtocolDialer<T> {
+    pub fn new(inner: T) -> Self {
+        Self { inner, spec: None }
+    }
+
+    pub fn with_spec(mut self, spec: Option<ProxyProtocolSpec>) -> Self {
+        self.spec = spec;
+        self
+    }
+
+    async fn send_header(&self, mut conn: TcpConnection, peer: Option<SocketAddr>) -> Result<TcpConnection, UdocError> {
+        let Some(spec) = self.spec else { return Ok(conn) };
+        let source = spec.source.or(conn.local_addr);
+        let destination = spec.destination.or(peer);
+        let addresses = match (source, destination) {
+            (Some(src), Some(dst)) if src.is_ipv4() != dst.is_ipv4() => {
+                return Err(UdocError::input(format!("PROXY protocol source {} and destination {} must be the same address family", src, dst)));
+            }
+            (Some(src), Some(dst)) => Some((src, dst)),
+            _ => None,
+        };
+
+        let bytes = match spec.version {
+            ProxyProtocolVersion::V1 => encode_v1(addresses),
+            ProxyProtocolVersion::V2 => encode_v2(addresses),
+        };
+        conn.stream.write_all(&bytes).await.map_err(|e| header_error(spec.version, e))?;
+        conn.stream.flush().await.map_err(|e| header_error(spec.version, e))?;
+
+        conn.proxy_protocol = Some(ProxyProtocolHeader {
+            version: spec.version,
+            source: addresses.map(|(src, _)| src),
+            destination: addresses.map(|(_, dst)| dst),
+            bytes: bytes.len(),
+        });
+        Ok(conn)
+    }
+}
+
+impl<T: TcpDialer> TcpDialer for ProxyProtocolDialer<T> {
+    async fn connect(&self, ip: IpAddr, port: u16) -> Result<TcpConnection, UdocError> {
+        let conn = self.inner.connect(ip, port).await?;
+        self.send_header(conn, Some(SocketAddr::new(ip, port))).await
+    }
+
+    fn tunnels(&self, host: &str, port: u16) -> bool {
+        self.inner.tunnels(host, port)
+    }
+
+    async fn connect_tunnel(&self, host: &str, port: u16) -> Result<TcpConnection, UdocError> {
+        let conn = self.inner.connect_tunnel(host, port).await?;
+        let peer = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().ok().map(|ip| SocketAddr::new(ip, port));
+        self.send_header(conn, peer).await
+    }
+}
+
+fn header_error(version: ProxyProtocolVersion, e: std::io::Error) -> UdocError {
+    UdocError::tcp(format!("failed to write PROXY protocol {} header: {}", version, e))
+}
+
+fn encode_v1(addresses: Option<(SocketAddr, SocketAddr)>) -> Vec<u8> {
+    match addresses {
+        Some((src, dst)) => {
+            let family = if src.is_ipv4() { "TCP4" } else { "TCP6" };
+            format!("PROXY {} {} {} {} {}\r\n", family, src.ip(), dst.ip(), src.port(), dst.port()).into_bytes()
+        }
+        None => b"PROXY UNKNOWN\r\n".to_vec(),
+    }
+}
+
+fn encode_v2(addresses: Option<(SocketAddr, SocketAddr)>) -> Vec<u8> {
+    let mut out = V2_SIGNATURE.to_vec();
+    let Some((src, dst)) = addresses else {
+        out.extend_from_slice(&[0x20, 0x00, 0x00, 0x00]);
+        return out;
+    };
+
+    let mut body = Vec::with_capacity(36);
+    let family = match (src.ip(), dst.ip()) {
+        (IpAddr::V4(s), IpAddr::V4(d)) => {
+            body.extend_from_slice(&s.octets());
+            body.extend_from_slice(&d.octets());
+            0x11
+        }
+        (s, d) => {
+            body.extend_from_slice(&to_v6(s).octets());
+            body.extend_from_slice(&to_v6(d).octets());
+            0x21
+        }
+    };
+    body.extend_from_slice(&src.port().to_be_bytes());
+    body.extend_from_slice(&dst.port().to_be_bytes());
+
+    out.extend_from_slice(&[0x21, family]);
+    out.extend_from_slice(&(body.len() as u16).to_be_bytes());
+    out.extend_from_slice(&body);
+    out
+}
+
+fn to_v6(ip: IpAddr) -> std::net::Ipv6Addr {
+    match ip {
+        IpAddr::V4(v4) => v4.to_ipv6_mapped(),
+        IpAddr::V6(v6) => v6,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+    use super::super::TokioTcpDialer;
+    use super::*;
+
+    fn v4(a: [u8; 4], port: u16) -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::from(a)), port)
+    }
+
+    #[test]
+    fn encodes_v1_lines() {
+        assert_eq!(encode_v1(Some((v4([192, 0, 2, 1], 56324), v4([198, 51, 100, 7], 443)))), b"PROXY TCP4 192.0.2.1 198.51.100.7 56324 443\r\n");
+        let (src, dst) = ("[2001:db8::1]:1000".parse().unwrap(), "[2001:db8::2]:443".parse().unwrap());
+        assert_eq!(encode_v1(Some((src, dst))), b"PROXY TCP6 2001:db8::1 2001:db8::2 1000 443\r\n");
+        assert_eq!(encode_v1(None), b"PROXY UNKNOWN\r\n");
+    }
+
+    #[test]
+    fn encodes_v2_headers() {
+        let header = encode_v2(Some((v4([192, 0, 2, 1], 0x1234), v4([198, 51, 100, 7], 443))));
+        let expected = [
+            &V2_SIGNATURE[..],
+            &[0x21, 0x11, 0x00, 12],
+            &[192, 0, 2, 1, 198, 51, 100, 7],
+            &[0x12, 0x34, 0x01, 0xbb],
+        ].concat();
+        assert_eq!(header, expected);
+
+        let src = SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST), 1);
+        let header = encode_v2(Some((src, SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST), 2))));
+        assert_eq!(&header[12..16], &[0x21, 0x21, 0x00, 36]);
+        assert_eq!(header.len(), 16 + 36);
+        assert_eq!(&header[16..32], &Ipv6Addr::LOCALHOST.octets());
+        assert_eq!(&header[48..], &[0, 1, 0, 2]);
+
+        assert_eq!(encode_v2(None), [&V2_SIGNATURE[..], &[0x20, 0x00, 0x00, 0x00]].concat());
+    }
+
+    async fn accept_header(version: ProxyProtocolVersion) -> (SocketAddr, SocketAddr, Vec<u8>, ProxyProtocolHeader) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let server_addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, peer) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut byte = [0u8; 1];
+            loop {
+                let done = match version {
+                    ProxyProtocolVersion::V1 => received.ends_with(b"\r\n"),
+                    ProxyProtocolVersion::V2 => received.len() >= 16 && received.len() == 16 + u16::from_be_bytes([received[14], received[15]]) as usize,
+                };
+                if done { break; }
+                socket.read_exact(&mut byte).await.unwrap();
+                received.push(byte[0]);
+            }
+            (peer, received)
+        });
+
+        let dialer = ProxyProtocolDialer::new(TokioTcpDialer::new())
+            .with_spec(Some(ProxyProtocolSpec { version, source: None, destination: None }));
+        let conn = dialer.connect(server_addr.ip(), server_addr.port()).await.unwrap();
+        let (peer, received) = server.await.unwrap();
+        (peer, server_addr, received, conn.proxy_protocol.unwrap())
+    }
+
+    #[tokio::test]
+    async fn sends_v1_line_on_connect() {
+        let (peer, server, received, header) = accept_header(ProxyProtocolVersion::V1).await;
+        let line = format!("PROXY TCP4 {} {} {} {}\r\n", peer.ip(), server.ip(), peer.port(), server.port());
+        assert_eq!(String::from_utf8(received).unwrap(), line);
+        assert_eq!((header.source, header.destination, header.bytes), (Some(peer), Some(server), line.len()));
+    }
+
+    #[tokio::test]
+    async fn sends_v2_header_on_connect() {
+        let (peer, server, received, header) = accept_header(ProxyProtocolVersion::V2).await;
+        assert_eq!(&received[..12], &V2_SIGNATURE);
+        assert_eq!(received[12], 0x21, "version 2, PROXY command");
+        assert_eq!(received[13], 0x11, "AF_INET over STREAM");
+        assert_eq!(&received[14..16], &[0, 12]);
+        assert_eq!(&received[16..20], &[127, 0, 0, 1]);
+        assert_eq!(&received[20..24], &[127, 0, 0, 1]);
+        assert_eq!(&received[24..26], &peer.port().to_be_bytes());
+        assert_eq!(&received[26..28], &server.port().to_be_bytes());
+        assert_eq!((header.source, header.destination, header.bytes), (Some(peer), Some(server), 28));
+    }
+}
@@ -0,0 +1,258 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Instant;
+use bytes::{Buf, Bytes};
+use quinn::crypto::rustls::{HandshakeData, QuicClientConfig};
+use rustls::ClientConfig;
+use rustls::pki_types::CertificateDer;
+use crate::domain::{ExchangeTiming, HeaderList, HttpSummary, QuicSummary, TlsSummary, UdocError};
+use crate::ports::{HttpRequest, HttpResponse, QuicClient, QuicExchange};
+use super::http::{elapsed_ms, response_headers};
+
+#[derive(Clone)]
+pub struct QuinnQuicClient {
+    config: quinn::ClientConfig,
+}
+
+impl QuinnQuicClient {
+    pub fn new() -> Result<Self, UdocError> {
+        Self::with_roots(rustls::RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()))
+    }
+
+    fn with_roots(root_store: rustls::RootCertStore) -> Result<Self, UdocError> {
+        let mut tls = ClientConfig::builder_with_protocol_versions(&[&rustls::version::TLS13])
+            .with_root_certificates(root_store)
+            .with_no_client_auth();
+        tls.alpn_protocols = vec![b"h3".to_vec()];
+        tls.enable_early_data = true;
+        let crypto = QuicClientConfig::try_from(tls)
+            .map_err(|e| UdocError::tls(format!("QUIC TLS config rejected: {}", e)))?;
+        Ok(Self { config: quinn::ClientConfig::new(Arc::new(crypto)) })
+    }
+}
+
+impl QuicClient for QuinnQuicClient {
+    async fn request_h3(&self, ip: IpAddr, port: u16, request: &HttpRequest) -> Result<QuicExchange, UdocError> {
+        let bind = match ip {
+            IpAddr::V4(_) => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+            IpAddr::V6(_) => SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+        };
+        let mut endpoint = quinn::Endpoint::client(bind)
+            .map_err(|e| UdocError::tcp(format!("failed to open UDP socket: {}", e)))?;
+        endpoint.set_default_client_config(self.config.clone());
+
+        let start = Instant::now();
+        let connecting = endpoint.connect(SocketAddr::new(ip, port), &request.host)
+            .map_err(|e| UdocError::tcp(format!("QUIC connect failed: {}", e)))?;
+        // 0-RTT data can be replayed by an attacker, so only safe requests are sent early.
+        let zero_rtt = if request.is_safe() {
+            connecting.into_0rtt()
+        } else {
+            Err(connecting)
+        };
+        let (connection, handshake) = match zero_rtt {
+            Ok((connection, accepted)) => {
+                // The request goes out before the handshake completes; time the handshake
+                // in the background instead of stopping the clock right away.
+                let handshake = tokio::spawn(async move {
+                    let accepted = accepted.await;
+                    (elapsed_ms(start), Some(accepted))
+                });
+                (connection, handshake)
+            }
+            Err(connecting) => {
+                let connection = connecting.await
+                    .map_err(|e| UdocError::tls(format!("QUIC handshake failed: {}", e)))?;
+                let handshake_ms = elapsed_ms(start);
+                (connection, tokio::spawn(async move { (handshake_ms, None) }))
+            }
+        };
+
+        let (mut driver, mut sender) = h3::client::new(h3_quinn::Connection::new(connection.clone())).await
+            .map_err(|e| UdocError::http(format!("h3 setup failed: {}", e)))?;
+        let driver_task = tokio::spawn(async move {
+            let _ = std::future::poll_fn(|cx| driver.poll_close(cx)).await;
+        });
+
+        let uri = format!("https://{}{}", request.authority(), request.path);
+        let mut builder = http::Request::builder()
+            .method(request.method.as_str())
+            .uri(&uri)
+            .header("user-agent", "udoc/0.2.1")
+            .header("accept", "*/*");
+        for (name, value) in &request.headers {
+            builder = builder.header(name.to_ascii_lowercase(), value.as_str());
+        }
+        let req = builder.body(())
+            .map_err(|e| UdocError::http(format!("failed to build request: {}", e)))?;
+
+        let exchange_start = Instant::now();
+        let mut stream = sender.send_request(req).await
+            .map_err(|e| UdocError::http(format!("h3 request failed: {}", e)))?;
+        if !request.body.is_empty() {
+            stream.send_data(Bytes::copy_from_slice(&request.body)).await
+                .map_err(|e| UdocError::http(format!("h3 request failed: {}", e)))?;
+        }
+        stream.finish().await
+            .map_err(|e| UdocError::http(format!("h3 request failed: {}", e)))?;
+        let request_sent_ms = elapsed_ms(exchange_start);
+
+        let res = stream.recv_response().await
+            .map_err(|e| UdocError::http(format!("h3 response failed: {}", e)))?;
+        let headers_ms = elapsed_ms(exchange_start);
+
+        let status = res.status().as_u16();
+        let reason = res.status().canonical_reason().map(|s| s.to_string());
+        let headers = response_headers(res.headers());
+
+        let body_limit = request.body_limit;
+        let mut body_preview = Vec::with_capacity(body_limit.min(8192));
+        let mut body_truncated = false;
+        while let Some(mut chunk) = stream.recv_data().await
+            .map_err(|e| UdocError::http(format!("failed to read h3 body: {}", e)))? {
+            while chunk.has_remaining() && !body_truncated {
+                let bytes = chunk.chunk();
+                let to_copy = bytes.len().min(body_limit - body_preview.len());
+                body_preview.extend_from_slice(&bytes[..to_copy]);
+                body_truncated = to_copy < bytes.len();
+                let len = bytes.len();
+                chunk.advance(len);
+            }
+            if body_truncated { break; }
+        }
+
+        let mut trailers = HeaderList::new();
+        if !body_truncated
+            && let Ok(Some(map)) = stream.recv_trailers().await {
+            for (key, value) in &map {
+                trailers.push(key.as_str(), value.to_str().unwrap_or(""));
+            }
+        }
+        let body_ms = elapsed_ms(exchange_start);
+
+        let (handshake_ms, early_data_accepted) = handshake.await
+            .map_err(|e| UdocError::tls(format!("QUIC handshake failed: {}", e)))?;
+
+        let alpn = connection.handshake_data()
+            .and_then(|data| data.downcast::<HandshakeData>().ok())
+            .and_then(|data| data.protocol)
+            .map(|p| String::from_utf8_lossy(&p).to_string());
+        let peer_certs: Vec<Vec<u8>> = connection.peer_identity()
+            .and_then(|id| id.downcast::<Vec<CertificateDer<'static>>>().ok())
+            .map(|certs| certs.iter().map(|c| c.as_ref().to_vec()).collect())
+            .unwrap_or_default();
+        let chain_len = peer_certs.len();
+
+        connection.close(0u32.into(), b"done");
+        driver_task.abort();
+        // Dropping the endpoint straight away can lose the CONNECTION_CLOSE frame.
+        endpoint.wait_idle().await;
+
+        Ok(QuicExchange {
+            quic: QuicSummary { handshake_ms, zero_rtt_attempted: early_data_accepted.is_some(), early_data_accepted, via_alt_svc: false },
+            // quinn does not expose the negotiated cipher suite. Verification is real: the handshake
+            // above fails unless the chain passed the webpki verifier.
+            tls: TlsSummary::new("TLS1.3".to_string(), alpn, None, chain_len, true)
+                .with_alpn_offered(vec!["h3".to_string()]),
+            peer_certs,
+            response: HttpResponse {
+                summary: HttpSummary::new(status, reason, "h3".to_string(), "HTTPS".to_string()),
+                headers,
+                timing: ExchangeTiming { request_sent_ms, ttfb_ms: headers_ms, headers_ms, body_ms },
+                body_preview,
+                body_truncated,
+                trailers,
+                upgrade: None,
+                websocket: None,
+                h2: None,
+                connection: None,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustls::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer};
+    use super::*;
+
+    async fn h3_server(cert: CertificateDer<'static>, key: PrivateKeyDer<'static>) -> SocketAddr {
+        let mut tls = rustls::ServerConfig::builder_with_protocol_versions(&[&rustls::version::TLS13])
+            .with_no_client_auth()
+            .with_single_cert(vec![cert], key)
+            .unwrap();
+        tls.alpn_protocols = vec![b"h3".to_vec()];
+        tls.max_early_data_size = u32::MAX;
+        let crypto = quinn::crypto::rustls::QuicServerConfig::try_from(tls).unwrap();
+        let config = quinn::ServerConfig::with_crypto(Arc::new(crypto));
+        let endpoint = quinn::Endpoint::server(config, SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+        let addr = endpoint.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Some(incoming) = endpoint.accept().await {
+                tokio::spawn(async move {
+                    let Ok(connection) = incoming.await else { return };
+                    let mut h3_conn = h3::server::builder()
+                        .build::<_, Bytes>(h3_quinn::Connection::new(connection)).await.unwrap();
+                    while let Ok(Some(resolver)) = h3_conn.accept().await {
+                        let (request, mut stream) = resolver.resolve_request().await.unwrap();
+                        let status = if request.uri().path() == "/missing" { 404 } else { 200 };
+                        let response = http::Response::builder().status(status).header("server", "loopback").body(()).unwrap();
+                        stream.send_response(response).await.unwrap();
+                        stream.send_data(Bytes::from_static(b"hello h3")).await.unwrap();
+                        stream.finish().await.unwrap();
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    async fn loopback_client() -> (QuinnQuicClient, SocketAddr) {
+        rustls::crypto::ring::default_provider().install_default().ok();
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert = certified.cert.der().clone();
+        let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(certified.signing_key.serialize_der()));
+        let addr = h3_server(cert.clone(), key).await;
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(cert).unwrap();
+        (QuinnQuicClient::with_roots(roots).unwrap(), addr)
+    }
+
+    #[tokio::test]
+    async fn h3_exchange_over_loopback() {
+        let (client, addr) = loopback_client().await;
+        let request = HttpRequest::get("localhost", addr.port(), "/", true, 1024);
+        let exchange = client.request_h3(addr.ip(), addr.port(), &request).await.unwrap();
+
+        assert!(exchange.quic.handshake_ms > 0.0);
+        assert!(!exchange.quic.zero_rtt_attempted);
+        assert_eq!(exchange.quic.mode(), "1-RTT");
+        assert_eq!(exchange.tls.alpn.as_deref(), Some("h3"));
+        assert_eq!((exchange.tls.version.as_str(), exchange.tls.cipher.as_deref(), exchange.tls.chain_len), ("TLS1.3", None, 1));
+        assert_eq!(exchange.response.summary.status, 200);
+        assert_eq!(exchange.response.headers.all.get("server"), Some("loopback"));
+        assert_eq!(exchange.response.body_preview, b"hello h3");
+        assert_eq!(exchange.peer_certs.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn zero_rtt_only_for_safe_methods_on_resumption() {
+        let (client, addr) = loopback_client().await;
+        let get = HttpRequest::get("localhost", addr.port(), "/", true, 1024);
+        let first = client.request_h3(addr.ip(), addr.port(), &get).await.unwrap();
+        assert!(!first.quic.zero_rtt_attempted);
+
+        let resumed = client.request_h3(addr.ip(), addr.port(), &get).await.unwrap();
+        assert!(resumed.quic.zero_rtt_attempted);
+        assert_eq!(resumed.quic.early_data_accepted, Some(true));
+        assert!(resumed.quic.handshake_ms > 0.0);
+        assert_eq!(resumed.response.body_preview, b"hello h3");
+
+        let post = get.with_body("POST", b"payload".to_vec());
+        let exchange = client.request_h3(addr.ip(), addr.port(), &post).await.unwrap();
+        assert!(!exchange.quic.zero_rtt_attempted);
+        assert_eq!(exchange.response.summary.status, 200);
+    }
+}
@@ -1,11 +1,35 @@
-use crate::domain::Report;
+use crate::domain::{CacheSummary, CertSummary, LatencyStats, Report, TcpingAttempt, TcpingSummary, TlsProbeReport, TlsSummary, Warning};
 use crate::ports::Renderer;
 use serde::Serialize;
 
+#[derive(Default)]
 pub struct PrettyRenderer;
 
 impl PrettyRenderer {
     pub fn new() -> Self { Self }
+
+    pub fn render_tcping_header(&self, summary: &TcpingSummary) -> String {
+        format!(
+            "TCPING {} ({}, {})  dns={:.1} ms{}\n",
+            summary.target(), summary.resolved.as_socket_str(), summary.resolved.family,
+            summary.dns_ms, if summary.tls { "  +tls" } else { "" },
+        )
+    }
+
+    pub fn render_tcping_attempt(&self, attempt: &TcpingAttempt) -> String {
+        let mut line = format!("  seq={:<4}", attempt.seq);
+        if let Some(connect_ms) = attempt.connect_ms {
+            line.push_str(&format!(" connect={:>8.1} ms", connect_ms));
+        }
+        if let Some(tls_ms) = attempt.tls_ms {
+            line.push_str(&format!("  tls={:>8.1} ms", tls_ms));
+        }
+        if let Some(ref error) = attempt.error {
+            line.push_str(&format!("  error: {}", error));
+        }
+        line.push('\n');
+        line
+    }
 }
 
 impl Renderer for PrettyRenderer {
@@ -19,22 +43,14 @@ impl Renderer for PrettyRenderer {
             report.http.status,
             report.http.reason.as_deref().unwrap_or(""),
             proto_ver,
-            report.resolved.ip,
+            report.resolved.address(),
             report.timings.total_ms,
             report.timings.ttfb_ms,
             tls_ver,
             report.bottleneck()
         ));
 
-        if report.was_downgrade {
-            out.push_str("⚠ WARNING: HTTPS→HTTP downgrade detected!\n");
-        }
-
-        if let Some(ref cert) = report.cert {
-            if cert.days_left < 14 {
-                out.push_str(&format!("⚠ CERT EXPIRING in {} days!\n", cert.days_left));
-            }
-        }
+        render_warnings(&mut out, &report.warnings);
 
         out.push('\n');
         out.push_str("URL\n");
@@ -45,6 +61,29 @@ impl Renderer for PrettyRenderer {
         if report.resolved.all_ips.len() > 1 {
             out.push_str(&format!("  ips:    {}\n", report.resolved.ips_short()));
         }
+        if let Some(local) = report.timings.hops.last().and_then(|h| h.local_addr) {
+            out.push_str(&format!("  local:  {}\n", local));
+        }
+        if let Some(pp) = report.timings.hops.iter().rev().find_map(|h| h.proxy_protocol.as_ref()) {
+            let addresses = match (pp.source, pp.destination) {
+                (Some(src), Some(dst)) => format!("{} → {}", src, dst),
+                _ => "UNKNOWN".to_string(),
+            };
+            out.push_str(&format!("  pp:     PROXY {} {}  ({} B)\n", pp.version, addresses, pp.bytes));
+        }
+
+        if let Some(ref proxy) = report.timings.proxy {
+            let handshake = if proxy.status.is_some() { "CONNECT" } else { "SOCKS5" };
+            out.push('\n');
+            out.push_str("PROXY\n");
+            out.push_str(&format!("  via:     {}  ({}:{})\n", proxy.url, proxy.ip, proxy.port));
+            out.push_str(&format!("  dns:     {:>8.1} ms\n", proxy.dns_ms));
+            out.push_str(&format!("  tcp:     {:>8.1} ms{}\n", proxy.tcp_ms, if proxy.tcp_deferred { "  (deferred: TFO)" } else { "" }));
+            if let Some(tls_ms) = proxy.tls_ms {
+                out.push_str(&format!("  tls:     {:>8.1} ms\n", tls_ms));
+            }
+            out.push_str(&format!("  {:<8} {:>8.1} ms  ({})\n", format!("{}:", handshake.to_ascii_lowercase()), proxy.connect_ms, proxy.reply));
+        }
 
         if !report.redirects.is_empty() {
             out.push('\n');
@@ -53,9 +92,17 @@ impl Renderer for PrettyRenderer {
                 out.push_str(&format!("  [{}] {} → {}\n", hop.status, shorten_url(&hop.from, 40), shorten_url(&hop.to, 40)));
                 if i < report.timings.hops.len() {
                     let ht = &report.timings.hops[i];
-                    out.push_str(&format!("      dns={:.1}ms tcp={:.1}ms", ht.dns_ms, ht.tcp_ms));
-                    if let Some(tls) = ht.tls_ms { out.push_str(&format!(" tls={:.1}ms", tls)); }
-                    out.push_str(&format!(" ttfb={:.1}ms\n", ht.ttfb_ms));
+                    if ht.reused {
+                        out.push_str("      reused");
+                    } else if let Some(ref proxy) = ht.proxy {
+                        out.push_str(&format!("      proxy={:.1}ms", proxy.total()));
+                        if let Some(tls) = ht.tls_ms { out.push_str(&format!(" tls={:.1}ms", tls)); }
+                    } else {
+                        out.push_str(&format!("      dns={:.1}ms tcp={:.1}ms{}", ht.dns_ms, ht.tcp_ms, if ht.tcp_deferred { "(TFO)" } else { "" }));
+                        if let Some(tls) = ht.tls_ms { out.push_str(&format!(" tls={:.1}ms", tls)); }
+                    }
+                    if let Some(local) = ht.local_addr { out.push_str(&format!(" local={}", local)); }
+                    out.push_str(&format!(" ttfb={:.1}ms\n", ht.exchange.ttfb_ms));
                 }
             }
         }
@@ -65,45 +112,455 @@ impl Renderer for PrettyRenderer {
         out.push_str(&format!("  status: {}\n", report.http.status_line()));
         out.push_str(&format!("  proto:  {}\n", report.http.proto));
         out.push_str(&format!("  ver:    {}\n", report.http.version));
+        if let Some(ref up) = report.upgrade {
+            let outcome = if up.switched { "switched" } else { "refused" };
+            out.push_str(&format!("  upgrade: {} {} ({})\n", up.protocol, outcome, up.status));
+        }
+        for alt in &report.alt_svc {
+            let ma = alt.max_age.map(|s| format!(" ma={}", s)).unwrap_or_default();
+            out.push_str(&format!("  alt-svc: {} {}:{}{}\n", alt.protocol, alt.host.as_deref().unwrap_or(""), alt.port, ma));
+        }
+
+        if let Some(ref ws) = report.websocket {
+            out.push('\n');
+            out.push_str("WEBSOCKET\n");
+            out.push_str(&format!("  via:      {}\n", ws.transport));
+            out.push_str(&format!("  result:   {} ({})\n", if ws.switched { "switched" } else { "refused" }, ws.status));
+            if let Some(valid) = ws.accept_valid {
+                out.push_str(&format!("  accept:   {}\n", if valid { "ok" } else { "MISMATCH" }));
+            }
+            let offered = if ws.offered_protocols.is_empty() { String::new() } else { format!("  (offered {})", ws.offered_protocols.join(",")) };
+            out.push_str(&format!("  protocol: {}{}\n", ws.subprotocol.as_deref().unwrap_or("none"), offered));
+            if !ws.extensions.is_empty() {
+                out.push_str(&format!("  ext:      {}\n", ws.extensions.join(", ")));
+            }
+            out.push_str(&format!("  upgrade:  {:.1} ms\n", ws.upgrade_ms));
+            match (ws.ping_rtt_ms, &ws.ping_error) {
+                (Some(rtt), _) => out.push_str(&format!("  ping:     {:.1} ms\n", rtt)),
+                (None, Some(error)) => out.push_str(&format!("  ping:     failed: {}\n", error)),
+                (None, None) => {}
+            }
+        }
+
+        if let Some(ref grpc) = report.grpc {
+            out.push('\n');
+            out.push_str("GRPC\n");
+            out.push_str(&format!("  service: {}\n", grpc.service_label()));
+            match grpc.grpc_status {
+                Some(code) => out.push_str(&format!("  status:  {} {} ({})\n", code, grpc.grpc_status_name.unwrap_or("UNRECOGNIZED"), grpc.status_source.map(|s| s.to_string()).unwrap_or_default())),
+                None => out.push_str("  status:  missing\n"),
+            }
+            if let Some(ref message) = grpc.grpc_message {
+                out.push_str(&format!("  message: {}\n", message));
+            }
+            match (grpc.serving_status, &grpc.decode_error) {
+                (Some(status), _) => out.push_str(&format!("  health:  {}\n", status)),
+                (None, Some(error)) => out.push_str(&format!("  health:  undecodable: {}\n", error)),
+                (None, None) => {}
+            }
+            if !grpc.is_grpc_response() {
+                out.push_str(&format!("  type:    {}\n", grpc.content_type.as_deref().unwrap_or("none")));
+            }
+        }
+
+        if let Some(ref h2) = report.h2 {
+            let s = &h2.server_settings;
+            let opt = |v: Option<u32>, default: &str| v.map(|v| v.to_string()).unwrap_or_else(|| format!("{} (default)", default));
+            out.push('\n');
+            out.push_str("H2\n");
+            out.push_str(&format!("  streams: max {}\n", opt(s.max_concurrent_streams, "unlimited")));
+            out.push_str(&format!("  window:  {}\n", opt(s.initial_window_size, "65535")));
+            out.push_str(&format!("  frame:   {}\n", opt(s.max_frame_size, "16384")));
+            out.push_str(&format!("  headers: {}\n", opt(s.max_header_list_size, "unlimited")));
+            out.push_str(&format!("  data:    {} frames, {}\n", h2.data_frames, format_bytes(h2.data_bytes as usize)));
+            if let Some(ref goaway) = h2.goaway {
+                out.push_str(&format!("  goaway:  {} (0x{:x}, last stream {})\n", goaway.name, goaway.code, goaway.stream_id));
+            }
+            for rst in &h2.rst_stream {
+                out.push_str(&format!("  rst:     stream {} {} (0x{:x})\n", rst.stream_id, rst.name, rst.code));
+            }
+        }
 
         out.push('\n');
         out.push_str("TIMINGS\n");
-        out.push_str(&format!("  dns:    {:>8.1} ms\n", report.timings.dns_ms));
-        out.push_str(&format!("  tcp:    {:>8.1} ms\n", report.timings.tcp_ms));
+        if let Some(proxy_ms) = report.timings.proxy_ms {
+            out.push_str(&format!("  proxy:  {:>8.1} ms\n", proxy_ms));
+        }
+        let tunneled = report.timings.quic_ms.is_some() || report.timings.proxy_ms.is_some();
+        let no_lookup = report.timings.proxy_ms.is_some() || report.resolved.unix_socket.is_some();
+        if !no_lookup || report.timings.dns_ms > 0.0 {
+            out.push_str(&format!("  dns:    {:>8.1} ms\n", report.timings.dns_ms));
+        }
+        if !tunneled || report.timings.tcp_ms > 0.0 {
+            let deferred = if report.timings.tcp_deferred { "  (deferred: TFO, handshake counted in tls/ttfb)" } else { "" };
+            out.push_str(&format!("  tcp:    {:>8.1} ms{}\n", report.timings.tcp_ms, deferred));
+        }
         if let Some(tls_ms) = report.timings.tls_ms {
             out.push_str(&format!("  tls:    {:>8.1} ms\n", tls_ms));
         }
+        if let Some(quic_ms) = report.timings.quic_ms {
+            out.push_str(&format!("  quic:   {:>8.1} ms\n", quic_ms));
+        }
+        out.push_str(&format!("  sent:   {:>8.1} ms\n", report.timings.request_sent_ms));
         out.push_str(&format!("  ttfb:   {:>8.1} ms\n", report.timings.ttfb_ms));
+        if let (Some(server), Some(network)) = (report.timings.server_ms, report.timings.network_ms) {
+            out.push_str(&format!("  server: {:>8.1} ms\n", server));
+            out.push_str(&format!("  network:{:>8.1} ms\n", network));
+        }
+        out.push_str(&format!("  headers:{:>8.1} ms\n", report.timings.headers_ms));
+        out.push_str(&format!("  body:   {:>8.1} ms\n", report.timings.body_ms));
         out.push_str(&format!("  total:  {:>8.1} ms\n", report.timings.total_ms));
 
-        if let Some(ref tls) = report.tls {
+        if let Some(ref tcp) = report.tcp {
+            let info = tcp.latest();
+            out.push('\n');
+            out.push_str("TCP\n");
+            out.push_str(&format!("  rtt:     {:.1} ms ±{:.1} ms  (min {:.1} ms", info.rtt_ms, info.rttvar_ms, info.min_rtt_ms));
+            if tcp.after_response.is_some() {
+                out.push_str(&format!(", {:.1} ms after connect", tcp.after_connect.rtt_ms));
+            }
+            out.push_str(")\n");
+            out.push_str(&format!("  retrans: {}", info.retransmits));
+            if info.lost > 0 { out.push_str(&format!("  ({} segments lost)", info.lost)); }
+            out.push('\n');
+            out.push_str(&format!("  cwnd:    {} segments  (mss {} B, pmtu {} B)\n", info.cwnd, info.mss, info.pmtu));
+            out.push_str(&format!("  rate:    {:.1} Mbit/s\n", tcp.delivery_rate_mbps()));
+            let opts = &tcp.options;
+            let on_off = |on: bool| if on { "on" } else { "off" };
+            let buffer = |size: Option<u32>| size.map(|s| format_bytes(s as usize)).unwrap_or_else(|| "-".to_string());
+            out.push_str(&format!(
+                "  opts:    nodelay={} keepalive={} sndbuf={} rcvbuf={}\n",
+                on_off(opts.nodelay), on_off(opts.keepalive), buffer(opts.send_buffer), buffer(opts.recv_buffer),
+            ));
+            match (tcp.fastopen_used(), tcp.fastopen_failure()) {
+                (Some(true), _) => out.push_str("  tfo:     used (cookie accepted, request data carried in the SYN)\n"),
+                (Some(false), Some(failure)) => out.push_str(&format!("  tfo:     requested, not used ({})\n", failure)),
+                (Some(false), None) => out.push_str("  tfo:     requested, not used (no data in the SYN)\n"),
+                (None, _) => {}
+            }
+        }
+
+        if let Some(ref enc) = report.encoding {
+            out.push('\n');
+            out.push_str("ENCODING\n");
+            out.push_str(&format!("  coding:  {}\n", enc.encoding));
+            out.push_str(&format!("  wire:    {}{}\n", format_bytes(enc.wire_bytes), if enc.truncated { " (preview limit)" } else { "" }));
+            let limit = if enc.decode_limit_hit { " (decode limit)" } else { "" };
+            match (&enc.error, enc.ratio, enc.savings_percent()) {
+                (Some(err), _, _) => out.push_str(&format!("  decoded: FAILED ({})\n", err)),
+                (None, Some(ratio), Some(saved)) => out.push_str(&format!("  decoded: {}{}  (ratio {:.2}x, saved {:.0}%)\n", format_bytes(enc.decoded_bytes), limit, ratio, saved)),
+                (None, _, _) => out.push_str(&format!("  decoded: {}{}\n", format_bytes(enc.decoded_bytes), limit)),
+            }
+        }
+
+        if !report.encoding_probe.is_empty() {
+            out.push('\n');
+            out.push_str("ENCODINGS\n");
+            for probe in &report.encoding_probe {
+                out.push_str(&format!(
+                    "  {:<5} {:<13} [{}] ce={:<8} {:>10}  ttfb={:.1}ms\n",
+                    probe.offered,
+                    if probe.supported { "supported" } else { "not supported" },
+                    probe.status,
+                    probe.content_encoding.as_deref().unwrap_or("-"),
+                    format_bytes(probe.wire_bytes),
+                    probe.ttfb_ms
+                ));
+            }
+        }
+
+        if let Some(ref body) = report.body {
+            out.push('\n');
+            out.push_str("BODY\n");
+            let limit = match report.encoding {
+                Some(ref enc) if enc.decode_limit_hit => " (decode limit)",
+                _ if body.truncated => " (preview limit)",
+                _ => "",
+            };
+            out.push_str(&format!("  length:  {}{}\n", format_bytes(body.length), limit));
+            match body.content_type {
+                Some(ref ct) => out.push_str(&format!("  type:    {}  ({})\n", body.kind, ct)),
+                None => out.push_str(&format!("  type:    {}\n", body.kind)),
+            }
+            match body.sha256 {
+                Some(ref digest) => out.push_str(&format!("  sha256:  {}\n", digest)),
+                None => out.push_str("  sha256:  -  (partial body, use -o for the full hash)\n"),
+            }
+            if let Some(ref path) = body.saved_to { out.push_str(&format!("  saved:   {}\n", path)); }
+            if let Some(ref json) = body.json {
+                out.push_str("  json:\n");
+                for line in serde_json::to_string_pretty(json).unwrap_or_default().lines() {
+                    out.push_str(&format!("    {}\n", line));
+                }
+            } else if let Some(ref preview) = body.preview {
+                out.push_str("  preview:\n");
+                for line in preview.lines() {
+                    out.push_str(&format!("    | {}\n", line));
+                }
+            }
+        }
+
+        if !report.timings.server_timing.is_empty() {
+            out.push('\n');
+            out.push_str("SERVER-TIMING\n");
+            let width = report.timings.server_timing.iter().map(|m| m.name.len()).max().unwrap_or(0);
+            for m in &report.timings.server_timing {
+                let dur = m.dur_ms.map(|d| format!("{:>8.1} ms", d)).unwrap_or_else(|| format!("{:>11}", "-"));
+                match m.desc {
+                    Some(ref desc) => out.push_str(&format!("  {:<width$} {}  {}\n", m.name, dur, desc, width = width)),
+                    None => out.push_str(&format!("  {:<width$} {}\n", m.name, dur, width = width)),
+                }
+            }
+        }
+
+        if !report.cache.is_empty() {
+            render_cache(&mut out, &report.cache);
+        }
+
+        if !report.security.is_empty() {
+            out.push('\n');
+            out.push_str("SECURITY\n");
+            let width = report.security.iter().map(|f| f.check.len()).max().unwrap_or(0) + 1;
+            for f in &report.security {
+                let label = if f.passed { "ok".to_string() } else { f.severity.to_string() };
+                out.push_str(&format!("  {:<4} {:<width$} {}\n", label, format!("{}:", f.check), f.message, width = width));
+            }
+        }
+
+        if let Some(ref probe) = report.connection {
+            out.push('\n');
+            out.push_str(&format!("CONNECTION ({} of {} requests)\n", probe.requests.len(), probe.requested));
+            for r in &probe.requests {
+                let how = if r.reused { "reused".to_string() } else { format!("new connection (setup {:.1} ms)", r.setup_ms) };
+                out.push_str(&format!("  #{:<3} [{}] ttfb={:>8.1} ms  {}\n", r.index, r.status, r.ttfb_ms, how));
+            }
+            if let Some(cold) = probe.cold_ms() {
+                out.push_str(&format!("  cold:   {:>8.1} ms  (setup + ttfb of #1)\n", cold));
+            }
+            if let Some(warm) = probe.warm_ttfb_ms() {
+                out.push_str(&format!("  warm:   {:>8.1} ms  (avg ttfb on reused connection)\n", warm));
+            }
+            if let Some(n) = probe.closed_after {
+                out.push_str(&format!("  closed: server closed the connection after request #{}\n", n));
+            }
+        }
+
+        if let Some(ref mux) = report.multiplex {
             out.push('\n');
-            out.push_str("TLS\n");
-            out.push_str(&format!("  version: {}\n", tls.version));
-            if let Some(ref alpn) = tls.alpn { out.push_str(&format!("  alpn:    {}\n", alpn)); }
-            out.push_str(&format!("  cipher:  {}\n", tls.cipher));
-            out.push_str(&format!("  chain:   {} certs\n", tls.chain_len));
-            out.push_str(&format!("  verify:  {}\n", if tls.verified { "ok" } else { "FAILED" }));
+            out.push_str(&format!("MULTIPLEX ({} streams, {} in flight)\n", mux.requested, mux.in_flight));
+            for s in &mux.streams {
+                let result = match (s.status, &s.error) {
+                    (_, Some(e)) => format!("error: {}", e),
+                    (Some(status), None) => format!("[{}] {} bytes", status, s.bytes),
+                    (None, None) => "no response".to_string(),
+                };
+                let ttfb = s.ttfb_ms.map(|t| format!("{:>8.1} ms", t)).unwrap_or_else(|| format!("{:>11}", "-"));
+                let done = s.done_ms.map(|t| format!("{:>8.1} ms", t)).unwrap_or_else(|| format!("{:>11}", "-"));
+                out.push_str(&format!("  #{:<3} start={:>6.1} ms  ttfb={}  done={}  {}  {}\n", s.index, s.started_ms, ttfb, done, s.path, result));
+            }
+            let max = match mux.max_concurrent_streams {
+                Some(m) if mux.limited_by_server() => format!("{} (limited by server)", m),
+                Some(m) => m.to_string(),
+                None => "unlimited (not advertised)".to_string(),
+            };
+            out.push_str(&format!("  max streams: {}\n", max));
+            out.push_str(&format!("  completed:   {}/{} in {:.1} ms\n", mux.completed(), mux.requested, mux.total_ms));
+            if let Some(spread) = mux.ttfb_spread_ms() {
+                out.push_str(&format!("  ttfb spread: {:.1} ms\n", spread));
+            }
+            if mux.head_of_line_blocked() {
+                out.push_str("  hol:         responses were served one at a time\n");
+            }
+            for rst in &mux.rst_stream {
+                out.push_str(&format!("  rst:         stream {} {} (0x{:x})\n", rst.stream_id, rst.name, rst.code));
+            }
+            if let Some(ref goaway) = mux.goaway {
+                out.push_str(&format!("  goaway:      {} (0x{:x}, last stream {})\n", goaway.name, goaway.code, goaway.stream_id));
+            }
+        }
+
+        if let Some(ref quic) = report.quic {
+            out.push('\n');
+            out.push_str("QUIC\n");
+            let early = match quic.early_data_accepted {
+                Some(true) => " (early data accepted)",
+                Some(false) => " (early data rejected)",
+                None => "",
+            };
+            out.push_str(&format!("  handshake: {}{}\n", quic.mode(), early));
+            out.push_str(&format!("  time:      {:.1} ms\n", quic.handshake_ms));
+            out.push_str(&format!("  via:       {}\n", if quic.via_alt_svc { "alt-svc" } else { "--http3" }));
+        }
+
+        if let Some(ref tls) = report.tls {
+            render_tls(&mut out, tls);
         }
 
         if let Some(ref cert) = report.cert {
+            render_cert(&mut out, cert);
+        }
+
+        if !report.assertions.is_empty() {
+            let passed = report.assertions.len() - report.assertions_failed();
             out.push('\n');
-            out.push_str("CERT\n");
-            if let Some(ref cn) = cert.subject_cn { out.push_str(&format!("  subject: CN={}\n", cn)); }
-            out.push_str(&format!("  issuer:  {}\n", cert.issuer));
-            if !cert.san_short.is_empty() { out.push_str(&format!("  san:     {}\n", cert.san_short)); }
-            out.push_str(&format!("  valid:   {}\n", cert.validity_range()));
-            out.push_str(&format!("  sha256:  {}\n", cert.short_fingerprint()));
+            out.push_str(&format!("ASSERTIONS ({}/{} passed)\n", passed, report.assertions.len()));
+            let width = report.assertions.iter().map(|a| a.assertion.len()).max().unwrap_or(0);
+            for a in &report.assertions {
+                out.push_str(&format!(
+                    "  {} {:<width$}  actual: {}\n",
+                    if a.passed { "PASS" } else { "FAIL" },
+                    a.assertion,
+                    a.actual,
+                    width = width
+                ));
+            }
+        }
+
+        out
+    }
+
+    fn render_tcping(&self, summary: &TcpingSummary) -> String {
+        let mut out = format!("\n--- {} tcping statistics ---\n", summary.target());
+        out.push_str(&format!("  {} attempts, {} ok, {:.1}% loss\n", summary.sent(), summary.received(), summary.loss_pct()));
+        if let Some(stats) = summary.connect_stats() {
+            out.push_str(&render_latency("connect:", &stats));
+        }
+        if let Some(stats) = summary.tls_stats() {
+            out.push_str(&render_latency("tls:", &stats));
+        }
+        out
+    }
+
+    fn render_tls_probe(&self, report: &TlsProbeReport) -> String {
+        let mut out = format!(
+            "{}  {}  ip={}  total={:.1}ms  alpn={}  verify={}\n",
+            report.tls.version,
+            report.target(),
+            report.resolved.address(),
+            report.total_ms,
+            report.tls.alpn.as_deref().unwrap_or("-"),
+            if report.tls.verified { "ok" } else { "FAILED" },
+        );
+
+        render_warnings(&mut out, &report.warnings);
+
+        out.push('\n');
+        out.push_str("TARGET\n");
+        out.push_str(&format!("  host:   {}\n", report.host));
+        out.push_str(&format!("  ip:     {}   ({})\n", report.resolved.as_socket_str(), report.resolved.family));
+        if report.resolved.all_ips.len() > 1 {
+            out.push_str(&format!("  ips:    {}\n", report.resolved.ips_short()));
+        }
+        if let Some(ref starttls) = report.starttls {
+            out.push_str(&format!("  proto:  {} (STARTTLS)\n", starttls.protocol));
+            if let Some(ref greeting) = starttls.greeting {
+                out.push_str(&format!("  banner: {}\n", greeting));
+            }
+            out.push_str(&format!("  reply:  {}\n", starttls.reply));
+        }
+
+        out.push('\n');
+        out.push_str("TIMINGS\n");
+        out.push_str(&format!("  dns:    {:>8.1} ms\n", report.dns_ms));
+        out.push_str(&format!("  tcp:    {:>8.1} ms\n", report.tcp_ms));
+        if let Some(starttls_ms) = report.starttls_ms() {
+            out.push_str(&format!("  starttls: {:>6.1} ms\n", starttls_ms));
         }
+        out.push_str(&format!("  tls:    {:>8.1} ms\n", report.tls_ms));
+        out.push_str(&format!("  total:  {:>8.1} ms\n", report.total_ms));
 
+        render_tls(&mut out, &report.tls);
+        if let Some(ref cert) = report.cert {
+            render_cert(&mut out, cert);
+        }
         out
     }
 }
 
+fn render_warnings(out: &mut String, warnings: &[Warning]) {
+    if warnings.is_empty() { return; }
+    out.push('\n');
+    out.push_str(&format!("WARNINGS ({})\n", warnings.len()));
+    for w in warnings {
+        out.push_str(&format!("  ⚠ [{}] {}: {}\n", w.severity, w.code, w.message));
+    }
+}
+
+fn render_tls(out: &mut String, tls: &TlsSummary) {
+    out.push('\n');
+    out.push_str("TLS\n");
+    out.push_str(&format!("  version: {}\n", tls.version));
+    match tls.alpn {
+        Some(ref alpn) => out.push_str(&format!("  alpn:    {}\n", alpn)),
+        None if tls.alpn_missing() => out.push_str(&format!("  alpn:    none (offered {})\n", tls.alpn_offered.join(","))),
+        None => out.push_str("  alpn:    not offered\n"),
+    }
+    out.push_str(&format!("  cipher:  {}\n", tls.cipher.as_deref().unwrap_or("-")));
+    out.push_str(&format!("  chain:   {} certs\n", tls.chain_len));
+    out.push_str(&format!("  verify:  {}\n", if tls.verified { "ok" } else { "FAILED" }));
+}
+
+fn render_cert(out: &mut String, cert: &CertSummary) {
+    out.push('\n');
+    out.push_str("CERT\n");
+    if let Some(ref cn) = cert.subject_cn { out.push_str(&format!("  subject: CN={}\n", cn)); }
+    out.push_str(&format!("  issuer:  {}\n", cert.issuer));
+    if !cert.san_short.is_empty() { out.push_str(&format!("  san:     {}\n", cert.san_short)); }
+    out.push_str(&format!("  valid:   {}\n", cert.validity_range()));
+    out.push_str(&format!("  sha256:  {}\n", cert.short_fingerprint()));
+}
+
+fn render_cache(out: &mut String, cache: &CacheSummary) {
+    out.push('\n');
+    out.push_str("CACHE\n");
+    match (&cache.cdn, &cache.pop) {
+        (Some(cdn), Some(pop)) => out.push_str(&format!("  cdn:     {} (pop {})\n", cdn, pop)),
+        (Some(cdn), None) => out.push_str(&format!("  cdn:     {}\n", cdn)),
+        (None, Some(pop)) => out.push_str(&format!("  edge:    {}\n", pop)),
+        (None, None) => {}
+    }
+    if let (Some(status), Some(source)) = (&cache.status, &cache.status_source) {
+        out.push_str(&format!("  status:  {} ({})\n", status, source));
+    }
+    out.push_str(&format!("  control: {}\n", cache.cache_control.as_deref().unwrap_or("-")));
+    out.push_str(&format!("  store:   {}\n", if cache.cacheable { "cacheable" } else { "not cacheable" }));
+    if let Some(age) = cache.age_s { out.push_str(&format!("  age:     {}s\n", age)); }
+    if let (Some(fresh), Some(source)) = (cache.freshness_lifetime_s, &cache.freshness_source) {
+        out.push_str(&format!("  fresh:   {}s ({})\n", fresh, source));
+    }
+    if let Some(ttl) = cache.ttl_s { out.push_str(&format!("  ttl:     {}s\n", ttl)); }
+    if let Some(ref etag) = cache.etag { out.push_str(&format!("  etag:    {}\n", etag)); }
+    if let Some(ref lm) = cache.last_modified { out.push_str(&format!("  lastmod: {}\n", lm)); }
+    if let Some(ref vary) = cache.vary { out.push_str(&format!("  vary:    {}\n", vary)); }
+    if let Some(ref via) = cache.via { out.push_str(&format!("  via:     {}\n", via)); }
+    if let Some(ref r) = cache.revalidation {
+        out.push_str(&format!(
+            "  reval:   [{}] {} via {}  body={:.1}ms vs {:.1}ms ({:+.1}ms)\n",
+            r.status,
+            if r.not_modified { "not modified" } else { "full response" },
+            r.validator,
+            r.body_ms,
+            r.original_body_ms,
+            -r.saved_ms()
+        ));
+    }
+}
+
+fn render_latency(label: &str, stats: &LatencyStats) -> String {
+    format!("  {:<8} min/avg/max/p95 = {:.1}/{:.1}/{:.1}/{:.1} ms\n", label, stats.min_ms, stats.avg_ms, stats.max_ms, stats.p95_ms)
+}
+
+fn format_bytes(n: usize) -> String {
+    if n < 1024 { format!("{} B", n) }
+    else if n < 1024 * 1024 { format!("{:.1} KiB", n as f64 / 1024.0) }
+    else { format!("{:.1} MiB", n as f64 / (1024.0 * 1024.0)) }
+}
+
 fn shorten_url(url: &str, max: usize) -> String {
     if url.len() <= max { url.to_string() } else { format!("{}...", &url[..max.saturating_sub(3)]) }
 }
 
+#[derive(Default)]
 pub struct JsonRenderer;
 
 impl JsonRenderer {
@@ -114,7 +571,18 @@ impl JsonRenderer {
 struct JsonReport<'a> {
     #[serde(flatten)]
     report: &'a Report,
-    bottleneck: &'static str,
+    bottleneck: String,
+}
+
+#[derive(Serialize)]
+struct JsonTcping<'a> {
+    #[serde(flatten)]
+    summary: &'a TcpingSummary,
+    sent: usize,
+    received: usize,
+    loss_pct: f64,
+    connect: Option<LatencyStats>,
+    tls_handshake: Option<LatencyStats>,
 }
 
 impl Renderer for JsonRenderer {
@@ -125,4 +593,20 @@ impl Renderer for JsonRenderer {
         };
         serde_json::to_string_pretty(&json_report).unwrap_or_else(|_| "{}".to_string())
     }
+
+    fn render_tcping(&self, summary: &TcpingSummary) -> String {
+        let json_summary = JsonTcping {
+            summary,
+            sent: summary.sent(),
+            received: summary.received(),
+            loss_pct: summary.loss_pct(),
+            connect: summary.connect_stats(),
+            tls_handshake: summary.tls_stats(),
+        };
+        serde_json::to_string_pretty(&json_summary).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    fn render_tls_probe(&self, report: &TlsProbeReport) -> String {
+        serde_json::to_string_pretty(report).unwrap_or_else(|_| "{}".to_string())
+    }
 }
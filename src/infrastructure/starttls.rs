@@ -0,0 +1,347 @@
+use std::time::Instant;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use crate::domain::{StartTlsOutcome, StartTlsProtocol, UdocError};
+use crate::ports::{BoxedIoStream, StartTlsNegotiator};
+use super::http::elapsed_ms;
+
+const LINE_LIMIT: usize = 8 * 1024;
+const XMPP_LIMIT: usize = 64 * 1024;
+const POSTGRES_SSL_REQUEST: [u8; 8] = [0x00, 0x00, 0x00, 0x08, 0x04, 0xD2, 0x16, 0x2F];
+const LDAP_STARTTLS_OID: &[u8] = b"1.3.6.1.4.1.1466.20037";
+const LDAP_LIMIT: usize = 64 * 1024;
+
+#[derive(Default)]
+pub struct PlaintextStartTls;
+
+impl PlaintextStartTls {
+    pub fn new() -> Self { Self }
+}
+
+impl StartTlsNegotiator for PlaintextStartTls {
+    async fn negotiate(&self, stream: &mut BoxedIoStream, protocol: StartTlsProtocol, host: &str) -> Result<StartTlsOutcome, UdocError> {
+        let start = Instant::now();
+        let mut conn = Plaintext { stream, protocol };
+        let (greeting, reply) = match protocol {
+            StartTlsProtocol::Smtp => conn.smtp().await?,
+            StartTlsProtocol::Imap => conn.imap().await?,
+            StartTlsProtocol::Pop3 => conn.pop3().await?,
+            StartTlsProtocol::Ftp => conn.ftp().await?,
+            StartTlsProtocol::Xmpp => conn.xmpp(host).await?,
+            StartTlsProtocol::Postgres => conn.postgres().await?,
+            StartTlsProtocol::Ldap => conn.ldap().await?,
+        };
+        Ok(StartTlsOutcome { protocol, greeting, reply, negotiate_ms: elapsed_ms(start) })
+    }
+}
+
+type Negotiated = (Option<String>, String);
+
+struct Plaintext<'a> {
+    stream: &'a mut BoxedIoStream,
+    protocol: StartTlsProtocol,
+}
+
+impl Plaintext<'_> {
+    async fn smtp(&mut self) -> Result<Negotiated, UdocError> {
+        let greeting = self.expect_reply(220).await?;
+        self.send("EHLO udoc.invalid").await?;
+        let (code, lines) = self.read_reply().await?;
+        if code != 250 {
+            return Err(self.refused(&lines.join(" ")));
+        }
+        if !lines.iter().any(|l| l.get(4..).is_some_and(|ext| ext.trim().eq_ignore_ascii_case("STARTTLS"))) {
+            return Err(UdocError::tls("SMTP server does not advertise STARTTLS in its EHLO reply"));
+        }
+        self.send("STARTTLS").await?;
+        let reply = self.expect_reply(220).await?;
+        Ok((Some(greeting), reply))
+    }
+
+    async fn imap(&mut self) -> Result<Negotiated, UdocError> {
+        let greeting = self.read_line().await?;
+        if !(greeting.starts_with("* OK") || greeting.starts_with("* PREAUTH")) {
+            return Err(self.refused(&greeting));
+        }
+        self.send("a1 STARTTLS").await?;
+        loop {
+            let line = self.read_line().await?;
+            match line.strip_prefix("a1 ") {
+                Some(status) if status.starts_with("OK") => return Ok((Some(greeting), line)),
+                Some(_) => return Err(self.refused(&line)),
+                None => continue,
+            }
+        }
+    }
+
+    async fn pop3(&mut self) -> Result<Negotiated, UdocError> {
+        let greeting = self.read_line().await?;
+        if !greeting.starts_with("+OK") {
+            return Err(self.refused(&greeting));
+        }
+        self.send("STLS").await?;
+        let reply = self.read_line().await?;
+        if !reply.starts_with("+OK") {
+            return Err(self.refused(&reply));
+        }
+        Ok((Some(greeting), reply))
+    }
+
+    async fn ftp(&mut self) -> Result<Negotiated, UdocError> {
+        let greeting = self.expect_reply(220).await?;
+        self.send("AUTH TLS").await?;
+        let reply = self.expect_reply(234).await?;
+        Ok((Some(greeting), reply))
+    }
+
+    async fn xmpp(&mut self, host: &str) -> Result<Negotiated, UdocError> {
+        let open = format!(
+            "<?xml version='1.0'?><stream:stream to='{}' xmlns='jabber:client' xmlns:stream='http://etherx.jabber.org/streams' version='1.0'>",
+            xml_escape(host),
+        );
+        self.write(open.as_bytes()).await?;
+        let features = self.read_until(|buf| buf.contains("</stream:features>") || buf.contains("<stream:features/>")).await?;
+        if !features.contains("<starttls") {
+            return Err(UdocError::tls("XMPP server does not offer STARTTLS in its stream features"));
+        }
+        self.write(b"<starttls xmlns='urn:ietf:params:xml:ns:xmpp-tls'/>").await?;
+        let reply = self.read_until(|buf| (buf.contains("<proceed") || buf.contains("<failure")) && buf.ends_with('>')).await?;
+        if reply.contains("<failure") {
+            return Err(self.refused(reply.trim()));
+        }
+        Ok((None, "proceed".to_string()))
+    }
+
+    async fn postgres(&mut self) -> Result<Negotiated, UdocError> {
+        self.write(&POSTGRES_SSL_REQUEST).await?;
+        let mut answer = [0u8; 1];
+        self.stream.read_exact(&mut answer).await.map_err(|e| self.io_error(e))?;
+        match answer[0] {
+            b'S' => Ok((None, "S (SSLRequest accepted)".to_string())),
+            b'N' => Err(UdocError::tls("Postgres server does not accept SSL connections")),
+            other => Err(UdocError::tls(format!("unexpected Postgres SSLRequest answer 0x{:02x}", other))),
+        }
+    }
+
+    async fn ldap(&mut self) -> Result<Negotiated, UdocError> {
+        let mut request = vec![0x30, 0x1d, 0x02, 0x01, 0x01, 0x77, 0x18, 0x80, LDAP_STARTTLS_OID.len() as u8];
+        request.extend_from_slice(LDAP_STARTTLS_OID);
+        self.write(&request).await?;
+
+        let mut header = [0u8; 2];
+        self.stream.read_exact(&mut header).await.map_err(|e| self.io_error(e))?;
+        if header[0] != 0x30 {
+            return Err(UdocError::tls(format!("malformed LDAP response: expected a SEQUENCE, got tag 0x{:02x}", header[0])));
+        }
+        let len = match header[1] {
+            short @ 0..=0x7f => short as usize,
+            long @ 0x81..=0x84 => {
+                let mut bytes = vec![0u8; (long & 0x7f) as usize];
+                self.stream.read_exact(&mut bytes).await.map_err(|e| self.io_error(e))?;
+                bytes.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize)
+            }
+            other => return Err(UdocError::tls(format!("malformed LDAP response: unsupported length byte 0x{:02x}", other))),
+        };
+        if len > LDAP_LIMIT {
+            return Err(UdocError::tls("LDAP server sent an oversized response"));
+        }
+        let mut message = vec![0u8; len];
+        self.stream.read_exact(&mut message).await.map_err(|e| self.io_error(e))?;
+
+        let (code, diagnostic) = parse_ldap_extended_response(&message)
+            .ok_or_else(|| UdocError::tls("malformed LDAP response: expected an ExtendedResponse"))?;
+        if code != 0 {
+            let detail = if diagnostic.is_empty() { String::new() } else { format!(": {}", diagnostic) };
+            return Err(self.refused(&format!("resultCode {} ({}){}", code, ldap_result_name(code), detail)));
+        }
+        Ok((None, "resultCode 0 (success)".to_string()))
+    }
+
+    async fn expect_reply(&mut self, expected: u16) -> Result<String, UdocError> {
+        let (code, lines) = self.read_reply().await?;
+        let text = lines.first().cloned().unwrap_or_default();
+        if code != expected {
+            return Err(self.refused(&text));
+        }
+        Ok(text)
+    }
+
+    async fn read_reply(&mut self) -> Result<(u16, Vec<String>), UdocError> {
+        let mut lines = Vec::new();
+        loop {
+            let line = self.read_line().await?;
+            let code = line.get(..3).and_then(|c| c.parse::<u16>().ok())
+                .ok_or_else(|| UdocError::tls(format!("malformed {} reply: {}", self.protocol, line)))?;
+            let more = line.as_bytes().get(3) == Some(&b'-');
+            lines.push(line);
+            if !more {
+                return Ok((code, lines));
+            }
+        }
+    }
+
+    async fn read_line(&mut self) -> Result<String, UdocError> {
+        let mut line = Vec::with_capacity(128);
+        let mut byte = [0u8; 1];
+        loop {
+            let n = self.stream.read(&mut byte).await.map_err(|e| self.io_error(e))?;
+            if n == 0 {
+                return Err(UdocError::tcp(format!("{} server closed the connection before STARTTLS", self.protocol)));
+            }
+            if byte[0] == b'\n' { break; }
+            if line.len() >= LINE_LIMIT {
+                return Err(UdocError::tls(format!("{} server sent an oversized line", self.protocol)));
+            }
+            line.push(byte[0]);
+        }
+        Ok(String::from_utf8_lossy(&line).trim_end_matches('\r').to_string())
+    }
+
+    async fn read_until(&mut self, done: impl Fn(&str) -> bool) -> Result<String, UdocError> {
+        let mut buffer = Vec::with_capacity(1024);
+        let mut byte = [0u8; 1];
+        loop {
+            let n = self.stream.read(&mut byte).await.map_err(|e| self.io_error(e))?;
+            if n == 0 {
+                return Err(UdocError::tcp(format!("{} server closed the connection before STARTTLS", self.protocol)));
+            }
+            buffer.push(byte[0]);
+            if byte[0] == b'>' && done(&String::from_utf8_lossy(&buffer)) { break; }
+            if buffer.len() >= XMPP_LIMIT {
+                return Err(UdocError::tls(format!("{} server sent an oversized response", self.protocol)));
+            }
+        }
+        Ok(String::from_utf8_lossy(&buffer).to_string())
+    }
+
+    async fn send(&mut self, command: &str) -> Result<(), UdocError> {
+        self.write(format!("{}\r\n", command).as_bytes()).await
+    }
+
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), UdocError> {
+        self.stream.write_all(bytes).await.map_err(|e| self.io_error(e))?;
+        self.stream.flush().await.map_err(|e| self.io_error(e))
+    }
+
+    fn refused(&self, reply: &str) -> UdocError {
+        UdocError::tls(format!("{} server refused STARTTLS: {}", self.protocol, reply))
+    }
+
+    fn io_error(&self, e: std::io::Error) -> UdocError {
+        UdocError::tcp(format!("{} STARTTLS negotiation failed: {}", self.protocol, e))
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('\'', "&apos;").replace('"', "&quot;")
+}
+
+fn ber_element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = match first {
+        0..=0x7f => (first as usize, rest),
+        0x81..=0x84 => {
+            let (bytes, rest) = rest.split_at_checked((first & 0x7f) as usize)?;
+            (bytes.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize), rest)
+        }
+        _ => return None,
+    };
+    let (content, rest) = rest.split_at_checked(len)?;
+    Some((tag, content, rest))
+}
+
+fn parse_ldap_extended_response(message: &[u8]) -> Option<(u32, String)> {
+    let (0x02, _, rest) = ber_element(message)? else { return None };
+    let (0x78, response, _) = ber_element(rest)? else { return None };
+    let (0x0a, code, rest) = ber_element(response)? else { return None };
+    let code = code.iter().fold(0u32, |acc, &b| (acc << 8) | b as u32);
+    let (_, _, rest) = ber_element(rest)?;
+    let diagnostic = ber_element(rest).map(|(_, text, _)| String::from_utf8_lossy(text).to_string()).unwrap_or_default();
+    Some((code, diagnostic))
+}
+
+fn ldap_result_name(code: u32) -> &'static str {
+    match code {
+        1 => "operationsError",
+        2 => "protocolError",
+        12 => "unavailableCriticalExtension",
+        51 => "busy",
+        52 => "unavailable",
+        53 => "unwillingToPerform",
+        80 => "other",
+        _ => "error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+    use super::*;
+
+    fn ldap_response(code: u8, diagnostic: &str) -> Vec<u8> {
+        let mut response = vec![0x0a, 0x01, code, 0x04, 0x00, 0x04, diagnostic.len() as u8];
+        response.extend_from_slice(diagnostic.as_bytes());
+        let mut op = vec![0x78, response.len() as u8];
+        op.extend(response);
+        let mut message = vec![0x30, (op.len() + 3) as u8, 0x02, 0x01, 0x01];
+        message.extend(op);
+        message
+    }
+
+    async fn negotiate(protocol: StartTlsProtocol, host: &str, server: impl FnOnce(DuplexStream) -> tokio::task::JoinHandle<Vec<u8>>) -> (Result<StartTlsOutcome, UdocError>, Vec<u8>) {
+        let (client, remote) = tokio::io::duplex(4096);
+        let task = server(remote);
+        let mut stream = BoxedIoStream(Box::new(client));
+        let result = PlaintextStartTls::new().negotiate(&mut stream, protocol, host).await;
+        drop(stream);
+        (result, task.await.unwrap())
+    }
+
+    fn ldap_server(response: Vec<u8>) -> impl FnOnce(DuplexStream) -> tokio::task::JoinHandle<Vec<u8>> {
+        move |mut remote| tokio::spawn(async move {
+            let mut request = vec![0u8; 31];
+            remote.read_exact(&mut request).await.unwrap();
+            remote.write_all(&response).await.unwrap();
+            request
+        })
+    }
+
+    #[tokio::test]
+    async fn ldap_sends_extended_request_and_accepts_success() {
+        let (result, request) = negotiate(StartTlsProtocol::Ldap, "ldap.test", ldap_server(ldap_response(0, ""))).await;
+        assert_eq!(&request[..9], &[0x30, 0x1d, 0x02, 0x01, 0x01, 0x77, 0x18, 0x80, 0x16]);
+        assert_eq!(&request[9..], LDAP_STARTTLS_OID);
+        assert_eq!(result.unwrap().reply, "resultCode 0 (success)");
+    }
+
+    #[tokio::test]
+    async fn ldap_refusal_is_a_tls_error() {
+        let (result, _) = negotiate(StartTlsProtocol::Ldap, "ldap.test", ldap_server(ldap_response(53, "no TLS here"))).await;
+        let error = result.unwrap_err();
+        assert_eq!(error.class, crate::domain::ErrorClass::Tls);
+        assert!(error.message.contains("resultCode 53 (unwillingToPerform): no TLS here"), "{}", error.message);
+    }
+
+    #[tokio::test]
+    async fn xmpp_escapes_the_host() {
+        let (_, sent) = negotiate(StartTlsProtocol::Xmpp, "a'b<c&", |mut remote| tokio::spawn(async move {
+            let mut sent = Vec::new();
+            let mut byte = [0u8; 1];
+            while !sent.ends_with(b"version='1.0'>") {
+                remote.read_exact(&mut byte).await.unwrap();
+                sent.push(byte[0]);
+            }
+            sent
+        })).await;
+        let sent = String::from_utf8(sent).unwrap();
+        assert!(sent.contains("to='a&apos;b&lt;c&amp;'"), "{}", sent);
+    }
+
+    #[test]
+    fn parses_long_form_ber_lengths() {
+        let mut message = vec![0x02, 0x01, 0x01, 0x78, 0x81, 0x08, 0x0a, 0x01, 0x02, 0x04, 0x00, 0x04, 0x01, b'x'];
+        message.truncate(14);
+        assert_eq!(parse_ldap_extended_response(&message), Some((2, "x".to_string())));
+        assert_eq!(parse_ldap_extended_response(&message[..8]), None);
+    }
+}
@@ -1,16 +1,26 @@
 use std::net::{IpAddr, SocketAddr};
+use std::os::fd::RawFd;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use tokio::net::TcpStream;
+use tokio::net::{TcpSocket, TcpStream};
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
-use crate::domain::UdocError;
-use crate::ports::{TcpDialer, TcpConnection, IoStream, BoxedIoStream};
+use crate::domain::{FastOpenFailure, TcpInfo, TcpOptions, TcpStats, UdocError};
+use crate::ports::{TcpDialer, TcpConnection, IoStream, BoxedIoStream, TcpInfoProbe};
 
-struct TokioTcpStream(TcpStream);
+struct TokioTcpStream(TcpStream, Option<Arc<SocketTcpInfo>>);
 
 impl IoStream for TokioTcpStream {}
 
+impl Drop for TokioTcpStream {
+    fn drop(&mut self) {
+        if let Some(ref probe) = self.1 {
+            probe.close();
+        }
+    }
+}
+
 impl AsyncRead for TokioTcpStream {
     fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
         Pin::new(&mut self.0).poll_read(cx, buf)
@@ -29,17 +39,83 @@ impl AsyncWrite for TokioTcpStream {
     }
 }
 
-pub struct TokioTcpDialer;
+#[derive(Default)]
+pub struct TokioTcpDialer {
+    local_addr: Option<SocketAddr>,
+    interface: Option<String>,
+    options: TcpOptions,
+}
 
 impl TokioTcpDialer {
-    pub fn new() -> Self { Self }
+    pub fn new() -> Self { Self::default() }
+
+    pub fn with_local_addr(mut self, local_addr: Option<SocketAddr>) -> Self {
+        self.local_addr = local_addr;
+        self
+    }
+
+    pub fn with_interface(mut self, interface: Option<String>) -> Self {
+        self.interface = interface;
+        self
+    }
+
+    pub fn with_options(mut self, options: TcpOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    fn socket(&self, ip: IpAddr) -> Result<TcpSocket, UdocError> {
+        let socket = match ip {
+            IpAddr::V4(_) => TcpSocket::new_v4(),
+            IpAddr::V6(_) => TcpSocket::new_v6(),
+        }.map_err(|e| UdocError::tcp(format!("failed to create socket: {}", e)))?;
+
+        if let Some(ref interface) = self.interface {
+            bind_device(&socket, interface)?;
+        }
+        if let Some(local) = self.local_addr {
+            if local.is_ipv4() != ip.is_ipv4() {
+                return Err(UdocError::tcp(format!("local address {} cannot reach {} (address family mismatch)", local.ip(), ip)));
+            }
+            socket.set_reuseaddr(true).map_err(|e| UdocError::tcp(format!("failed to set SO_REUSEADDR: {}", e)))?;
+            socket.bind(local).map_err(|e| UdocError::tcp(format!("cannot bind to local address {}: {}", local, e)))?;
+        }
+        if self.options.nodelay {
+            socket.set_nodelay(true).map_err(|e| option_error("TCP_NODELAY", e))?;
+        }
+        if self.options.keepalive {
+            socket.set_keepalive(true).map_err(|e| option_error("SO_KEEPALIVE", e))?;
+        }
+        if let Some(size) = self.options.send_buffer {
+            socket.set_send_buffer_size(size).map_err(|e| option_error("SO_SNDBUF", e))?;
+        }
+        if let Some(size) = self.options.recv_buffer {
+            socket.set_recv_buffer_size(size).map_err(|e| option_error("SO_RCVBUF", e))?;
+        }
+        if self.options.fastopen {
+            enable_fastopen(&socket)?;
+        }
+        Ok(socket)
+    }
+
+    fn effective_options(&self, socket: &TcpSocket) -> TcpOptions {
+        TcpOptions {
+            nodelay: socket.nodelay().unwrap_or(self.options.nodelay),
+            keepalive: socket.keepalive().unwrap_or(self.options.keepalive),
+            fastopen: self.options.fastopen,
+            send_buffer: socket.send_buffer_size().ok(),
+            recv_buffer: socket.recv_buffer_size().ok(),
+        }
+    }
 }
 
 impl TcpDialer for TokioTcpDialer {
     async fn connect(&self, ip: IpAddr, port: u16) -> Result<TcpConnection, UdocError> {
+        let socket = self.socket(ip)?;
+        let options = self.effective_options(&socket);
         let start = Instant::now();
         let addr = SocketAddr::new(ip, port);
-        let stream = TcpStream::connect(addr).await.map_err(|e| {
+        let stream = socket.connect(addr).await.map_err(|e| {
             let msg = match e.kind() {
                 std::io::ErrorKind::ConnectionRefused => format!("connection refused: {}:{}", ip, port),
                 std::io::ErrorKind::TimedOut => format!("connection timed out: {}:{}", ip, port),
@@ -47,9 +123,164 @@ impl TcpDialer for TokioTcpDialer {
             };
             UdocError::tcp(msg)
         })?;
+        let tcp_ms = start.elapsed().as_secs_f64() * 1000.0;
+        let tcp_probe = SocketTcpInfo::attach(&stream);
         Ok(TcpConnection {
-            stream: BoxedIoStream(Box::new(TokioTcpStream(stream))),
-            tcp_ms: start.elapsed().as_secs_f64() * 1000.0,
+            local_addr: stream.local_addr().ok(),
+            tcp_stats: tcp_probe.as_ref().and_then(|p| p.sample()).map(|after_connect| TcpStats { options, after_connect, after_response: None }),
+            tcp_probe: tcp_probe.clone().map(|p| p as _),
+            stream: BoxedIoStream(Box::new(TokioTcpStream(stream, tcp_probe))),
+            tcp_ms,
+            tcp_deferred: options.fastopen,
+            proxy: None,
+            unix_socket: None,
+            proxy_protocol: None,
         })
     }
 }
+
+fn option_error(name: &str, e: std::io::Error) -> UdocError {
+    UdocError::tcp(format!("failed to set {}: {}", name, e))
+}
+
+#[cfg(target_os = "linux")]
+fn enable_fastopen(socket: &TcpSocket) -> Result<(), UdocError> {
+    use std::os::fd::AsRawFd;
+
+    let enable: libc::c_int = 1;
+    let rc = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::IPPROTO_TCP,
+            libc::TCP_FASTOPEN_CONNECT,
+            (&enable as *const libc::c_int).cast(),
+            std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if rc != 0 {
+        return Err(option_error("TCP_FASTOPEN_CONNECT", std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn enable_fastopen(_socket: &TcpSocket) -> Result<(), UdocError> {
+    Err(UdocError::tcp("TCP Fast Open (TCP_FASTOPEN_CONNECT) is only supported on Linux"))
+}
+
+#[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+fn bind_device(socket: &TcpSocket, interface: &str) -> Result<(), UdocError> {
+    socket.bind_device(Some(interface.as_bytes())).map_err(|e| {
+        let msg = match e.kind() {
+            std::io::ErrorKind::PermissionDenied => format!("binding to interface {} requires CAP_NET_RAW: {}", interface, e),
+            _ => format!("cannot bind to interface {}: {}", interface, e),
+        };
+        UdocError::tcp(msg)
+    })
+}
+
+#[cfg(not(any(target_os = "android", target_os = "fuchsia", target_os = "linux")))]
+fn bind_device(_socket: &TcpSocket, interface: &str) -> Result<(), UdocError> {
+    Err(UdocError::tcp(format!("binding to interface {} (SO_BINDTODEVICE) is only supported on Linux", interface)))
+}
+
+#[cfg(target_os = "linux")]
+const TCPI_OPT_SYN_DATA: u8 = 32;
+
+#[derive(Default)]
+struct TcpInfoState {
+    fd: Option<RawFd>,
+    last: Option<TcpInfo>,
+}
+
+/// Reads TCP_INFO from the socket of a live `TokioTcpStream` through its borrowed fd.
+/// The stream takes a final sample and clears the fd under the lock before the socket
+/// closes, so the probe never keeps the connection open or touches a reused fd.
+struct SocketTcpInfo(Mutex<TcpInfoState>);
+
+impl SocketTcpInfo {
+    fn attach(stream: &TcpStream) -> Option<Arc<Self>> {
+        use std::os::fd::AsRawFd;
+
+        let fd = stream.as_raw_fd();
+        read_tcp_info(fd)?;
+        Some(Arc::new(Self(Mutex::new(TcpInfoState { fd: Some(fd), last: None }))))
+    }
+
+    fn close(&self) {
+        if let Ok(mut state) = self.0.lock() {
+            state.last = state.fd.take().and_then(read_tcp_info).or(state.last);
+        }
+    }
+}
+
+impl TcpInfoProbe for SocketTcpInfo {
+    fn sample(&self) -> Option<TcpInfo> {
+        let state = self.0.lock().ok()?;
+        match state.fd {
+            Some(fd) => read_tcp_info(fd),
+            None => state.last,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_tcp_info(fd: RawFd) -> Option<TcpInfo> {
+    let mut raw = [0u8; std::mem::size_of::<libc::tcp_info>()];
+    let mut len = raw.len() as libc::socklen_t;
+    let rc = unsafe {
+        libc::getsockopt(fd, libc::IPPROTO_TCP, libc::TCP_INFO, raw.as_mut_ptr().cast(), &mut len)
+    };
+    if rc != 0 {
+        return None;
+    }
+    let info = unsafe { std::ptr::read_unaligned(raw.as_ptr().cast::<libc::tcp_info>()) };
+    // Byte 7 holds the `tcpi_delivery_rate_app_limited:1, tcpi_fastopen_client_fail:2`
+    // bitfields, which the libc struct leaves as padding.
+    let bitfields = raw[7];
+    Some(TcpInfo {
+        rtt_ms: info.tcpi_rtt as f64 / 1000.0,
+        rttvar_ms: info.tcpi_rttvar as f64 / 1000.0,
+        min_rtt_ms: info.tcpi_min_rtt as f64 / 1000.0,
+        retransmits: info.tcpi_total_retrans,
+        lost: info.tcpi_lost,
+        cwnd: info.tcpi_snd_cwnd,
+        mss: info.tcpi_snd_mss,
+        pmtu: info.tcpi_pmtu,
+        delivery_rate: info.tcpi_delivery_rate,
+        syn_data: info.tcpi_options & TCPI_OPT_SYN_DATA != 0,
+        fastopen_fail: FastOpenFailure::from_kernel((bitfields >> 1) & 0b11),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_tcp_info(_fd: RawFd) -> Option<TcpInfo> {
+    None
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::net::Ipv4Addr;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+    use super::*;
+
+    #[tokio::test]
+    async fn tcp_info_probe_does_not_keep_the_socket_open() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let conn = TokioTcpDialer::new().connect(addr.ip(), addr.port()).await.unwrap();
+        let (mut server, _) = listener.accept().await.unwrap();
+
+        let probe = conn.tcp_probe.expect("TCP_INFO is available on Linux");
+        assert!(conn.tcp_stats.is_some());
+        let live = probe.sample().unwrap();
+        assert!(live.mss > 0);
+
+        drop(conn.stream);
+        let mut buf = [0u8; 1];
+        let n = tokio::time::timeout(std::time::Duration::from_secs(2), server.read(&mut buf)).await.unwrap().unwrap();
+        assert_eq!(n, 0, "peer sees EOF while the probe is still alive");
+        assert!(probe.sample().is_some(), "the last sample survives the close");
+    }
+}
@@ -52,16 +52,35 @@ impl AsyncWrite for IoStreamAdapter {
     }
 }
 
+const DEFAULT_ALPN: &[&str] = &["h2", "http/1.1"];
+
 pub struct RustlsTlsHandshaker {
     connector: TlsConnector,
+    alpn: Vec<String>,
 }
 
 impl RustlsTlsHandshaker {
     pub fn new() -> Result<Self, UdocError> {
+        let alpn: Vec<String> = DEFAULT_ALPN.iter().map(|p| p.to_string()).collect();
+        Self::with_alpn(&alpn)
+    }
+
+    pub fn with_alpn(alpn: &[String]) -> Result<Self, UdocError> {
         let root_store = rustls::RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
         let mut config = ClientConfig::builder().with_root_certificates(root_store).with_no_client_auth();
-        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
-        Ok(Self { connector: TlsConnector::from(Arc::new(config)) })
+        config.alpn_protocols = alpn.iter().map(|p| p.as_bytes().to_vec()).collect();
+        Ok(Self { connector: TlsConnector::from(Arc::new(config)), alpn: alpn.to_vec() })
+    }
+
+    fn handshake_error(&self, err: std::io::Error) -> UdocError {
+        let unoffered = err.get_ref()
+            .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+            .is_some_and(|e| matches!(e, rustls::Error::PeerMisbehaved(rustls::PeerMisbehaved::SelectedUnofferedApplicationProtocol)));
+        if unoffered {
+            UdocError::tls(format!("TLS handshake failed: server selected an ALPN protocol that was not offered (offered: {})", self.alpn.join(",")))
+        } else {
+            UdocError::tls(format!("TLS handshake failed: {}", err))
+        }
     }
 }
 
@@ -73,7 +92,7 @@ impl TlsHandshaker for RustlsTlsHandshaker {
 
         let adapter = IoStreamAdapter(stream);
         let tls_stream = self.connector.connect(server_name, adapter).await
-            .map_err(|e| UdocError::tls(format!("TLS handshake failed: {}", e)))?;
+            .map_err(|e| self.handshake_error(e))?;
 
         let tls_ms = start.elapsed().as_secs_f64() * 1000.0;
         let (_, conn) = tls_stream.get_ref();
@@ -86,14 +105,14 @@ impl TlsHandshaker for RustlsTlsHandshaker {
         };
 
         let alpn = conn.alpn_protocol().map(|p| String::from_utf8_lossy(p).to_string());
-        let cipher = conn.negotiated_cipher_suite().map(|cs| format!("{:?}", cs.suite())).unwrap_or_else(|| "unknown".to_string());
+        let cipher = conn.negotiated_cipher_suite().map(|cs| format!("{:?}", cs.suite()));
         let peer_certs: Vec<Vec<u8>> = conn.peer_certificates().map(|certs| certs.iter().map(|c| c.as_ref().to_vec()).collect()).unwrap_or_default();
         let chain_len = peer_certs.len();
 
         Ok(TlsSession {
             stream: BoxedIoStream(Box::new(RustlsTlsStream(tls_stream))),
             tls_ms,
-            summary: TlsSummary::new(version, alpn, cipher, chain_len, true),
+            summary: TlsSummary::new(version, alpn, cipher, chain_len, true).with_alpn_offered(self.alpn.clone()),
             peer_certs,
         })
     }
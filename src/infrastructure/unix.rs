@@ -0,0 +1,108 @@
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::UnixStream;
+use crate::domain::UdocError;
+use crate::ports::{BoxedIoStream, IoStream, TcpConnection, TcpDialer};
+
+struct TokioUnixStream(UnixStream);
+
+impl IoStream for TokioUnixStream {}
+
+impl AsyncRead for TokioUnixStream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for TokioUnixStream {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
+pub struct UnixSocketDialer<T> {
+    inner: T,
+    path: Option<String>,
+}
+
+impl<T: TcpDialer> UnixSocketDialer<T> {
+    pub fn new(inner: T) -> Self {
+        Self { inner, path: None }
+    }
+
+    pub fn with_path(mut self, path: Option<String>) -> Self {
+        self.path = path;
+        self
+    }
+}
+
+impl<T: TcpDialer> TcpDialer for UnixSocketDialer<T> {
+    async fn connect(&self, ip: IpAddr, port: u16) -> Result<TcpConnection, UdocError> {
+        self.inner.connect(ip, port).await
+    }
+
+    fn tunnels(&self, host: &str, port: u16) -> bool {
+        self.path.is_some() || self.inner.tunnels(host, port)
+    }
+
+    async fn connect_tunnel(&self, host: &str, port: u16) -> Result<TcpConnection, UdocError> {
+        let Some(ref path) = self.path else {
+            return self.inner.connect_tunnel(host, port).await;
+        };
+        let start = Instant::now();
+        let stream = connect_unix(path).await.map_err(|e| {
+            let msg = match e.kind() {
+                std::io::ErrorKind::NotFound => format!("unix socket not found: {}", path),
+                std::io::ErrorKind::ConnectionRefused => format!("connection refused: {}", path),
+                std::io::ErrorKind::PermissionDenied => format!("permission denied: {}", path),
+                _ => format!("unix socket connect failed to {}: {}", path, e),
+            };
+            UdocError::tcp(msg)
+        })?;
+        Ok(TcpConnection {
+            stream: BoxedIoStream(Box::new(TokioUnixStream(stream))),
+            tcp_ms: start.elapsed().as_secs_f64() * 1000.0,
+            tcp_deferred: false,
+            proxy: None,
+            unix_socket: Some(path.clone()),
+            local_addr: None,
+            tcp_stats: None,
+            tcp_probe: None,
+            proxy_protocol: None,
+        })
+    }
+}
+
+async fn connect_unix(path: &str) -> std::io::Result<UnixStream> {
+    match path.strip_prefix('@') {
+        Some(name) => connect_abstract(name),
+        None => UnixStream::connect(path).await,
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn connect_abstract(name: &str) -> std::io::Result<UnixStream> {
+    #[cfg(target_os = "linux")]
+    use std::os::linux::net::SocketAddrExt;
+    #[cfg(target_os = "android")]
+    use std::os::android::net::SocketAddrExt;
+
+    let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+    let stream = std::os::unix::net::UnixStream::connect_addr(&addr)?;
+    stream.set_nonblocking(true)?;
+    UnixStream::from_std(stream)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn connect_abstract(name: &str) -> std::io::Result<UnixStream> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, format!("abstract socket @{} is only supported on Linux", name)))
+}
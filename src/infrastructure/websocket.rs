@@ -0,0 +1,468 @@
+use std::time::Instant;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ring::rand::{SecureRandom, SystemRandom};
+use tokio::io::AsyncReadExt;
+use crate::domain::{ExchangeTiming, HeaderList, UdocError, WebSocketOptions, WebSocketTransport, WebSocketUpgrade};
+use crate::ports::{BoxedIoStream, HttpRequest, HttpResponse, ResponseHeaders};
+use super::h2_frames::*;
+use super::h2c::response_from_fields;
+use super::http::{elapsed_ms, find_header_end, h1_request_head, parse_headers, read_h1_response, write_request};
+
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const CONNECT_STREAM: u32 = 1;
+const DEFAULT_WINDOW: u32 = 65535;
+const SETTINGS_ENABLE_CONNECT_PROTOCOL: u16 = 0x8;
+const MAX_FRAME_PAYLOAD: usize = 16 * 1024 * 1024;
+const PING_PAYLOAD: &[u8] = b"udoc-ping";
+const CLOSE_NORMAL: [u8; 2] = [0x03, 0xE8];
+
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+pub async fn h1_upgrade(mut stream: BoxedIoStream, request: &HttpRequest, options: &WebSocketOptions) -> Result<HttpResponse, UdocError> {
+    let proto = if request.is_https { "HTTPS" } else { "HTTP" };
+    let key = STANDARD.encode(random_bytes::<16>()?);
+    let mut extra = vec![
+        ("Upgrade", "websocket".to_string()),
+        ("Sec-WebSocket-Key", key.clone()),
+        ("Sec-WebSocket-Version", "13".to_string()),
+    ];
+    if !options.protocols.is_empty() {
+        extra.push(("Sec-WebSocket-Protocol", options.protocols.join(", ")));
+    }
+    if let Some(ref extensions) = options.extensions {
+        extra.push(("Sec-WebSocket-Extensions", extensions.clone()));
+    }
+    let head = h1_request_head(request, "Upgrade", &extra);
+
+    let start = Instant::now();
+    write_request(&mut stream, head.as_bytes()).await?;
+    let request_sent_ms = elapsed_ms(start);
+
+    let mut buffer = Vec::new();
+    let mut chunk = vec![0u8; 16 * 1024];
+    let mut first_byte_ms = None;
+    let head_end = loop {
+        if let Some(pos) = find_header_end(&buffer) { break pos; }
+        let n = stream.read(&mut chunk).await.map_err(|e| UdocError::http(format!("failed to read response: {}", e)))?;
+        if n == 0 { return Err(UdocError::http("connection closed before WebSocket upgrade response")); }
+        if first_byte_ms.is_none() { first_byte_ms = Some(elapsed_ms(start)); }
+        buffer.extend_from_slice(&chunk[..n]);
+    };
+
+    let (summary, headers) = parse_headers(&buffer[..head_end], proto)?;
+    if summary.status != 101 {
+        let (mut response, _) = read_h1_response(&mut stream, request, start, request_sent_ms, buffer, first_byte_ms).await?;
+        let upgrade = WebSocketUpgrade::new(WebSocketTransport::Http1Upgrade, response.summary.status, options.protocols.clone(), response.timing.headers_ms);
+        response.websocket = Some(upgrade);
+        return Ok(response);
+    }
+    let headers_ms = elapsed_ms(start);
+
+    let mut upgrade = switched(WebSocketTransport::Http1Upgrade, &headers, options, headers_ms);
+    upgrade.accept_valid = Some(headers.all.get("sec-websocket-accept").map(str::trim) == Some(accept_for(&key).as_str()));
+
+    let mut socket = H1Socket { stream, buffer: buffer.split_off(head_end + 4) };
+    if options.ping {
+        record_ping(&mut upgrade, ping(&mut socket).await);
+    }
+    socket.send(&encode_ws_frame(OP_CLOSE, &CLOSE_NORMAL)?).await.ok();
+
+    Ok(HttpResponse {
+        summary,
+        headers,
+        timing: ExchangeTiming {
+            request_sent_ms,
+            ttfb_ms: first_byte_ms.unwrap_or(headers_ms),
+            headers_ms,
+            body_ms: headers_ms,
+        },
+        body_preview: Vec::new(),
+        body_truncated: false,
+        trailers: HeaderList::new(),
+        upgrade: None,
+        websocket: Some(upgrade),
+        h2: None,
+        connection: None,
+    })
+}
+
+pub async fn h2_connect(stream: BoxedIoStream, request: &HttpRequest, options: &WebSocketOptions, windows: H2Windows) -> Result<HttpResponse, UdocError> {
+    let (proto, version) = if request.is_https { ("HTTPS", "h2") } else { ("HTTP", "h2c") };
+    let start = Instant::now();
+    let mut conn = FrameReader::new(stream, Vec::new(), FrameTracer::default());
+
+    let settings = [
+        (SETTINGS_ENABLE_PUSH, 0),
+        (SETTINGS_INITIAL_WINDOW_SIZE, windows.stream),
+        (SETTINGS_MAX_FRAME_SIZE, windows.max_frame_size),
+    ];
+    let mut preface = PREFACE.to_vec();
+    preface.extend(encode_frame(SETTINGS, 0, 0, &settings_payload(&settings)));
+    if windows.connection > DEFAULT_WINDOW {
+        preface.extend(encode_frame(WINDOW_UPDATE, 0, 0, &(windows.connection - DEFAULT_WINDOW).to_be_bytes()));
+    }
+    conn.write(&preface).await?;
+
+    let connect_enabled = loop {
+        match conn.next_stream_frame(CONNECT_STREAM).await? {
+            StreamFrame::Settings(settings) => {
+                break settings.iter().any(|&(id, value)| id == SETTINGS_ENABLE_CONNECT_PROTOCOL && value == 1);
+            }
+            StreamFrame::GoAway(code) => return Err(UdocError::http(format!("h2 GOAWAY before extended CONNECT: {}", error_code_name(code)))),
+            _ => {}
+        }
+    };
+    if !connect_enabled {
+        return Err(UdocError::http("server did not enable extended CONNECT (SETTINGS_ENABLE_CONNECT_PROTOCOL), so it cannot carry WebSockets over h2; retry with --http1.1"));
+    }
+
+    let authority = request.authority();
+    let mut fields: Vec<(String, String)> = vec![
+        (":method".to_string(), "CONNECT".to_string()),
+        (":protocol".to_string(), "websocket".to_string()),
+        (":scheme".to_string(), if request.is_https { "https" } else { "http" }.to_string()),
+        (":path".to_string(), request.path.clone()),
+        (":authority".to_string(), authority),
+        ("sec-websocket-version".to_string(), "13".to_string()),
+        ("user-agent".to_string(), "udoc/0.2".to_string()),
+    ];
+    if !options.protocols.is_empty() {
+        fields.push(("sec-websocket-protocol".to_string(), options.protocols.join(", ")));
+    }
+    if let Some(ref extensions) = options.extensions {
+        fields.push(("sec-websocket-extensions".to_string(), extensions.clone()));
+    }
+    for (name, value) in &request.headers {
+        fields.push((name.to_ascii_lowercase(), value.clone()));
+    }
+    let fields: Vec<(Vec<u8>, Vec<u8>)> = fields.into_iter().map(|(n, v)| (n.into_bytes(), v.into_bytes())).collect();
+    let block = hpack::Encoder::new().encode(&fields);
+    conn.write(&encode_frame(HEADERS, FLAG_END_HEADERS, CONNECT_STREAM, &block)).await?;
+    let request_sent_ms = elapsed_ms(start);
+
+    let (summary, headers, mut ended) = loop {
+        match conn.next_stream_frame(CONNECT_STREAM).await? {
+            StreamFrame::GoAway(code) => return Err(UdocError::http(format!("h2 GOAWAY before extended CONNECT response: {}", error_code_name(code)))),
+            StreamFrame::Reset(code) => return Err(UdocError::http(format!("extended CONNECT stream reset: {}", error_code_name(code)))),
+            StreamFrame::Headers { fields, end_stream } => {
+                let (summary, headers) = response_from_fields(&fields, version, proto)?;
+                if summary.status >= 200 {
+                    break (summary, headers, end_stream);
+                }
+            }
+            StreamFrame::Data { .. } | StreamFrame::Settings(_) => {}
+        }
+    };
+    let headers_ms = elapsed_ms(start);
+
+    if summary.status != 200 {
+        let mut body_preview = Vec::new();
+        let mut body_truncated = false;
+        while !ended {
+            match conn.next_stream_frame(CONNECT_STREAM).await? {
+                StreamFrame::GoAway(_) | StreamFrame::Reset(_) => break,
+                StreamFrame::Headers { end_stream, .. } => ended = end_stream,
+                StreamFrame::Data { data, length, end_stream } => {
+                    let room = request.body_limit.saturating_sub(body_preview.len());
+                    body_preview.extend_from_slice(&data[..data.len().min(room)]);
+                    ended = end_stream;
+                    if !ended && body_preview.len() >= request.body_limit { body_truncated = true; break; }
+                    if length > 0 { conn.write(&window_update(CONNECT_STREAM, length)).await?; }
+                }
+                StreamFrame::Settings(_) => {}
+            }
+        }
+        let body_ms = elapsed_ms(start);
+        let upgrade = WebSocketUpgrade::new(WebSocketTransport::H2ExtendedConnect, summary.status, options.protocols.clone(), headers_ms);
+        return Ok(HttpResponse {
+            summary,
+            headers,
+            timing: ExchangeTiming { request_sent_ms, ttfb_ms: headers_ms, headers_ms, body_ms },
+            body_preview,
+            body_truncated,
+            trailers: HeaderList::new(),
+            upgrade: None,
+            websocket: Some(upgrade),
+            h2: Some(conn.session()),
+            connection: None,
+        });
+    }
+
+    let mut upgrade = switched(WebSocketTransport::H2ExtendedConnect, &headers, options, headers_ms);
+    {
+        let mut socket = H2Socket { conn: &mut conn, buffer: Vec::new(), ended };
+        if options.ping {
+            record_ping(&mut upgrade, ping(&mut socket).await);
+        }
+        let close = encode_ws_frame(OP_CLOSE, &CLOSE_NORMAL)?;
+        if !socket.ended {
+            socket.conn.write(&encode_frame(DATA, FLAG_END_STREAM, CONNECT_STREAM, &close)).await.ok();
+        }
+    }
+
+    Ok(HttpResponse {
+        summary,
+        headers,
+        timing: ExchangeTiming { request_sent_ms, ttfb_ms: headers_ms, headers_ms, body_ms: headers_ms },
+        body_preview: Vec::new(),
+        body_truncated: false,
+        trailers: HeaderList::new(),
+        upgrade: None,
+        websocket: Some(upgrade),
+        h2: Some(conn.session()),
+        connection: None,
+    })
+}
+
+fn switched(transport: WebSocketTransport, headers: &ResponseHeaders, options: &WebSocketOptions, upgrade_ms: f64) -> WebSocketUpgrade {
+    let status = if transport == WebSocketTransport::Http1Upgrade { 101 } else { 200 };
+    let mut upgrade = WebSocketUpgrade::new(transport, status, options.protocols.clone(), upgrade_ms);
+    upgrade.switched = true;
+    upgrade.subprotocol = headers.all.get("sec-websocket-protocol").map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    upgrade.extensions = headers.all.get_all("sec-websocket-extensions")
+        .flat_map(|v| v.split(','))
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty())
+        .collect();
+    upgrade
+}
+
+fn record_ping(upgrade: &mut WebSocketUpgrade, result: Result<f64, UdocError>) {
+    match result {
+        Ok(rtt_ms) => upgrade.ping_rtt_ms = Some(rtt_ms),
+        Err(e) => upgrade.ping_error = Some(e.message),
+    }
+}
+
+trait FrameIo {
+    fn buffer(&mut self) -> &mut Vec<u8>;
+
+    async fn send(&mut self, bytes: &[u8]) -> Result<(), UdocError>;
+
+    async fn fill(&mut self) -> Result<(), UdocError>;
+}
+
+struct H1Socket {
+    stream: BoxedIoStream,
+    buffer: Vec<u8>,
+}
+
+impl FrameIo for H1Socket {
+    fn buffer(&mut self) -> &mut Vec<u8> {
+        &mut self.buffer
+    }
+
+    async fn send(&mut self, bytes: &[u8]) -> Result<(), UdocError> {
+        write_request(&mut self.stream, bytes).await
+    }
+
+    async fn fill(&mut self) -> Result<(), UdocError> {
+        let mut chunk = [0u8; 16 * 1024];
+        let n = self.stream.read(&mut chunk).await.map_err(|e| UdocError::http(format!("WebSocket read failed: {}", e)))?;
+        if n == 0 { return Err(UdocError::http("server closed the connection")); }
+        self.buffer.extend_from_slice(&chunk[..n]);
+        Ok(())
+    }
+}
+
+struct H2Socket<'a> {
+    conn: &'a mut FrameReader,
+    buffer: Vec<u8>,
+    ended: bool,
+}
+
+impl FrameIo for H2Socket<'_> {
+    fn buffer(&mut self) -> &mut Vec<u8> {
+        &mut self.buffer
+    }
+
+    async fn send(&mut self, bytes: &[u8]) -> Result<(), UdocError> {
+        self.conn.write(&encode_frame(DATA, 0, CONNECT_STREAM, bytes)).await
+    }
+
+    async fn fill(&mut self) -> Result<(), UdocError> {
+        loop {
+            if self.ended { return Err(UdocError::http("server ended the WebSocket stream")); }
+            match self.conn.next_stream_frame(CONNECT_STREAM).await? {
+                StreamFrame::GoAway(code) => return Err(UdocError::http(format!("h2 GOAWAY: {}", error_code_name(code)))),
+                StreamFrame::Reset(code) => return Err(UdocError::http(format!("WebSocket stream reset: {}", error_code_name(code)))),
+                StreamFrame::Data { data, length, end_stream } => {
+                    self.ended = end_stream;
+                    if length > 0 && !self.ended { self.conn.write(&window_update(CONNECT_STREAM, length)).await?; }
+                    if !data.is_empty() {
+                        self.buffer.extend_from_slice(&data);
+                        return Ok(());
+                    }
+                }
+                StreamFrame::Headers { end_stream, .. } => self.ended = end_stream,
+                StreamFrame::Settings(_) => {}
+            }
+        }
+    }
+}
+
+async fn ping(io: &mut impl FrameIo) -> Result<f64, UdocError> {
+    let start = Instant::now();
+    io.send(&encode_ws_frame(OP_PING, PING_PAYLOAD)?).await?;
+    loop {
+        while let Some((opcode, payload, used)) = parse_ws_frame(io.buffer())? {
+            io.buffer().drain(..used);
+            match opcode {
+                OP_PONG if payload == PING_PAYLOAD => return Ok(elapsed_ms(start)),
+                OP_PING => io.send(&encode_ws_frame(OP_PONG, &payload)?).await?,
+                OP_CLOSE => return Err(UdocError::http(format!("server closed the WebSocket before the pong{}", close_reason(&payload)))),
+                _ => {}
+            }
+        }
+        io.fill().await?;
+    }
+}
+
+fn parse_ws_frame(buf: &[u8]) -> Result<Option<(u8, Vec<u8>, usize)>, UdocError> {
+    if buf.len() < 2 { return Ok(None); }
+    let opcode = buf[0] & 0x0f;
+    let masked = buf[1] & 0x80 != 0;
+    let (len, mut pos) = match buf[1] & 0x7f {
+        126 if buf.len() < 4 => return Ok(None),
+        126 => (u16::from_be_bytes([buf[2], buf[3]]) as usize, 4),
+        127 if buf.len() < 10 => return Ok(None),
+        127 => {
+            let len = u64::from_be_bytes([buf[2], buf[3], buf[4], buf[5], buf[6], buf[7], buf[8], buf[9]]);
+            (usize::try_from(len).unwrap_or(usize::MAX), 10)
+        }
+        n => (n as usize, 2),
+    };
+    if len > MAX_FRAME_PAYLOAD {
+        return Err(UdocError::http(format!("WebSocket frame of {} bytes exceeds the {} byte limit", len, MAX_FRAME_PAYLOAD)));
+    }
+    let mask = if masked {
+        if buf.len() < pos + 4 { return Ok(None); }
+        pos += 4;
+        Some([buf[pos - 4], buf[pos - 3], buf[pos - 2], buf[pos - 1]])
+    } else {
+        None
+    };
+    if buf.len() < pos + len { return Ok(None); }
+    let mut payload = buf[pos..pos + len].to_vec();
+    if let Some(mask) = mask {
+        payload.iter_mut().enumerate().for_each(|(i, b)| *b ^= mask[i % 4]);
+    }
+    Ok(Some((opcode, payload, pos + len)))
+}
+
+fn encode_ws_frame(opcode: u8, payload: &[u8]) -> Result<Vec<u8>, UdocError> {
+    let mask = random_bytes::<4>()?;
+    let mut out = Vec::with_capacity(payload.len() + 14);
+    out.push(0x80 | opcode);
+    match payload.len() {
+        n if n < 126 => out.push(0x80 | n as u8),
+        n if n <= u16::MAX as usize => {
+            out.push(0x80 | 126);
+            out.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        n => {
+            out.push(0x80 | 127);
+            out.extend_from_slice(&(n as u64).to_be_bytes());
+        }
+    }
+    out.extend_from_slice(&mask);
+    out.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    Ok(out)
+}
+
+fn close_reason(payload: &[u8]) -> String {
+    match payload {
+        [hi, lo, reason @ ..] => format!(" (close code {}{})", u16::from_be_bytes([*hi, *lo]),
+            if reason.is_empty() { String::new() } else { format!(": {}", String::from_utf8_lossy(reason)) }),
+        _ => String::new(),
+    }
+}
+
+fn accept_for(key: &str) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, format!("{}{}", key, ACCEPT_GUID).as_bytes());
+    STANDARD.encode(digest.as_ref())
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], UdocError> {
+    let mut bytes = [0u8; N];
+    SystemRandom::new().fill(&mut bytes).map_err(|_| UdocError::other("failed to generate random bytes for WebSocket"))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use super::*;
+
+    /// An h2 server that accepts one extended CONNECT, checks `:protocol`, and answers
+    /// the client's WebSocket ping with a pong before the close frame.
+    async fn h2_websocket_server(io: tokio::io::DuplexStream, enable_connect: bool) {
+        let mut builder = h2::server::Builder::new();
+        if enable_connect { builder.enable_connect_protocol(); }
+        let mut conn = builder.handshake::<_, Bytes>(io).await.unwrap();
+        let Some(Ok((request, mut respond))) = conn.accept().await else { return };
+        tokio::spawn(async move { while conn.accept().await.is_some() {} });
+
+        assert_eq!(request.method(), http::Method::CONNECT);
+        assert_eq!(request.extensions().get::<h2::ext::Protocol>().map(|p| p.as_str()), Some("websocket"));
+        let response = http::Response::builder().status(200).header("sec-websocket-protocol", "chat").body(()).unwrap();
+        let mut send = respond.send_response(response, false).unwrap();
+        let mut recv = request.into_body();
+        let mut buffer = Vec::new();
+        while let Some(Ok(chunk)) = recv.data().await {
+            let _ = recv.flow_control().release_capacity(chunk.len());
+            buffer.extend_from_slice(&chunk);
+            while let Some((opcode, payload, used)) = parse_ws_frame(&buffer).unwrap() {
+                buffer.drain(..used);
+                if opcode == OP_PING {
+                    let mut pong = vec![0x80 | OP_PONG, payload.len() as u8];
+                    pong.extend(payload);
+                    send.send_data(Bytes::from(pong), false).unwrap();
+                }
+            }
+        }
+        let _ = send.send_data(Bytes::new(), true);
+    }
+
+    fn connect_request() -> (HttpRequest, WebSocketOptions) {
+        let request = HttpRequest::get("localhost", 443, "/chat", true, 1024);
+        let options = WebSocketOptions { protocols: vec!["chat".to_string()], extensions: None, ping: true };
+        (request, options)
+    }
+
+    #[tokio::test]
+    async fn h2_extended_connect_switches_and_pings() {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(h2_websocket_server(server, true));
+        let (request, options) = connect_request();
+
+        let response = h2_connect(BoxedIoStream(Box::new(client)), &request, &options, H2Windows::default()).await.unwrap();
+        assert_eq!(response.summary.status, 200);
+        let upgrade = response.websocket.unwrap();
+        assert!(upgrade.switched);
+        assert_eq!(upgrade.transport, WebSocketTransport::H2ExtendedConnect);
+        assert_eq!(upgrade.subprotocol.as_deref(), Some("chat"));
+        assert!(upgrade.ping_rtt_ms.is_some(), "{:?}", upgrade.ping_error);
+    }
+
+    #[tokio::test]
+    async fn h2_requires_enable_connect_protocol() {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(h2_websocket_server(server, false));
+        let (request, options) = connect_request();
+
+        let err = h2_connect(BoxedIoStream(Box::new(client)), &request, &options, H2Windows::default()).await.err().unwrap();
+        assert!(err.message.contains("SETTINGS_ENABLE_CONNECT_PROTOCOL"), "{}", err.message);
+    }
+
+    #[test]
+    fn round_trips_masked_frames() {
+        let frame = encode_ws_frame(OP_PING, PING_PAYLOAD).unwrap();
+        assert_eq!((frame[0], frame[1]), (0x80 | OP_PING, 0x80 | PING_PAYLOAD.len() as u8));
+        assert_eq!(parse_ws_frame(&frame).unwrap(), Some((OP_PING, PING_PAYLOAD.to_vec(), frame.len())));
+        assert_eq!(parse_ws_frame(&frame[..frame.len() - 1]).unwrap(), None);
+    }
+}
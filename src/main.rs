@@ -1,6 +1,8 @@
 use std::process::ExitCode;
-use udoc::application::{GenerateReportUseCase, Config};
-use udoc::infrastructure::{HickoryDnsResolver, HybridHttpClient, PrettyRenderer, JsonRenderer, RustlsTlsHandshaker, TokioClock, TokioTcpDialer};
+use std::net::{IpAddr, SocketAddr};
+use udoc::application::{GenerateReportUseCase, TcpingUseCase, TlsProbeUseCase, Config, ParsedUrl, parse_status_assertion, parse_header_assertion, parse_duration, parse_duration_ms, parse_host_port, parse_proxy_url, proxy_from_env, no_proxy_from_env};
+use udoc::infrastructure::{HickoryDnsResolver, HybridHttpClient, PrettyRenderer, JsonRenderer, PlaintextStartTls, ProxyProtocolDialer, ProxyTcpDialer, QuinnQuicClient, RustlsTlsHandshaker, TokioClock, TokioTcpDialer, UnixSocketDialer};
+use udoc::domain::{Assertion, ErrorClass, NoProxy, ProxyProtocolSpec, ProxyProtocolVersion, Report, StartTlsProtocol, TcpOptions, UdocError, WebSocketOptions};
 use udoc::ports::Renderer;
 
 fn main() -> ExitCode {
@@ -8,7 +10,12 @@ fn main() -> ExitCode {
 
     let args: Vec<String> = std::env::args().collect();
 
-    let (url, json_mode) = match parse_args(&args) {
+    let command = match args.get(1).map(String::as_str) {
+        Some("tcping") => parse_tcping_args(&args).map(|(host, port, config)| Command::Tcping(host, port, config)),
+        Some("tls") => parse_tls_args(&args).map(|(host, port, config)| Command::Tls(host, port, config)),
+        _ => parse_args(&args).map(|(url, config)| Command::Report(url, config)),
+    };
+    let command = match command {
         Ok(v) => v,
         Err(msg) => {
             eprintln!("{}", msg);
@@ -16,8 +23,6 @@ fn main() -> ExitCode {
         }
     };
 
-    let config = Config::from_env().with_json(json_mode);
-
     let rt = match tokio::runtime::Builder::new_multi_thread().enable_all().build() {
         Ok(r) => r,
         Err(e) => {
@@ -26,16 +31,177 @@ fn main() -> ExitCode {
         }
     };
 
-    rt.block_on(async_main(&url, config))
+    match command {
+        Command::Report(url, config) => rt.block_on(async_main(&url, config)),
+        Command::Tcping(host, port, config) => rt.block_on(tcping_main(&host, port, config)),
+        Command::Tls(host, port, config) => rt.block_on(tls_main(&host, port, config)),
+    }
+}
+
+enum Command {
+    Report(String, Config),
+    Tcping(String, u16, Config),
+    Tls(String, u16, Config),
 }
 
-fn parse_args(args: &[String]) -> Result<(String, bool), String> {
+fn parse_args(args: &[String]) -> Result<(String, Config), String> {
     let mut url = None;
-    let mut json = false;
+    let mut config = Config::from_env();
+    let mut proxy_set = false;
+    let mut no_proxy_set = false;
+    let mut proxy_protocol = None;
+    let mut proxy_protocol_src = None;
+    let mut proxy_protocol_dst = None;
+    let mut alpn_set = false;
+    let mut ws_options = WebSocketOptions::default();
 
-    for arg in args.iter().skip(1) {
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
         if arg == "--json" || arg == "-j" {
-            json = true;
+            config = config.with_json(true);
+        } else if arg == "--compressed" {
+            config = config.with_compressed(true);
+        } else if arg == "--probe-encodings" {
+            config = config.with_probe_encodings(true);
+        } else if arg == "--output" || arg == "-o" {
+            let path = option_value(&mut iter, arg)?;
+            config = config.with_output(Some(path.clone()));
+        } else if arg == "--show-body" {
+            config = config.with_show_body(true);
+        } else if arg == "--body-json" {
+            config = config.with_body_json(true);
+        } else if arg == "--expect-status" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_assertion(parse_status_assertion(value)?);
+        } else if arg == "--expect-header" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_assertion(parse_header_assertion(value)?);
+        } else if arg == "--expect-body-contains" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_assertion(Assertion::BodyContains(value.clone()));
+        } else if arg == "--max-ttfb" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_assertion(Assertion::MaxTtfb(parse_duration_ms(value)?));
+        } else if arg == "--max-total" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_assertion(Assertion::MaxTotal(parse_duration_ms(value)?));
+        } else if arg == "--revalidate" {
+            config = config.with_revalidate(true);
+        } else if arg == "--warn-cert-days" {
+            let value = option_value(&mut iter, arg)?;
+            let days = value.parse().map_err(|_| format!("invalid --warn-cert-days value '{}'", value))?;
+            config = config.with_warn_cert_days(days);
+        } else if arg == "--fail-on-warning" {
+            config = config.with_fail_on_warning(true);
+        } else if arg == "--http2-prior-knowledge" {
+            config = config.with_http2_prior_knowledge(true);
+        } else if arg == "--h2c-upgrade" {
+            config = config.with_h2c_upgrade(true);
+        } else if arg == "--http1.1" {
+            config = config.with_alpn(vec!["http/1.1".to_string()]);
+            alpn_set = true;
+        } else if arg == "--http2" {
+            config = config.with_alpn(vec!["h2".to_string()]);
+            alpn_set = true;
+        } else if arg == "--alpn" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_alpn(parse_alpn_list(value)?);
+            alpn_set = true;
+        } else if arg == "--ws-protocol" {
+            let value = option_value(&mut iter, arg)?;
+            ws_options.protocols = value.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()).map(|p| p.to_string()).collect();
+        } else if arg == "--ws-extensions" {
+            let value = option_value(&mut iter, arg)?;
+            ws_options.extensions = Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        } else if arg == "--ws-ping" {
+            ws_options.ping = true;
+        } else if arg == "--grpc-health" {
+            let service = config.grpc_health.clone().unwrap_or_default();
+            config = config.with_grpc_health(Some(service));
+        } else if arg == "--grpc-service" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_grpc_health(Some(value.clone()));
+        } else if arg == "--compare-http-versions" {
+            config = config.with_compare_http_versions(true);
+        } else if arg == "--http3" {
+            config = config.with_http3(true);
+        } else if arg == "--follow-alt-svc" {
+            config = config.with_follow_alt_svc(true);
+        } else if arg == "--requests" {
+            let value = option_value(&mut iter, arg)?;
+            let n = value.parse::<usize>().ok().filter(|n| *n >= 1)
+                .ok_or_else(|| format!("invalid --requests value '{}'", value))?;
+            config = config.with_requests(n);
+        } else if arg == "--h2-streams" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_h2_streams(parse_bounded(arg, value, 1, MAX_H2_STREAMS)? as usize);
+        } else if arg == "--h2-path" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_h2_path(parse_h2_path(value)?);
+        } else if arg == "--proxy" || arg == "-x" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_proxy(Some(parse_proxy_url(value)?));
+            proxy_set = true;
+        } else if arg == "--no-proxy" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_no_proxy(NoProxy::parse(value));
+            no_proxy_set = true;
+        } else if arg == "--unix-socket" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_unix_socket(Some(parse_unix_socket(value)?));
+        } else if arg == "--local-addr" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_local_addr(Some(parse_local_addr(value)?));
+        } else if arg == "--interface" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_interface(Some(parse_interface(value)?));
+        } else if arg == "--tcp-nodelay" {
+            let options = TcpOptions { nodelay: true, ..config.tcp_options };
+            config = config.with_tcp_options(options);
+        } else if arg == "--tcp-keepalive" {
+            let options = TcpOptions { keepalive: true, ..config.tcp_options };
+            config = config.with_tcp_options(options);
+        } else if arg == "--tcp-fastopen" {
+            let options = TcpOptions { fastopen: true, ..config.tcp_options };
+            config = config.with_tcp_options(options);
+        } else if arg == "--tcp-sndbuf" {
+            let value = option_value(&mut iter, arg)?;
+            let size = parse_bounded(arg, value, MIN_SOCKET_BUFFER, MAX_SOCKET_BUFFER)?;
+            let options = TcpOptions { send_buffer: Some(size), ..config.tcp_options };
+            config = config.with_tcp_options(options);
+        } else if arg == "--tcp-rcvbuf" {
+            let value = option_value(&mut iter, arg)?;
+            let size = parse_bounded(arg, value, MIN_SOCKET_BUFFER, MAX_SOCKET_BUFFER)?;
+            let options = TcpOptions { recv_buffer: Some(size), ..config.tcp_options };
+            config = config.with_tcp_options(options);
+        } else if arg == "--proxy-protocol" {
+            let value = option_value(&mut iter, arg)?;
+            proxy_protocol = Some(match value.to_ascii_lowercase().as_str() {
+                "v1" | "1" => ProxyProtocolVersion::V1,
+                "v2" | "2" => ProxyProtocolVersion::V2,
+                _ => return Err(format!("invalid --proxy-protocol value '{}': expected v1 or v2", value)),
+            });
+        } else if arg == "--proxy-protocol-src" {
+            let value = option_value(&mut iter, arg)?;
+            proxy_protocol_src = Some(parse_socket_addr(arg, value)?);
+        } else if arg == "--proxy-protocol-dst" {
+            let value = option_value(&mut iter, arg)?;
+            proxy_protocol_dst = Some(parse_socket_addr(arg, value)?);
+        } else if arg == "--no-reuse" {
+            config = config.with_no_reuse(true);
+        } else if arg == "--h2-window" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_h2_stream_window(parse_bounded(arg, value, 1, MAX_H2_WINDOW)?);
+        } else if arg == "--h2-conn-window" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_h2_connection_window(parse_bounded(arg, value, 65535, MAX_H2_WINDOW)?);
+        } else if arg == "--h2-max-frame" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_h2_max_frame_size(parse_bounded(arg, value, 16384, 16_777_215)?);
+        } else if arg == "--min-cert-days" {
+            let value = option_value(&mut iter, arg)?;
+            let days = value.parse().map_err(|_| format!("invalid --min-cert-days value '{}'", value))?;
+            config = config.with_assertion(Assertion::MinCertDays(days));
         } else if arg == "--help" || arg == "-h" {
             return Err(usage());
         } else if arg.starts_with('-') {
@@ -47,48 +213,370 @@ fn parse_args(args: &[String]) -> Result<(String, bool), String> {
         }
     }
 
+    let parsed_url = url.as_deref().and_then(|u| ParsedUrl::parse(u).ok());
+    if !proxy_set {
+        config = config.with_proxy(proxy_from_env(parsed_url.as_ref().is_none_or(|u| u.is_https()))?);
+    }
+    if !no_proxy_set {
+        config = config.with_no_proxy(no_proxy_from_env());
+    }
+    config = config.with_proxy_protocol(proxy_protocol_spec(proxy_protocol, proxy_protocol_src, proxy_protocol_dst)?);
+    check_transport_flags(&config, proxy_set)?;
+    check_http_flags(&config, parsed_url.as_ref())?;
+
+    let websocket = parsed_url.as_ref().is_some_and(|u| u.is_websocket());
+    if config.grpc_health.is_some() {
+        config = grpc_health_config(config, parsed_url.as_ref(), websocket, alpn_set)?;
+    }
+    if websocket {
+        config = websocket_config(config, ws_options, alpn_set)?;
+    } else if ws_options != WebSocketOptions::default() {
+        return Err("--ws-protocol, --ws-extensions and --ws-ping require a ws:// or wss:// URL".to_string());
+    }
+
     match url {
-        Some(u) => Ok((u, json)),
+        Some(u) => Ok((u, config)),
         None => Err(usage()),
     }
 }
 
+fn proxy_protocol_spec(
+    version: Option<ProxyProtocolVersion>,
+    source: Option<SocketAddr>,
+    destination: Option<SocketAddr>,
+) -> Result<Option<ProxyProtocolSpec>, String> {
+    let Some(version) = version else {
+        if source.is_some() || destination.is_some() {
+            return Err("--proxy-protocol-src and --proxy-protocol-dst require --proxy-protocol".to_string());
+        }
+        return Ok(None);
+    };
+    if let (Some(src), Some(dst)) = (source, destination)
+        && src.is_ipv4() != dst.is_ipv4() {
+        return Err("--proxy-protocol-src and --proxy-protocol-dst must be the same address family".to_string());
+    }
+    Ok(Some(ProxyProtocolSpec { version, source, destination }))
+}
+
+/// Flags that shape the TCP connection cannot be combined with transports that bypass it.
+fn check_transport_flags(config: &Config, proxy_set: bool) -> Result<(), String> {
+    let quic = config.http3 || config.follow_alt_svc;
+    if config.unix_socket.is_some() && (proxy_set || quic) {
+        return Err("--unix-socket cannot be combined with --proxy, --http3 or --follow-alt-svc".to_string());
+    }
+    if config.proxy_protocol.is_some() && quic {
+        return Err("--proxy-protocol applies to TCP only and cannot be combined with --http3 or --follow-alt-svc".to_string());
+    }
+    let tcp_tuned = config.local_addr.is_some() || config.interface.is_some() || config.tcp_options != TcpOptions::default();
+    if tcp_tuned && (config.unix_socket.is_some() || quic) {
+        return Err("--local-addr, --interface and --tcp-* options apply to TCP only and cannot be combined with --unix-socket, --http3 or --follow-alt-svc".to_string());
+    }
+    if proxy_set && quic {
+        return Err("--proxy tunnels TCP only and cannot be combined with --http3 or --follow-alt-svc".to_string());
+    }
+    Ok(())
+}
+
+fn check_http_flags(config: &Config, url: Option<&ParsedUrl>) -> Result<(), String> {
+    if config.http2_prior_knowledge && config.h2c_upgrade {
+        return Err("--http2-prior-knowledge and --h2c-upgrade cannot be combined".to_string());
+    }
+    if config.no_reuse && config.requests > 1 {
+        return Err("--requests needs connection reuse and cannot be combined with --no-reuse".to_string());
+    }
+    if config.requests > 1 && (config.http3 || config.follow_alt_svc) {
+        return Err("--requests measures TCP keep-alive reuse and cannot be combined with --http3 or --follow-alt-svc".to_string());
+    }
+    if config.h2_streams > 0 && config.http3 {
+        return Err("--h2-streams tests HTTP/2 and cannot be combined with --http3".to_string());
+    }
+    if !config.h2_paths.is_empty() && config.h2_streams == 0 {
+        return Err("--h2-path requires --h2-streams".to_string());
+    }
+    if config.h2_streams > 0 && !config.http2_prior_knowledge && url.is_some_and(|u| !u.is_https()) {
+        return Err("--h2-streams needs h2; use an https:// URL or add --http2-prior-knowledge for http://".to_string());
+    }
+    Ok(())
+}
+
+/// gRPC health checks run a single h2 call, so they rule out the other probe modes and force h2.
+fn grpc_health_config(config: Config, url: Option<&ParsedUrl>, websocket: bool, alpn_set: bool) -> Result<Config, String> {
+    if websocket || config.http3 || config.follow_alt_svc || config.h2c_upgrade || config.compare_http_versions
+        || config.h2_streams > 0 || config.probe_encodings || config.revalidate {
+        return Err("--grpc-health calls grpc.health.v1.Health/Check over h2 and cannot be combined with ws:// URLs, --http3, --follow-alt-svc, --h2c-upgrade, --compare-http-versions, --h2-streams, --probe-encodings or --revalidate".to_string());
+    }
+    let config = if url.is_some_and(|u| !u.is_https()) { config.with_http2_prior_knowledge(true) } else { config };
+    Ok(if alpn_set { config } else { config.with_alpn(vec!["h2".to_string()]) })
+}
+
+/// ws:// and wss:// URLs run one HTTP/1.1 Upgrade handshake instead of a report request.
+fn websocket_config(config: Config, options: WebSocketOptions, alpn_set: bool) -> Result<Config, String> {
+    if config.http3 || config.follow_alt_svc || config.h2c_upgrade || config.compare_http_versions
+        || config.requests > 1 || config.h2_streams > 0 || config.probe_encodings || config.revalidate {
+        return Err("ws:// and wss:// URLs run a single WebSocket handshake and cannot be combined with --http3, --follow-alt-svc, --h2c-upgrade, --compare-http-versions, --requests, --h2-streams, --probe-encodings or --revalidate".to_string());
+    }
+    let config = if alpn_set { config } else { config.with_alpn(vec!["http/1.1".to_string()]) };
+    Ok(config.with_websocket(options))
+}
+
+fn parse_tcping_args(args: &[String]) -> Result<(String, u16, Config), String> {
+    let mut target = None;
+    let mut config = Config::from_env();
+    let mut count = config.tcping_count;
+    let mut interval = config.tcping_interval;
+    let mut tls = false;
+
+    let mut iter = args.iter().skip(2);
+    while let Some(arg) = iter.next() {
+        if arg == "--json" || arg == "-j" {
+            config = config.with_json(true);
+        } else if arg == "--count" || arg == "-c" {
+            let value = option_value(&mut iter, arg)?;
+            count = parse_bounded(arg, value, 1, MAX_TCPING_COUNT)? as usize;
+        } else if arg == "--interval" || arg == "-i" {
+            let value = option_value(&mut iter, arg)?;
+            interval = parse_duration(value).ok_or_else(|| format!("invalid --interval value '{}': expected e.g. 500ms or 1s", value))?;
+        } else if arg == "--tls" {
+            tls = true;
+        } else if arg == "--local-addr" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_local_addr(Some(parse_local_addr(value)?));
+        } else if arg == "--help" || arg == "-h" {
+            return Err(usage());
+        } else if arg.starts_with('-') {
+            return Err(format!("unknown tcping option: {}\n\n{}", arg, usage()));
+        } else if target.is_none() {
+            target = Some(arg.clone());
+        } else {
+            return Err(format!("unexpected argument: {}\n\n{}", arg, usage()));
+        }
+    }
+
+    let target = target.ok_or_else(|| format!("tcping requires a host:port target\n\n{}", usage()))?;
+    let (host, port) = parse_host_port(&target).map_err(|e| e.message)?;
+    Ok((host, port, config.with_tcping(count, interval, tls)))
+}
+
+fn parse_tls_args(args: &[String]) -> Result<(String, u16, Config), String> {
+    let mut target = None;
+    let mut config = Config::from_env().with_alpn(Vec::new());
+
+    let mut iter = args.iter().skip(2);
+    while let Some(arg) = iter.next() {
+        if arg == "--json" || arg == "-j" {
+            config = config.with_json(true);
+        } else if arg == "--starttls" {
+            let value = option_value(&mut iter, arg)?;
+            let protocol = StartTlsProtocol::parse(value)
+                .ok_or_else(|| format!("invalid --starttls value '{}': expected smtp, imap, pop3, ftp, xmpp, postgres or ldap", value))?;
+            config = config.with_starttls(Some(protocol));
+        } else if arg == "--alpn" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_alpn(parse_alpn_list(value)?);
+        } else if arg == "--warn-cert-days" {
+            let value = option_value(&mut iter, arg)?;
+            let days = value.parse().map_err(|_| format!("invalid --warn-cert-days value '{}'", value))?;
+            config = config.with_warn_cert_days(days);
+        } else if arg == "--fail-on-warning" {
+            config = config.with_fail_on_warning(true);
+        } else if arg == "--local-addr" {
+            let value = option_value(&mut iter, arg)?;
+            config = config.with_local_addr(Some(parse_local_addr(value)?));
+        } else if arg == "--help" || arg == "-h" {
+            return Err(usage());
+        } else if arg.starts_with('-') {
+            return Err(format!("unknown tls option: {}\n\n{}", arg, usage()));
+        } else if target.is_none() {
+            target = Some(arg.clone());
+        } else {
+            return Err(format!("unexpected argument: {}\n\n{}", arg, usage()));
+        }
+    }
+
+    let target = target.ok_or_else(|| format!("tls requires a host:port target\n\n{}", usage()))?;
+    let (host, port) = parse_host_port(&target).map_err(|e| e.message)?;
+    Ok((host, port, config))
+}
+
+const MAX_TCPING_COUNT: u32 = 100_000;
+const MAX_H2_WINDOW: u32 = (1 << 31) - 1;
+const MAX_H2_STREAMS: u32 = 1000;
+const MIN_SOCKET_BUFFER: u32 = 1024;
+const MAX_SOCKET_BUFFER: u32 = 64 * 1024 * 1024;
+
+fn parse_bounded(name: &str, value: &str, min: u32, max: u32) -> Result<u32, String> {
+    value.trim().parse::<u32>().ok()
+        .filter(|v| (min..=max).contains(v))
+        .ok_or_else(|| format!("invalid {} value '{}': expected {}..={}", name, value, min, max))
+}
+
+fn parse_local_addr(value: &str) -> Result<SocketAddr, String> {
+    let value = value.trim();
+    value.parse::<SocketAddr>()
+        .or_else(|_| value.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 0)))
+        .map_err(|_| format!("invalid --local-addr value '{}': expected ip, ip:port or [ipv6]:port", value))
+}
+
+fn parse_h2_path(value: &str) -> Result<String, String> {
+    if !value.starts_with('/') {
+        return Err(format!("invalid --h2-path value '{}': expected an absolute path such as /api", value));
+    }
+    Ok(value.to_string())
+}
+
+fn parse_unix_socket(value: &str) -> Result<String, String> {
+    if value.is_empty() || value == "@" {
+        return Err("--unix-socket requires a socket path or @abstract-name".to_string());
+    }
+    Ok(value.to_string())
+}
+
+fn parse_interface(value: &str) -> Result<String, String> {
+    if value.is_empty() || value.len() > 15 || value.contains(['/', ' ', '\0']) {
+        return Err(format!("invalid --interface value '{}': expected a network interface name such as eth0", value));
+    }
+    Ok(value.to_string())
+}
+
+fn parse_socket_addr(name: &str, value: &str) -> Result<SocketAddr, String> {
+    value.trim().parse()
+        .map_err(|_| format!("invalid {} value '{}': expected ip:port or [ipv6]:port", name, value))
+}
+
+fn parse_alpn_list(value: &str) -> Result<Vec<String>, String> {
+    let protocols: Vec<String> = value.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()).map(|p| p.to_string()).collect();
+    if protocols.iter().any(|p| p.len() > 255) {
+        return Err(format!("invalid --alpn value '{}': protocol names are limited to 255 bytes", value));
+    }
+    Ok(protocols)
+}
+
+fn option_value<'a>(iter: &mut impl Iterator<Item = &'a String>, name: &str) -> Result<&'a String, String> {
+    iter.next().ok_or_else(|| format!("{} requires a value\n\n{}", name, usage()))
+}
+
 fn usage() -> String {
-    "usage: udoc [--json] <URL>\n\n\
+    "usage: udoc [options] <URL>\n       \
+           udoc tcping [tcping options] <host:port>\n       \
+           udoc tls [tls options] <host:port>\n\n\
     Prints connection report: DNS/TCP/TLS/TTFB timings + cert summary.\n\n\
     Options:\n  \
-      --json, -j    Output as JSON\n\n\
+      --json, -j           Output as JSON\n  \
+      --compressed         Advertise gzip/br/zstd and decode the body\n  \
+      --probe-encodings    Request each encoding separately and report server support\n  \
+      -o, --output <file>  Save the final response body (read in full)\n  \
+      --show-body          Show a short text preview of the body\n  \
+      --body-json          Pretty-print the body when it is JSON\n  \
+      --revalidate         Repeat the request with If-None-Match/If-Modified-Since\n  \
+      --warn-cert-days <n> Warn when the certificate expires within n days [default: 14]\n  \
+      --fail-on-warning    Exit with code 9 when any warning is raised\n  \
+      --http2-prior-knowledge  Speak h2c directly on http:// URLs\n  \
+      --h2c-upgrade        Offer Upgrade: h2c on http:// URLs and report the outcome\n  \
+      --http1.1            Offer only http/1.1 in ALPN\n  \
+      --http2              Offer only h2 in ALPN\n  \
+      --alpn <list>        Comma-separated ALPN offer, e.g. h2,http/1.1 (empty disables ALPN)\n  \
+      --compare-http-versions  Run once over http/1.1 and once over h2 and diff the results\n  \
+      --http3              Use HTTP/3 over QUIC (https:// only)\n  \
+      --follow-alt-svc     Retry over HTTP/3 when Alt-Svc advertises h3\n  \
+      --ws-protocol <list> Offer WebSocket subprotocols (ws:// and wss:// URLs)\n  \
+      --ws-extensions <value>  Send Sec-WebSocket-Extensions, e.g. permessage-deflate\n  \
+      --ws-ping            Measure a ping/pong round trip after the upgrade\n  \
+      --grpc-health        Call grpc.health.v1.Health/Check over h2 for the whole server\n  \
+      --grpc-service <name>  Health-check one service (implies --grpc-health)\n  \
+      -x, --proxy <url>    Tunnel through an http://, https:// or socks5h:// proxy\n  \
+      --no-proxy <list>    Comma-separated hosts/domains that bypass the proxy\n  \
+      --unix-socket <path> Connect to a Unix socket (@name for abstract) instead of TCP\n  \
+      --local-addr <ip[:port]>  Bind outgoing TCP connections to a local address\n  \
+      --interface <name>   Bind outgoing TCP connections to a network interface (Linux)\n  \
+      --tcp-nodelay        Set TCP_NODELAY (disable Nagle)\n  \
+      --tcp-keepalive      Set SO_KEEPALIVE\n  \
+      --tcp-fastopen       Use TCP Fast Open (TCP_FASTOPEN_CONNECT, Linux)\n  \
+      --tcp-sndbuf <bytes> Set SO_SNDBUF\n  \
+      --tcp-rcvbuf <bytes> Set SO_RCVBUF\n  \
+      --proxy-protocol <v1|v2>   Send a HAProxy PROXY protocol header before TLS\n  \
+      --proxy-protocol-src <ip:port>  PROXY header source [default: local address]\n  \
+      --proxy-protocol-dst <ip:port>  PROXY header destination [default: target address]\n  \
+      --no-reuse           Open a fresh connection for every redirect hop\n  \
+      --requests <n>       Send n sequential requests over the final connection\n  \
+      --h2-streams <n>     Open one h2 connection and send n concurrent streams\n  \
+      --h2-path <path>     Path for --h2-streams (repeatable, streams cycle through them)\n  \
+      --h2-window <bytes>      HTTP/2 initial stream window [default: 65535]\n  \
+      --h2-conn-window <bytes> HTTP/2 connection window [default: 65535]\n  \
+      --h2-max-frame <bytes>   HTTP/2 max frame size [default: 16384]\n\n\
+    Assertions (exit code 8 when any fails):\n  \
+      --expect-status <codes>         e.g. 200, 2xx, 200,204\n  \
+      --expect-header <spec>          name, name=value or name~substring\n  \
+      --expect-body-contains <text>   Body must contain text\n  \
+      --max-ttfb <duration>           e.g. 300ms\n  \
+      --max-total <duration>          e.g. 1s\n  \
+      --min-cert-days <days>          Minimum certificate days left\n\n\
+    Tcping options (repeated TCP connects, no HTTP):\n  \
+      -c, --count <n>      Number of connection attempts [default: 5]\n  \
+      -i, --interval <duration>  Pause between attempts [default: 1s]\n  \
+      --tls                Complete a TLS handshake on every connection\n  \
+      --local-addr <ip[:port]>  Bind to a local address\n  \
+      --json, -j           Output as JSON\n\n\
+    Tls options (TLS handshake and certificate only, no HTTP):\n  \
+      --starttls <proto>   Upgrade a plaintext session first: smtp, imap, pop3, ftp, xmpp, postgres, ldap\n  \
+      --alpn <list>        Comma-separated ALPN offer [default: none]\n  \
+      --warn-cert-days <n> Warn when the certificate expires within n days [default: 14]\n  \
+      --fail-on-warning    Exit with code 9 when any warning is raised\n  \
+      --local-addr <ip[:port]>  Bind to a local address\n  \
+      --json, -j           Output as JSON\n\n\
     Environment:\n  \
       UDOC_TIMEOUT     Request timeout (e.g. 5s, 3000ms) [default: 5s]\n  \
       UDOC_MAX_REDIRS  Max redirects [default: 10]\n  \
       UDOC_BODY_LIMIT  Body preview limit in bytes [default: 32768]\n  \
-      UDOC_REPEAT      Repeat count for stats [default: 1]".to_string()
+      UDOC_REPEAT      Repeat count for stats [default: 1]\n  \
+      HTTPS_PROXY      Proxy URL for https:// when --proxy is not given (ALL_PROXY as fallback)\n  \
+      HTTP_PROXY       Proxy URL for http:// when --proxy is not given (ALL_PROXY as fallback)\n  \
+      NO_PROXY         Hosts that bypass the proxy when --no-proxy is not given".to_string()
 }
 
 async fn async_main(url: &str, config: Config) -> ExitCode {
     let repeat = config.repeat.max(1);
     let json_output = config.json_output;
+    let output = config.output.clone();
+    let fail_on_warning = config.fail_on_warning;
 
     let dns = match HickoryDnsResolver::new() {
         Ok(d) => d,
         Err(e) => { eprintln!("{}", e); return ExitCode::from(e.class.exit_code() as u8); }
     };
 
-    let tls = match RustlsTlsHandshaker::new() {
+    if config.compare_http_versions {
+        return run_compare_http_versions(url, config).await;
+    }
+
+    let tls = match RustlsTlsHandshaker::with_alpn(&config.alpn) {
         Ok(t) => t,
         Err(e) => { eprintln!("{}", e); return ExitCode::from(e.class.exit_code() as u8); }
     };
 
+    let quic = match QuinnQuicClient::new() {
+        Ok(q) => q,
+        Err(e) => { eprintln!("{}", e); return ExitCode::from(e.class.exit_code() as u8); }
+    };
+
     if repeat == 1 {
-        let use_case = GenerateReportUseCase::new(dns, TokioTcpDialer::new(), tls, HybridHttpClient::new(), TokioClock::new(), config);
+        let tcp = match tcp_dialer(&config) {
+            Ok(t) => t,
+            Err(e) => { eprintln!("{}", e); return ExitCode::from(e.class.exit_code() as u8); }
+        };
+        let use_case = GenerateReportUseCase::new(dns, tcp, tls, http_client(&config), quic, TokioClock::new(), config);
         match use_case.execute(url).await {
-            Ok(report) => {
+            Ok(mut report) => {
+                if let Some(ref path) = output
+                    && let Err(e) = save_body(&mut report, path) {
+                    eprintln!("{}", e);
+                    return ExitCode::from(e.class.exit_code() as u8);
+                }
                 if json_output {
                     println!("{}", JsonRenderer::new().render(&report));
                 } else {
                     print!("{}", PrettyRenderer::new().render(&report));
                 }
-                ExitCode::SUCCESS
+                report_exit_code(&report, fail_on_warning)
             }
             Err(e) => {
                 eprintln!("{}", e);
@@ -96,7 +584,7 @@ async fn async_main(url: &str, config: Config) -> ExitCode {
             }
         }
     } else {
-        run_with_stats(url, repeat, json_output, dns, tls, config).await
+        run_with_stats(url, repeat, json_output, quic, config).await
     }
 }
 
@@ -104,12 +592,9 @@ async fn run_with_stats(
     url: &str,
     repeat: usize,
     json_output: bool,
-    dns: HickoryDnsResolver,
-    tls: RustlsTlsHandshaker,
+    quic: QuinnQuicClient,
     config: Config,
 ) -> ExitCode {
-    let _ = (dns, tls);
-
     let mut total_ms_samples: Vec<f64> = Vec::with_capacity(repeat);
     let mut ttfb_ms_samples: Vec<f64> = Vec::with_capacity(repeat);
     let mut dns_ms_samples: Vec<f64> = Vec::with_capacity(repeat);
@@ -117,18 +602,22 @@ async fn run_with_stats(
     let mut tls_ms_samples: Vec<f64> = Vec::with_capacity(repeat);
     let mut last_report = None;
     let mut errors = 0;
+    let mut last_error = ErrorClass::Other;
 
     for i in 0..repeat {
-        let dns_clone = HickoryDnsResolver::new().unwrap_or_else(|_| panic!("dns init"));
-        let tls_clone = RustlsTlsHandshaker::new().unwrap_or_else(|_| panic!("tls init"));
+        // Fresh resolver, handshaker and dialer per sample so no caches or pooled connections carry over.
+        let adapters = HickoryDnsResolver::new()
+            .and_then(|dns| Ok((dns, RustlsTlsHandshaker::with_alpn(&config.alpn)?, tcp_dialer(&config)?)));
+        let (dns, tls, tcp) = match adapters {
+            Ok(a) => a,
+            Err(e) => { eprintln!("{}", e); return ExitCode::from(e.class.exit_code() as u8); }
+        };
         let cfg = Config {
-            timeout: config.timeout,
-            max_redirects: config.max_redirects,
-            body_limit: config.body_limit,
             repeat: 1,
             json_output: false,
+            ..config.clone()
         };
-        let use_case = GenerateReportUseCase::new(dns_clone, TokioTcpDialer::new(), tls_clone, HybridHttpClient::new(), TokioClock::new(), cfg);
+        let use_case = GenerateReportUseCase::new(dns, tcp, tls, http_client(&cfg), quic.clone(), TokioClock::new(), cfg);
 
         match use_case.execute(url).await {
             Ok(report) => {
@@ -143,17 +632,22 @@ async fn run_with_stats(
             }
             Err(e) => {
                 errors += 1;
+                last_error = e.class;
                 eprintln!("[{}] error: {}", i + 1, e);
             }
         }
     }
 
-    if last_report.is_none() {
+    let Some(mut report) = last_report else {
         eprintln!("all {} requests failed", repeat);
-        return ExitCode::from(1);
-    }
+        return ExitCode::from(last_error.exit_code() as u8);
+    };
 
-    let report = last_report.unwrap();
+    if let Some(ref path) = config.output
+        && let Err(e) = save_body(&mut report, path) {
+        eprintln!("{}", e);
+        return ExitCode::from(e.class.exit_code() as u8);
+    }
 
     if json_output {
         println!("{}", JsonRenderer::new().render(&report));
@@ -185,10 +679,235 @@ async fn run_with_stats(
         }
     }
 
+    report_exit_code(&report, config.fail_on_warning)
+}
+
+async fn run_compare_http_versions(url: &str, config: Config) -> ExitCode {
+    let plain_http = url.starts_with("http://");
+    let variants = [
+        ("http/1.1", Config { alpn: vec!["http/1.1".to_string()], http2_prior_knowledge: false, ..config.clone() }),
+        ("h2", Config { alpn: vec!["h2".to_string()], http2_prior_knowledge: plain_http, ..config.clone() }),
+    ];
+
+    let mut results: Vec<(&str, Result<Report, UdocError>)> = Vec::with_capacity(variants.len());
+    for (label, variant) in variants {
+        let cfg = Config { repeat: 1, json_output: false, h2c_upgrade: false, http3: false, follow_alt_svc: false, compare_http_versions: false, ..variant };
+        let result = match (HickoryDnsResolver::new(), tcp_dialer(&cfg), RustlsTlsHandshaker::with_alpn(&cfg.alpn), QuinnQuicClient::new()) {
+            (Ok(dns), Ok(tcp), Ok(tls), Ok(quic)) => {
+                GenerateReportUseCase::new(dns, tcp, tls, http_client(&cfg), quic, TokioClock::new(), cfg).execute(url).await
+            }
+            (Err(e), _, _, _) | (_, Err(e), _, _) | (_, _, Err(e), _) | (_, _, _, Err(e)) => Err(e),
+        };
+        results.push((label, result));
+    }
+
+    let (h1, h2) = (&results[0].1, &results[1].1);
+    if config.json_output {
+        let side = |r: &Result<Report, UdocError>| match r {
+            Ok(report) => serde_json::to_value(report).unwrap_or_default(),
+            Err(e) => serde_json::json!({ "error": e.to_string() }),
+        };
+        let diff = match (h1, h2) {
+            (Ok(a), Ok(b)) => serde_json::json!({
+                "status_changed": a.http.status != b.http.status,
+                "dns_ms": b.timings.dns_ms - a.timings.dns_ms,
+                "tcp_ms": b.timings.tcp_ms - a.timings.tcp_ms,
+                "tls_ms": b.timings.tls_ms.unwrap_or(0.0) - a.timings.tls_ms.unwrap_or(0.0),
+                "ttfb_ms": b.timings.ttfb_ms - a.timings.ttfb_ms,
+                "total_ms": b.timings.total_ms - a.timings.total_ms,
+            }),
+            _ => serde_json::Value::Null,
+        };
+        let out = serde_json::json!({ "compare": { "http1": side(h1), "http2": side(h2), "diff": diff } });
+        println!("{}", serde_json::to_string_pretty(&out).unwrap_or_default());
+    } else {
+        print!("{}", render_comparison(&results));
+    }
+
+    for (_, result) in &results {
+        match result {
+            Err(e) => return ExitCode::from(e.class.exit_code() as u8),
+            Ok(report) => {
+                let code = report_exit_code(report, config.fail_on_warning);
+                if code != ExitCode::SUCCESS { return code; }
+            }
+        }
+    }
     ExitCode::SUCCESS
 }
 
-fn percentile(samples: &mut Vec<f64>, p: usize) -> f64 {
+type TimingRow = (&'static str, fn(&Report) -> f64);
+
+fn render_comparison(results: &[(&str, Result<Report, UdocError>)]) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("{:<26}", "COMPARE"));
+    for (label, _) in results {
+        out.push_str(&format!("{:>16}", label));
+    }
+    out.push_str(&format!("{:>12}\n", "diff"));
+
+    let reports: Vec<Option<&Report>> = results.iter().map(|(_, r)| r.as_ref().ok()).collect();
+    let text_row = |label: &str, value: &dyn Fn(&Report) -> String| {
+        let mut row = format!("  {:<24}", label);
+        for report in &reports {
+            row.push_str(&format!("{:>16}", report.map(value).unwrap_or_else(|| "error".to_string())));
+        }
+        row.push('\n');
+        row
+    };
+    out.push_str(&text_row("status:", &|r| r.http.status.to_string()));
+    out.push_str(&text_row("ver:", &|r| r.http.version.clone()));
+    out.push_str(&text_row("alpn:", &|r| r.tls.as_ref().and_then(|t| t.alpn.clone()).unwrap_or_else(|| "-".to_string())));
+
+    let timing_rows: [TimingRow; 5] = [
+        ("dns:", |r| r.timings.dns_ms),
+        ("tcp:", |r| r.timings.tcp_ms),
+        ("tls:", |r| r.timings.tls_ms.unwrap_or(0.0)),
+        ("ttfb:", |r| r.timings.ttfb_ms),
+        ("total:", |r| r.timings.total_ms),
+    ];
+    for (label, value) in timing_rows {
+        let mut row = format!("  {:<24}", label);
+        for report in &reports {
+            match report {
+                Some(r) => row.push_str(&format!("{:>13.1} ms", value(r))),
+                None => row.push_str(&format!("{:>16}", "error")),
+            }
+        }
+        if let [Some(a), Some(b)] = reports[..] {
+            row.push_str(&format!("{:>+9.1} ms", value(b) - value(a)));
+        }
+        row.push('\n');
+        out.push_str(&row);
+    }
+
+    if let [Some(a), Some(b)] = reports[..]
+        && a.http.status != b.http.status {
+        out.push_str(&format!("\n  status differs: {} over {} vs {} over {}\n", a.http.status, a.http.version, b.http.status, b.http.version));
+    }
+    for (label, result) in results {
+        if let Err(e) = result {
+            out.push_str(&format!("\n  {} failed: {}\n", label, e));
+        }
+    }
+    out
+}
+
+type Dialer = ProxyProtocolDialer<UnixSocketDialer<ProxyTcpDialer<TokioTcpDialer, HickoryDnsResolver, RustlsTlsHandshaker>>>;
+
+async fn tcping_main(host: &str, port: u16, config: Config) -> ExitCode {
+    let json_output = config.json_output;
+    let dns = match HickoryDnsResolver::new() {
+        Ok(d) => d,
+        Err(e) => { eprintln!("{}", e); return ExitCode::from(e.class.exit_code() as u8); }
+    };
+    let tls = match RustlsTlsHandshaker::with_alpn(&[]) {
+        Ok(t) => t,
+        Err(e) => { eprintln!("{}", e); return ExitCode::from(e.class.exit_code() as u8); }
+    };
+    let tcp = TokioTcpDialer::new().with_local_addr(config.local_addr);
+    let use_case = TcpingUseCase::new(dns, tcp, tls, TokioClock::new(), config);
+
+    let pretty = PrettyRenderer::new();
+    let result = use_case.execute(host, port, |summary, attempt| {
+        if json_output { return; }
+        if attempt.seq == 1 {
+            print!("{}", pretty.render_tcping_header(summary));
+        }
+        print!("{}", pretty.render_tcping_attempt(attempt));
+    }).await;
+
+    match result {
+        Ok(summary) => {
+            if json_output {
+                println!("{}", JsonRenderer::new().render_tcping(&summary));
+            } else {
+                print!("{}", pretty.render_tcping(&summary));
+            }
+            match summary.failure_class() {
+                Some(class) => ExitCode::from(class.exit_code() as u8),
+                None => ExitCode::SUCCESS,
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(e.class.exit_code() as u8)
+        }
+    }
+}
+
+async fn tls_main(host: &str, port: u16, config: Config) -> ExitCode {
+    let json_output = config.json_output;
+    let fail_on_warning = config.fail_on_warning;
+    let dns = match HickoryDnsResolver::new() {
+        Ok(d) => d,
+        Err(e) => { eprintln!("{}", e); return ExitCode::from(e.class.exit_code() as u8); }
+    };
+    let tls = match RustlsTlsHandshaker::with_alpn(&config.alpn) {
+        Ok(t) => t,
+        Err(e) => { eprintln!("{}", e); return ExitCode::from(e.class.exit_code() as u8); }
+    };
+    let tcp = TokioTcpDialer::new().with_local_addr(config.local_addr);
+    let use_case = TlsProbeUseCase::new(dns, tcp, PlaintextStartTls::new(), tls, TokioClock::new(), config);
+
+    match use_case.execute(host, port).await {
+        Ok(report) => {
+            if json_output {
+                println!("{}", JsonRenderer::new().render_tls_probe(&report));
+            } else {
+                print!("{}", PrettyRenderer::new().render_tls_probe(&report));
+            }
+            if fail_on_warning && !report.warnings.is_empty() {
+                ExitCode::from(ErrorClass::Warning.exit_code() as u8)
+            } else {
+                ExitCode::SUCCESS
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(e.class.exit_code() as u8)
+        }
+    }
+}
+
+fn tcp_dialer(config: &Config) -> Result<Dialer, UdocError> {
+    let tokio_dialer = TokioTcpDialer::new()
+        .with_local_addr(config.local_addr)
+        .with_interface(config.interface.clone())
+        .with_options(config.tcp_options);
+    let proxy = ProxyTcpDialer::new(tokio_dialer, HickoryDnsResolver::new()?, RustlsTlsHandshaker::with_alpn(&[])?)
+        .with_proxy(config.proxy.clone(), config.no_proxy.clone());
+    let unix = UnixSocketDialer::new(proxy).with_path(config.unix_socket.clone());
+    Ok(ProxyProtocolDialer::new(unix).with_spec(config.proxy_protocol))
+}
+
+fn http_client(config: &Config) -> HybridHttpClient {
+    HybridHttpClient::new().with_h2_windows(config.h2_stream_window, config.h2_connection_window, config.h2_max_frame_size)
+}
+
+fn report_exit_code(report: &Report, fail_on_warning: bool) -> ExitCode {
+    if report.assertions_failed() > 0 {
+        ExitCode::from(ErrorClass::Assertion.exit_code() as u8)
+    } else if fail_on_warning && !report.warnings.is_empty() {
+        ExitCode::from(ErrorClass::Warning.exit_code() as u8)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn save_body(report: &mut Report, path: &str) -> Result<(), UdocError> {
+    if report.encoding.as_ref().is_some_and(|enc| enc.decode_limit_hit) {
+        return Err(UdocError::other(format!("decoded body exceeds the 64 MiB decode limit; not writing a partial file to {}", path)));
+    }
+    std::fs::write(path, &report.body_bytes)
+        .map_err(|e| UdocError::other(format!("failed to write body to {}: {}", path, e)))?;
+    if let Some(ref mut body) = report.body {
+        body.saved_to = Some(path.to_string());
+    }
+    Ok(())
+}
+
+fn percentile(samples: &mut [f64], p: usize) -> f64 {
     if samples.is_empty() { return 0.0; }
     samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
     let idx = (samples.len() * p / 100).min(samples.len() - 1);
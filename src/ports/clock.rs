@@ -4,6 +4,8 @@ use crate::domain::UdocError;
 pub trait Clock: Send + Sync {
     fn now(&self) -> Instant;
 
+    fn sleep(&self, duration: Duration) -> impl std::future::Future<Output = ()> + Send;
+
     fn timeout<F, T>(&self, duration: Duration, future: F) -> impl std::future::Future<Output = Result<T, UdocError>> + Send
     where
         F: std::future::Future<Output = T> + Send,
@@ -1,26 +1,116 @@
-use crate::domain::{HttpSummary, UdocError};
+use crate::domain::{ExchangeTiming, H2Session, HeaderList, HttpSummary, MultiplexProbe, ProtocolUpgrade, UdocError, WebSocketOptions, WebSocketUpgrade};
 use super::io::BoxedIoStream;
 
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: String,
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+    pub is_https: bool,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub body_limit: usize,
+    pub keep_alive: bool,
+}
+
+impl HttpRequest {
+    pub fn get(host: &str, port: u16, path: &str, is_https: bool, body_limit: usize) -> Self {
+        Self {
+            method: "GET".to_string(),
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+            is_https,
+            headers: Vec::new(),
+            body: Vec::new(),
+            body_limit,
+            keep_alive: false,
+        }
+    }
+
+    pub fn with_keep_alive(mut self, keep_alive: bool) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    pub fn with_body(mut self, method: &str, body: Vec<u8>) -> Self {
+        self.method = method.to_string();
+        self.body = body;
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Methods that may be re-sent after a failure without repeating a side effect (RFC 9110 §9.2.2).
+    pub fn is_idempotent(&self) -> bool {
+        matches!(self.method.as_str(), "GET" | "HEAD" | "OPTIONS" | "TRACE" | "PUT" | "DELETE")
+    }
+
+    /// Read-only methods (RFC 9110 §9.2.1); the only ones worth sending as replayable early data.
+    pub fn is_safe(&self) -> bool {
+        matches!(self.method.as_str(), "GET" | "HEAD" | "OPTIONS" | "TRACE")
+    }
+
+    pub fn authority(&self) -> String {
+        let default_port = if self.is_https { 443 } else { 80 };
+        if self.port == default_port { self.host.clone() } else { format!("{}:{}", self.host, self.port) }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ResponseHeaders {
     pub location: Option<String>,
     pub server: Option<String>,
     pub content_type: Option<String>,
     pub content_length: Option<u64>,
+    pub content_encoding: Option<String>,
     pub transfer_encoding: Option<String>,
+    pub all: HeaderList,
+}
+
+pub struct H2Handle(pub Box<dyn std::any::Any + Send>);
+
+pub enum PooledConnection {
+    H1(BoxedIoStream),
+    H2(H2Handle),
 }
 
 pub struct HttpResponse {
     pub summary: HttpSummary,
     pub headers: ResponseHeaders,
-    pub ttfb_ms: f64,
+    pub timing: ExchangeTiming,
     pub body_preview: Vec<u8>,
+    pub body_truncated: bool,
+    pub trailers: HeaderList,
+    pub upgrade: Option<ProtocolUpgrade>,
+    pub websocket: Option<WebSocketUpgrade>,
+    pub h2: Option<H2Session>,
+    pub connection: Option<PooledConnection>,
 }
 
 pub trait HttpClient: Send + Sync {
-    fn request_h1(&self, stream: BoxedIoStream, method: &str, host: &str, port: u16, path: &str, is_https: bool, body_limit: usize)
+    fn request_h1(&self, stream: BoxedIoStream, request: &HttpRequest)
+        -> impl std::future::Future<Output = Result<HttpResponse, UdocError>> + Send;
+
+    fn request_h2(&self, stream: BoxedIoStream, request: &HttpRequest)
+        -> impl std::future::Future<Output = Result<HttpResponse, UdocError>> + Send;
+
+    fn request_h2c_upgrade(&self, stream: BoxedIoStream, request: &HttpRequest)
+        -> impl std::future::Future<Output = Result<HttpResponse, UdocError>> + Send;
+
+    fn request_h2_multiplex(&self, stream: BoxedIoStream, requests: &[HttpRequest])
+        -> impl std::future::Future<Output = Result<MultiplexProbe, UdocError>> + Send;
+
+    fn request_websocket_h1(&self, stream: BoxedIoStream, request: &HttpRequest, options: &WebSocketOptions)
+        -> impl std::future::Future<Output = Result<HttpResponse, UdocError>> + Send;
+
+    fn request_websocket_h2(&self, stream: BoxedIoStream, request: &HttpRequest, options: &WebSocketOptions)
         -> impl std::future::Future<Output = Result<HttpResponse, UdocError>> + Send;
 
-    fn request_h2(&self, stream: BoxedIoStream, method: &str, host: &str, port: u16, path: &str, body_limit: usize)
+    fn request_pooled(&self, connection: PooledConnection, request: &HttpRequest)
         -> impl std::future::Future<Output = Result<HttpResponse, UdocError>> + Send;
 }
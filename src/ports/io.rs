@@ -4,6 +4,9 @@ use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
 pub trait IoStream: AsyncRead + AsyncWrite + Unpin + Send {}
 
+#[cfg(test)]
+impl IoStream for tokio::io::DuplexStream {}
+
 pub struct BoxedIoStream(pub Box<dyn IoStream>);
 
 impl AsyncRead for BoxedIoStream {
@@ -5,11 +5,15 @@ mod http;
 mod clock;
 mod renderer;
 mod io;
+mod quic;
+mod starttls;
 
 pub use dns::DnsResolver;
-pub use tcp::{TcpDialer, TcpConnection};
+pub use tcp::{SharedTcpInfoProbe, TcpConnection, TcpDialer, TcpInfoProbe};
 pub use tls::{TlsHandshaker, TlsSession};
-pub use http::{HttpClient, HttpResponse, ResponseHeaders};
+pub use http::{H2Handle, HttpClient, HttpRequest, HttpResponse, PooledConnection, ResponseHeaders};
 pub use clock::Clock;
 pub use renderer::Renderer;
 pub use io::{IoStream, BoxedIoStream};
+pub use quic::{QuicClient, QuicExchange};
+pub use starttls::StartTlsNegotiator;
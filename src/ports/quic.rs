@@ -0,0 +1,15 @@
+use std::net::IpAddr;
+use crate::domain::{QuicSummary, TlsSummary, UdocError};
+use super::http::{HttpRequest, HttpResponse};
+
+pub struct QuicExchange {
+    pub quic: QuicSummary,
+    pub tls: TlsSummary,
+    pub peer_certs: Vec<Vec<u8>>,
+    pub response: HttpResponse,
+}
+
+pub trait QuicClient: Send + Sync {
+    fn request_h3(&self, ip: IpAddr, port: u16, request: &HttpRequest)
+        -> impl std::future::Future<Output = Result<QuicExchange, UdocError>> + Send;
+}
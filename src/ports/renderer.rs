@@ -1,5 +1,9 @@
-use crate::domain::Report;
+use crate::domain::{Report, TcpingSummary, TlsProbeReport};
 
 pub trait Renderer: Send + Sync {
     fn render(&self, report: &Report) -> String;
+
+    fn render_tcping(&self, summary: &TcpingSummary) -> String;
+
+    fn render_tls_probe(&self, report: &TlsProbeReport) -> String;
 }
@@ -0,0 +1,7 @@
+use crate::domain::{StartTlsOutcome, StartTlsProtocol, UdocError};
+use super::io::BoxedIoStream;
+
+pub trait StartTlsNegotiator: Send + Sync {
+    fn negotiate(&self, stream: &mut BoxedIoStream, protocol: StartTlsProtocol, host: &str)
+        -> impl std::future::Future<Output = Result<StartTlsOutcome, UdocError>> + Send;
+}
@@ -1,12 +1,37 @@
-use std::net::IpAddr;
-use crate::domain::UdocError;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use crate::domain::{ProxyProtocolHeader, ProxyTiming, TcpInfo, TcpStats, UdocError};
 use super::io::BoxedIoStream;
 
 pub struct TcpConnection {
     pub stream: BoxedIoStream,
     pub tcp_ms: f64,
+    /// The connect returned before the handshake (TCP Fast Open), so `tcp_ms` is near zero
+    /// and the round trip shows up in the first write instead.
+    pub tcp_deferred: bool,
+    pub proxy: Option<ProxyTiming>,
+    pub unix_socket: Option<String>,
+    pub local_addr: Option<SocketAddr>,
+    pub tcp_stats: Option<TcpStats>,
+    pub tcp_probe: Option<SharedTcpInfoProbe>,
+    pub proxy_protocol: Option<ProxyProtocolHeader>,
 }
 
+pub trait TcpInfoProbe: Send + Sync {
+    fn sample(&self) -> Option<TcpInfo>;
+}
+
+pub type SharedTcpInfoProbe = Arc<dyn TcpInfoProbe>;
+
 pub trait TcpDialer: Send + Sync {
     fn connect(&self, ip: IpAddr, port: u16) -> impl std::future::Future<Output = Result<TcpConnection, UdocError>> + Send;
+
+    fn tunnels(&self, _host: &str, _port: u16) -> bool {
+        false
+    }
+
+    fn connect_tunnel(&self, host: &str, port: u16) -> impl std::future::Future<Output = Result<TcpConnection, UdocError>> + Send {
+        let message = format!("no tunnel configured for {}:{}", host, port);
+        async move { Err(UdocError::tcp(message)) }
+    }
 }